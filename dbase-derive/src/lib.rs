@@ -0,0 +1,269 @@
+//! `#[derive(DBaseRecord)]` for the converter's xbase module.
+//!
+//! Deriving `DBaseRecord` on a struct with named fields implements both
+//! `xbase::DBaseRecord` (to write it as a table record) and `xbase::FromRecord` (to read it back),
+//! so the table layout is declared exactly once, by the struct's fields:
+//!
+//! ```ignore
+//! #[derive(Default, DBaseRecord)]
+//! #[dbase(ignore = "RODEO_NUM")]
+//! pub struct PersonRecord {
+//!     #[dbase(name = "IGRA_NUM", length = 4)]
+//!     pub igra_number: String,
+//!     #[dbase(name = "EXT_DOLLAR", length = 7, decimals = 2)]
+//!     pub ext_dollars: Decimal,
+//!     #[dbase(skip)]
+//!     pub scratch: String,
+//! }
+//! ```
+//!
+//! Each field's column is described by its `#[dbase(...)]` attribute:
+//! - `name`: the column name; defaults to the field's name in upper case.
+//! - `field_type`: a `FieldType` variant; defaults to the one for the field's Rust type (see `DBaseField`).
+//! - `length`: the column width, which is required.
+//! - `decimals`: the number of decimal places in `Numeric` columns; defaults to 0.
+//! - `skip`: leave this field out of the table; it's set to its default value when reading.
+//! - `columns`: a type implementing `xbase::ColumnGroup` for the field,
+//!   which stores it in any number of columns; no other options are allowed with it.
+//!
+//! Columns are written in the order of the struct's fields.
+//! When reading, columns may come in any order, and missing columns keep their default values,
+//! so the struct must implement `Default`.
+//! Columns not named by a field are offered to each `columns` group, in field order.
+//! Unknown columns are an error, unless they're listed with `ignore` on the struct;
+//! `FromRecord::from_record_lenient` reports them as warnings instead.
+//!
+//! The generated code refers to `crate::xbase`, so it's only meant for use inside the converter.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, Ident, LitInt, LitStr};
+
+/// dBASE column names hold at most 10 characters, plus a NUL terminator.
+const MAX_NAME_LEN: usize = 10;
+
+#[proc_macro_derive(DBaseRecord, attributes(dbase))]
+pub fn derive_dbase_record(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+/// A struct field that's stored in a table column.
+struct Column {
+    field: Ident,
+    ty: syn::Type,
+    name: String,
+    field_type: Option<Ident>,
+    length: u8,
+    decimals: u8,
+}
+
+/// A struct field that's stored in the columns of a `ColumnGroup`.
+struct Group {
+    field: Ident,
+    group: syn::Path,
+}
+
+/// How a struct field is stored, in field order.
+enum Stored {
+    Column(Box<Column>),
+    Group(Group),
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(s) => match &s.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(syn::Error::new_spanned(&input.ident, "DBaseRecord requires named fields")),
+        },
+        _ => return Err(syn::Error::new_spanned(&input.ident, "DBaseRecord can only be derived for structs")),
+    };
+
+    let mut ignored = Vec::new();
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("dbase")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("ignore") {
+                ignored.push(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else {
+                Err(meta.error("expected `ignore = \"COLUMN\"`"))
+            }
+        })?;
+    }
+
+    let mut stored = Vec::new();
+    for f in fields {
+        let ident = f.ident.clone().expect("named fields have identifiers");
+        let mut skip = false;
+        let mut group = None;
+        let mut name = None;
+        let mut field_type = None;
+        let mut length = None;
+        let mut decimals = 0;
+
+        for attr in f.attrs.iter().filter(|a| a.path().is_ident("dbase")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    skip = true;
+                } else if meta.path.is_ident("name") {
+                    let lit = meta.value()?.parse::<LitStr>()?;
+                    if lit.value().is_empty() || lit.value().len() > MAX_NAME_LEN || !lit.value().is_ascii() {
+                        return Err(syn::Error::new_spanned(lit, "column names must be 1 to 10 ASCII characters"));
+                    }
+                    name = Some(lit.value());
+                } else if meta.path.is_ident("field_type") {
+                    field_type = Some(meta.value()?.parse::<Ident>()?);
+                } else if meta.path.is_ident("length") {
+                    length = Some(meta.value()?.parse::<LitInt>()?.base10_parse::<u8>()?);
+                } else if meta.path.is_ident("decimals") {
+                    decimals = meta.value()?.parse::<LitInt>()?.base10_parse::<u8>()?;
+                } else if meta.path.is_ident("columns") {
+                    group = Some(meta.value()?.parse::<syn::Path>()?);
+                } else {
+                    return Err(meta.error("expected `name`, `field_type`, `length`, `decimals`, `columns`, or `skip`"));
+                }
+                Ok(())
+            })?;
+        }
+
+        if skip {
+            continue;
+        }
+        if let Some(group) = group {
+            if name.is_some() || field_type.is_some() || length.is_some() || decimals != 0 {
+                return Err(syn::Error::new_spanned(&ident, "`columns` can't be combined with other options"));
+            }
+            stored.push(Stored::Group(Group { field: ident, group }));
+            continue;
+        }
+
+        let name = match name {
+            Some(name) => name,
+            None => {
+                let name = ident.to_string().to_ascii_uppercase();
+                if name.len() > MAX_NAME_LEN {
+                    return Err(syn::Error::new_spanned(&ident, "field name is too long for a column; set `name`"));
+                }
+                name
+            }
+        };
+        let length = length.ok_or_else(|| syn::Error::new_spanned(&ident, "missing `#[dbase(length = N)]`"))?;
+
+        stored.push(Stored::Column(Box::new(Column { field: ident, ty: f.ty.clone(), name, field_type, length, decimals })));
+    }
+
+    let xbase = quote!(crate::xbase);
+    let field_type = |c: &Column| match &c.field_type {
+        Some(t) => quote!(#xbase::FieldType::#t),
+        None => {
+            let ty = &c.ty;
+            quote!(<#ty as #xbase::DBaseField>::FIELD_TYPE)
+        }
+    };
+
+    let descriptors = stored.iter().map(|s| match s {
+        Stored::Column(c) => {
+            let Column { name, length, decimals, .. } = &**c;
+            let length = *length as usize;
+            let field_type = field_type(c);
+            quote! {
+                fields.push(#xbase::FieldDescriptor {
+                    name: #name.to_string(),
+                    field_type: #field_type,
+                    length: #length,
+                    decimal_count: #decimals,
+                    work_area_id: 0,
+                    example: 1,
+                });
+            }
+        }
+        Stored::Group(Group { group, .. }) => {
+            quote!(fields.extend(<#group as #xbase::ColumnGroup>::describe());)
+        }
+    });
+
+    let values = stored.iter().map(|s| match s {
+        Stored::Column(c) => {
+            let field = &c.field;
+            let field_type = field_type(c);
+            quote!(data.push(#xbase::DBaseField::to_field(&self.#field, &#field_type));)
+        }
+        Stored::Group(Group { field, group }) => {
+            quote!(<#group as #xbase::ColumnGroup>::to_fields(&self.#field, &mut data);)
+        }
+    });
+
+    let assignments = stored.iter().filter_map(|s| match s {
+        Stored::Column(c) => {
+            let Column { field, name, .. } = &**c;
+            Some(quote! {
+                #name => {
+                    record.#field = #xbase::DBaseField::from_field(value.value)
+                        .map_err(|v| #xbase::DBaseErrorKind::UnexpectedValue(#name.to_string(), v))?;
+                }
+            })
+        }
+        Stored::Group(_) => None,
+    });
+
+    let groups = stored.iter().filter_map(|s| match s {
+        Stored::Group(Group { field, group }) => Some(quote! {
+            let Some(value) = <#group as #xbase::ColumnGroup>::from_field(&mut record.#field, value)? else {
+                continue;
+            };
+        }),
+        Stored::Column(_) => None,
+    });
+
+    let ignored = ignored.iter().map(|name| LitStr::new(name, Span::call_site())).collect::<Vec<_>>();
+    let ignored = if ignored.is_empty() { quote!() } else { quote!(#(#ignored)|* => {}) };
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #xbase::DBaseRecord for #ident #ty_generics #where_clause {
+            fn describe(&self) -> Vec<#xbase::FieldDescriptor> {
+                let mut fields = ::std::vec::Vec::new();
+                #(#descriptors)*
+                fields
+            }
+
+            fn to_record(&self) -> Vec<#xbase::Field> {
+                let mut data = ::std::vec::Vec::new();
+                #(#values)*
+                data
+            }
+        }
+
+        impl #impl_generics #xbase::FromRecord for #ident #ty_generics #where_clause {
+            fn from_record(fields: #xbase::FieldIterator) -> #xbase::DBaseResult<Self> {
+                let mut warnings = ::std::vec::Vec::new();
+                let record = Self::from_record_lenient(fields, &mut warnings)?;
+                match warnings.into_iter().next() {
+                    Some(warning) => Err(warning.into()),
+                    None => Ok(record),
+                }
+            }
+
+            fn from_record_lenient(
+                fields: #xbase::FieldIterator,
+                warnings: &mut ::std::vec::Vec<#xbase::SchemaWarning>,
+            ) -> #xbase::DBaseResult<Self> {
+                let mut record = <Self as ::std::default::Default>::default();
+                for value in fields {
+                    let value = value?;
+                    match value.name {
+                        #(#assignments)*
+                        #ignored
+                        _ => {
+                            #(#groups)*
+                            warnings.push(#xbase::SchemaWarning::UnexpectedColumn(value.name.to_string()));
+                        }
+                    }
+                }
+                Ok(record)
+            }
+        }
+    })
+}
@@ -0,0 +1,33 @@
+//! Read a single field value of any type from arbitrary bytes.
+//! The first three bytes pick the field type, decimal count, and codepage;
+//! the rest are the field's data.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// The xbase module refers to itself as `crate::xbase`.
+#[path = "../../src"]
+mod converter {
+    #[allow(dead_code, unused_imports)]
+    pub mod xbase;
+}
+use converter::xbase;
+
+use xbase::codepage::Codepage;
+use xbase::{FieldDescriptor, FIELD_TYPES};
+
+fuzz_target!(|data: &[u8]| {
+    let [field_type, decimal_count, language_driver, data @ ..] = data else {
+        return;
+    };
+    let field = FieldDescriptor {
+        name: "FUZZ".to_string(),
+        field_type: FIELD_TYPES[*field_type as usize % FIELD_TYPES.len()].clone(),
+        length: data.len(),
+        decimal_count: *decimal_count,
+        work_area_id: 0,
+        example: 1,
+    };
+    let codepage = Codepage::from_language_driver(*language_driver).unwrap_or_default();
+    let _ = field.read_field(data, codepage);
+});
@@ -0,0 +1,15 @@
+//! Read a whole table, including deleted records, from arbitrary bytes.
+//! Errors are fine, but the reader must not panic or loop forever.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// The xbase module refers to itself as `crate::xbase`.
+#[path = "../../src"]
+mod converter {
+    #[allow(dead_code, unused_imports)]
+    pub mod xbase;
+}
+use converter::xbase;
+
+fuzz_target!(|data: &[u8]| xbase::read_everything(data));
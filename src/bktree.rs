@@ -1,4 +1,5 @@
 use std::cmp::Ordering;
+use std::collections::BinaryHeap;
 use std::fmt;
 use std::ops::Sub;
 
@@ -8,6 +9,7 @@ pub trait Metric<Rhs = Self> {
     fn dist(&self, x: &Rhs) -> Self::Output;
 }
 
+#[derive(Clone)]
 pub struct BKTree<T, O>
     where
         O: Ord + Copy + Sub<Output=O>,
@@ -16,6 +18,8 @@ pub struct BKTree<T, O>
     root: Option<BKTreeNode<T, O>>,
     /// Number of entries in the tree.
     size: usize,
+    /// Number of nodes whose entries were removed, but which are kept to find the others.
+    n_removed: usize,
 }
 
 impl<T, O> fmt::Debug for BKTree<T, O>
@@ -44,13 +48,6 @@ impl<T, O> BKTree<T, O>
         O: Ord + Copy + Sub<Output=O>,
         T: Metric<Output=O>,
 {
-    pub fn new() -> Self {
-        BKTree {
-            root: None,
-            size: 0,
-        }
-    }
-
     pub fn insert(&mut self, item: T) {
         if let Some(ref mut r) = self.root {
             r.insert(item);
@@ -60,6 +57,48 @@ impl<T, O> BKTree<T, O>
         self.size += 1;
     }
 
+    /// Remove an item from the tree, returning whether it was found.
+    ///
+    /// Other entries may be found through the item's node, so it's only marked removed,
+    /// and searches skip it. Once removed nodes outnumber the entries, the tree is rebuilt without them.
+    pub fn remove(&mut self, item: &T) -> bool
+        where
+            T: PartialEq,
+    {
+        let Some(marked) = self.root.as_mut().and_then(|r| r.remove(item)) else {
+            return false;
+        };
+        self.size -= 1;
+        if marked {
+            self.n_removed += 1;
+            if self.n_removed > self.size {
+                self.compact();
+            }
+        }
+        true
+    }
+
+    /// Replace an item with a new one, such as when the value it's measured by changes.
+    ///
+    /// Returns whether the old item was found; the new one is inserted either way.
+    pub fn update(&mut self, old: &T, new: T) -> bool
+        where
+            T: PartialEq,
+    {
+        let removed = self.remove(old);
+        self.insert(new);
+        removed
+    }
+
+    /// Rebuild the tree without the nodes of removed entries.
+    pub fn compact(&mut self) {
+        let mut items = Vec::with_capacity(self.size);
+        if let Some(r) = self.root.take() {
+            r.into_values(&mut items);
+        }
+        *self = items.into_iter().collect();
+    }
+
     /// Find elements within a certain distance of the given element.
     #[allow(dead_code)]
     pub fn find<S>(&self, item: &S, max_dist: O) -> Vec<(O, &T)>
@@ -85,12 +124,35 @@ impl<T, O> BKTree<T, O>
                 "Processed {cnt} of {total} nodes and found {v_len} items.",
                 total=self.size, v_len=v.len()
             );
-            return v;
+            v
         } else {
             vec![]
         }
     }
 
+    /// Find the `k` elements closest to an item, using the given `dist` function like `find_by`,
+    /// along with any others as close as the farthest of them.
+    ///
+    /// Rather than needing a maximum distance, the search narrows as it finds closer elements,
+    /// so it only visits the parts of the tree that could hold something closer.
+    /// The results are sorted by distance.
+    pub fn find_k_nearest<F>(&self, k: usize, dist: F) -> Vec<(O, &T)>
+        where
+            F: Fn(&T) -> O
+    {
+        match &self.root {
+            Some(r) if k > 0 => {
+                let (cnt, v) = r.find_k_nearest(k, dist);
+                log::debug!(
+                    "Processed {cnt} of {total} nodes and found {v_len} items.",
+                    total=self.size, v_len=v.len()
+                );
+                v
+            }
+            _ => vec![],
+        }
+    }
+
     pub fn find_closest<F>(&self, max_dist: O, dist: F) -> Option<(O, &T)>
         where
             F: Fn(&T) -> O
@@ -104,17 +166,55 @@ impl<T, O> BKTree<T, O>
 }
 
 
+/// Builds a tree from a collection of items all at once.
+///
+/// Rather than inserting them in order, which can make a lopsided tree
+/// when the items are sorted (e.g. by IGRA number), this picks the root of each subtree
+/// to spread the others across as many distances as it can,
+/// so the tree is wide and shallow and searches visit fewer nodes.
+impl<T, O> Default for BKTree<T, O>
+    where
+        O: Ord + Copy + Sub<Output=O>,
+        T: Metric<Output=O>,
+{
+    fn default() -> Self {
+        BKTree { root: None, size: 0, n_removed: 0 }
+    }
+}
+
+impl<T, O> FromIterator<T> for BKTree<T, O>
+    where
+        O: Ord + Copy + Sub<Output=O>,
+        T: Metric<Output=O>,
+{
+    fn from_iter<I: IntoIterator<Item=T>>(iter: I) -> Self {
+        let items: Vec<T> = iter.into_iter().collect();
+        BKTree {
+            size: items.len(),
+            root: BKTreeNode::build(items),
+            n_removed: 0,
+        }
+    }
+}
+
 /// An internal node which stores a value and a list of children and their associated distances.
 ///
 /// For `Some((dist, child)) = self.children[i]`, every descendant of `child` is `dist` from `self`.
-#[derive(Debug)]
+///
+/// Values at distance zero from `value` are kept in `same` rather than in a child,
+/// so many equal keys, like blank names, don't make a long chain of nodes.
+#[derive(Debug, Clone)]
 struct BKTreeNode<T, O>
     where
         O: Ord + Copy + Sub<Output=O>,
         T: Metric<Output=O>,
 {
     value: T,
+    /// Other values at distance zero from `value`, which are found along with it.
+    same: Vec<T>,
     children: Option<Vec<(O, BKTreeNode<T, O>)>>,
+    /// Whether `value` was removed from the tree.
+    removed: bool,
 }
 
 /// A node which enqueued for processing during a search through the tree.
@@ -167,6 +267,11 @@ impl<'a, T, O> PartialEq<Self> for ProcNode<'a, T, O>
     }
 }
 
+/// Whether a distance is zero, which any distance minus itself is, since `O` has no constant for it.
+fn is_zero<O: Copy + Sub<Output=O> + PartialEq>(d: O) -> bool {
+    d == d.sub(d)
+}
+
 /// This is the actual tree implementation.
 impl<T, O> BKTreeNode<T, O>
     where
@@ -175,7 +280,112 @@ impl<T, O> BKTreeNode<T, O>
 {
     /// Create a new BKTree rooted at T.
     fn new(root: T) -> Self {
-        BKTreeNode { value: root, children: None }
+        BKTreeNode { value: root, same: Vec::new(), children: None, removed: false }
+    }
+
+    /// Build a tree from the given items, or None if there aren't any.
+    ///
+    /// The root is chosen by `pick_root`; the rest are grouped by their distance to it,
+    /// and each group is built into one of its children the same way,
+    /// except for the ones at distance zero, which the root keeps.
+    fn build(mut items: Vec<T>) -> Option<Self> {
+        if items.is_empty() {
+            return None;
+        }
+
+        let root = items.swap_remove(Self::pick_root(&items));
+        let mut by_dist: Vec<_> = items.into_iter().map(|x| (root.dist(&x), x)).collect();
+        by_dist.sort_by_key(|(d, _)| *d);
+
+        let mut same = Vec::new();
+        let mut children = Vec::new();
+        let mut by_dist = by_dist.into_iter().peekable();
+        while let Some((d, x)) = by_dist.next() {
+            let mut group = vec![x];
+            while let Some((_, y)) = by_dist.next_if(|(d_y, _)| *d_y == d) {
+                group.push(y);
+            }
+            if is_zero(d) {
+                same = group;
+            } else {
+                children.extend(Self::build(group).map(|child| (d, child)));
+            }
+        }
+
+        let children = if children.is_empty() { None } else { Some(children) };
+        Some(BKTreeNode { value: root, same, children, removed: false })
+    }
+
+    /// Choose which item should root a tree of `items`.
+    ///
+    /// This tries a few evenly spaced candidates, and measures each one's distances to a sample of the items.
+    /// It keeps the candidate whose most common distance is the least common,
+    /// since that splits the rest most evenly among its children, keeping the tree shallow.
+    fn pick_root(items: &[T]) -> usize {
+        const CANDIDATES: usize = 16;
+        const SAMPLES: usize = 64;
+
+        let candidate_step = (items.len() / CANDIDATES).max(1);
+        let sample_step = (items.len() / SAMPLES).max(1);
+
+        let mut best = (usize::MAX, 0);
+        for c in (0..items.len()).step_by(candidate_step).take(CANDIDATES) {
+            let mut dists: Vec<_> = items.iter()
+                .step_by(sample_step)
+                .take(SAMPLES)
+                .map(|x| items[c].dist(x))
+                .collect();
+            dists.sort();
+            let largest_group = dists.chunk_by(|a, b| a == b).map(<[O]>::len).max().unwrap_or(0);
+            if largest_group < best.0 {
+                best = (largest_group, c);
+            }
+        }
+        best.1
+    }
+
+    /// Remove an item, returning `None` if it wasn't found,
+    /// or whether its node had to be kept and marked removed.
+    ///
+    /// An item can only be below the child at its distance from this node,
+    /// so this follows a single path down the tree, just as `insert` does.
+    /// A node with other values at distance zero takes one of them in place of the removed one.
+    fn remove(&mut self, item: &T) -> Option<bool>
+        where
+            T: PartialEq,
+    {
+        let mut u = self;
+        loop {
+            if !u.removed && u.value == *item {
+                match u.same.pop() {
+                    Some(x) => u.value = x,
+                    None => u.removed = true,
+                }
+                return Some(u.removed);
+            }
+
+            let k = u.value.dist(item);
+            if is_zero(k) {
+                let i = u.same.iter().position(|x| x == item)?;
+                u.same.swap_remove(i);
+                return Some(false);
+            }
+            match u.children.as_mut().and_then(|c| c.iter_mut().find(|(duv, _)| *duv == k)) {
+                Some((_, v)) => u = v,
+                None => return None,
+            }
+        }
+    }
+
+    /// Move the values of this node and its descendants that weren't removed into `items`.
+    fn into_values(self, items: &mut Vec<T>) {
+        if !self.removed {
+            items.push(self.value);
+        }
+        items.extend(self.same);
+        for (_, v) in self.children.into_iter().flatten() {
+            v.into_values(items);
+        }
     }
 
     /// Insert an item into the tree.
@@ -185,12 +395,16 @@ impl<T, O> BKTreeNode<T, O>
     /// If it finds one, it calls its `insert` method, recursively traversing the tree
     /// until it finds a node that does not yet have a child of the same distance as its distance to `item`.
     /// There, it creates a new leaf node and adds it to the tree.
+    /// An item at distance zero from a node joins that node's `same` values instead.
     fn insert(&mut self, item: T) {
         let k = self.value.dist(&item);
         // If Metric should be a proper metric (not a pseudometric),
         // (i.e., enforce the metric property that dist(x, y) == 0 <=> x == y)
-        // uncomment the line below:
-        // if k == 0 { return; }
+        // drop the item here rather than keeping it.
+        if is_zero(k) {
+            self.same.push(item);
+            return;
+        }
 
         if let Some(ref mut c) = self.children {
             match c.iter_mut().find_map(|(duv, v)| if *duv == k { Some(v) } else { None }) {
@@ -218,9 +432,10 @@ impl<T, O> BKTreeNode<T, O>
         while let Some(ProcNode { u, dist_wu, id: _ }) = s.pop() {
             cnt += 1;
 
-            if dist_wu <= max_dist {
+            if dist_wu <= max_dist && !u.removed {
                 r.push((dist_wu, &u.value));
             }
+            r.extend(u.same.iter().map(|x| (dist(x), x)).filter(|(d, _)| *d <= max_dist));
 
             // Add children that live on a hypersphere that intersects our tolerance.
             if let Some(c) = &u.children {
@@ -237,7 +452,199 @@ impl<T, O> BKTreeNode<T, O>
             }
         }
 
-        r.sort_by(|(d0, _), (d1, _)| d0.cmp(d1));
+        r.sort_by_key(|(d0, _)| *d0);
         (cnt, r)
     }
+
+    /// Find the k closest elements to the given item, and any tied with the farthest of them.
+    /// Returns (number of nodes processed, Vec<(distance to &T, &T)>).
+    ///
+    /// Nodes are expanded closest first, so the search radius shrinks as quickly as possible.
+    fn find_k_nearest<F>(&self, k: usize, dist: F) -> (usize, Vec<(O, &T)>)
+        where
+            F: Fn(&T) -> O
+    {
+        let mut s = BinaryHeap::new();
+        let mut r: Vec<(O, &T)> = Vec::new();
+
+        s.push(ProcNode { u: self, dist_wu: dist(&self.value), id: 0 });
+
+        let mut cnt = 0;
+        while let Some(ProcNode { u, dist_wu, id: _ }) = s.pop() {
+            cnt += 1;
+
+            let values = Some((dist_wu, &u.value)).filter(|_| !u.removed)
+                .into_iter()
+                .chain(u.same.iter().map(|x| (dist(x), x)));
+            for (dist_wx, x) in values {
+                // Once we have k results, nothing farther than the k-th can be added.
+                if r.len() < k || dist_wx <= r[k - 1].0 {
+                    let i = r.partition_point(|(d, _)| *d <= dist_wx);
+                    r.insert(i, (dist_wx, x));
+                    if r.len() > k {
+                        let kth = r[k - 1].0;
+                        r.truncate(r.partition_point(|(d, _)| *d <= kth));
+                    }
+                }
+            }
+
+            // Even if this node is too far, its children might not be.
+            let radius = if r.len() >= k { Some(r[k - 1].0) } else { None };
+            if let Some(c) = &u.children {
+                for (dist_uv, v) in c {
+                    let diff = if dist_wu < *dist_uv {
+                        dist_uv.sub(dist_wu)
+                    } else {
+                        dist_wu.sub(*dist_uv)
+                    };
+                    if radius.is_none_or(|radius| diff <= radius) {
+                        s.push(ProcNode { u: v, dist_wu: dist(&v.value), id: cnt });
+                    }
+                }
+            }
+        }
+
+        (cnt, r)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Numbers, with the distance between them.
+    #[derive(Debug, Clone)]
+    struct Num(i32);
+
+    impl Metric for Num {
+        type Output = u32;
+
+        fn dist(&self, x: &Self) -> u32 {
+            self.0.abs_diff(x.0)
+        }
+    }
+
+    impl PartialEq for Num {
+        fn eq(&self, other: &Self) -> bool {
+            self.0 == other.0
+        }
+    }
+
+    #[test]
+    fn k_nearest_matches_a_full_scan() {
+        let values = [50, 7, 93, 12, 12, 64, 31, 88, 3, 45, 12, 70, 19, 99, 0, 56];
+        let mut tree = BKTree::default();
+        for v in values {
+            tree.insert(Num(v));
+        }
+
+        for target in [-5, 0, 12, 13, 40, 60, 100] {
+            let mut dists: Vec<_> = values.iter().map(|v| v.abs_diff(target)).collect();
+            dists.sort();
+            for k in 1..=values.len() {
+                let found: Vec<_> = tree.find_k_nearest(k, |x| x.0.abs_diff(target))
+                    .into_iter().map(|(d, _)| d).collect();
+                let kth = dists[k - 1];
+                let expected: Vec<_> = dists.iter().copied().filter(|d| *d <= kth).collect();
+                assert_eq!(found, expected, "k = {k}, target = {target}");
+            }
+        }
+
+        assert!(tree.find_k_nearest(0, |x| x.0.abs_diff(1)).is_empty());
+        assert!(BKTree::<Num, u32>::default().find_k_nearest(3, |x| x.0.abs_diff(1)).is_empty());
+    }
+
+    /// Find every value within `max_dist` of `target`, in order.
+    fn values_near(tree: &BKTree<Num, u32>, target: i32, max_dist: u32) -> Vec<i32> {
+        let mut found: Vec<_> = tree.find_by(max_dist, |x| x.0.abs_diff(target))
+            .into_iter().map(|(_, x)| x.0).collect();
+        found.sort();
+        found
+    }
+
+    /// Four-digit codes, like IGRA numbers, with the number of digits that differ between them.
+    struct Code(String);
+
+    impl Metric for Code {
+        type Output = usize;
+
+        fn dist(&self, x: &Self) -> usize {
+            self.0.bytes().zip(x.0.bytes()).filter(|(a, b)| a != b).count()
+        }
+    }
+
+    #[test]
+    fn bulk_built_trees_are_shallower() {
+        /// The sum of every node's depth, which is the work needed to reach all of them.
+        fn total_depth(node: &BKTreeNode<Code, usize>, depth: usize) -> usize {
+            depth + node.children.iter().flatten().map(|(_, v)| total_depth(v, depth + 1)).sum::<usize>()
+        }
+
+        let mut inserted = BKTree::default();
+        (1000..9000).for_each(|n| inserted.insert(Code(n.to_string())));
+        let built: BKTree<_, _> = (1000..9000).map(|n| Code(n.to_string())).collect();
+        assert_eq!(built.size, 8000);
+        assert!(total_depth(built.root.as_ref().unwrap(), 0) < total_depth(inserted.root.as_ref().unwrap(), 0));
+
+        for target in ["1000", "4321", "8999", "0000"] {
+            let near = |tree: &BKTree<Code, usize>| {
+                let mut found: Vec<_> = tree.find_by(1, |x| x.dist(&Code(target.to_string())))
+                    .into_iter().map(|(_, x)| x.0.clone()).collect();
+                found.sort();
+                found
+            };
+            assert_eq!(near(&built), near(&inserted));
+        }
+        assert!(BKTree::<Code, usize>::default().root.is_none());
+    }
+
+    #[test]
+    fn removed_items_are_not_found() {
+        let mut tree: BKTree<_, _> = [5, 1, 9, 5, 14, 3, 7].into_iter().map(Num).collect();
+
+        // Another 5 takes the removed one's place, so only the second removal leaves a node behind.
+        assert!(tree.remove(&Num(5)));
+        assert_eq!(values_near(&tree, 5, 100), [1, 3, 5, 7, 9, 14]);
+        assert!(tree.remove(&Num(5)));
+        assert!(!tree.remove(&Num(5)));
+        assert!(!tree.remove(&Num(8)));
+        assert_eq!(values_near(&tree, 5, 100), [1, 3, 7, 9, 14]);
+        assert_eq!(tree.find_k_nearest(1, |x| x.0.abs_diff(8)).len(), 2);
+
+        assert!(tree.update(&Num(14), Num(4)));
+        assert!(!tree.update(&Num(14), Num(20)));
+        assert_eq!(values_near(&tree, 5, 100), [1, 3, 4, 7, 9, 20]);
+        assert_eq!(tree.size, 6);
+
+        // Once removed nodes outnumber the entries, the tree is rebuilt without them.
+        assert_eq!(tree.n_removed, 2);
+        assert!(tree.remove(&Num(1)));
+        assert!(tree.remove(&Num(3)));
+        assert_eq!((tree.size, tree.n_removed), (4, 4));
+        assert!(tree.remove(&Num(9)));
+        assert_eq!((tree.size, tree.n_removed), (3, 0));
+        assert_eq!(values_near(&tree, 5, 100), [4, 7, 20]);
+    }
+
+    #[test]
+    fn equal_keys_do_not_deepen_the_tree() {
+        // Lots of people share a blank name, so building, searching, and dropping
+        // a tree of equal keys has to work on a thread with a small stack.
+        let handle = std::thread::Builder::new()
+            .stack_size(2 << 20)
+            .spawn(|| {
+                let mut tree: BKTree<_, _> = (0..50_000).map(|i| Num(i % 2)).collect();
+                assert_eq!(tree.size, 50_000);
+                assert_eq!(tree.find_by(0, |x| x.0.abs_diff(1)).len(), 25_000);
+                assert_eq!(tree.find_k_nearest(3, |x| x.0.abs_diff(0)).len(), 25_000);
+
+                tree.insert(Num(1));
+                assert!(tree.remove(&Num(0)) && tree.remove(&Num(1)));
+                assert_eq!((tree.size, tree.n_removed), (49_999, 0));
+                tree.compact();
+                tree.clone().size
+            })
+            .unwrap();
+        assert_eq!(handle.join().unwrap(), 49_999);
+    }
 }
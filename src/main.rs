@@ -1,5 +1,7 @@
 mod xbase;
 mod bktree;
+mod nicknames;
+mod phonetic;
 mod robin;
 mod validation;
 mod api;
@@ -10,8 +12,10 @@ use std::fs::File;
 use std::io::{BufRead, BufReader, BufWriter};
 use std::io::Write;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
 
 use axum::response::IntoResponse;
 use axum_server::tls_rustls::RustlsConfig;
@@ -20,22 +24,25 @@ use axum::{
     handler::HandlerWithoutStateExt,
     http::{HeaderValue, StatusCode, Uri, header},
     response::Redirect,
-    routing::{post},
+    routing::{get, post},
     Json, Router, BoxError,
 };
 use axum_extra::extract::WithRejection;
 
-use log;
+use chrono::NaiveDate;
 use rand::prelude::*;
 use serde::{Serialize, Deserialize};
 use tower_http::services::ServeDir;
 use serde_json::json;
 
 use crate::api::ApiError;
+use crate::nicknames::Nicknames;
 use crate::robin::{
     Address, Association, Contestant, Date, EventID, Payment, Registration,
 };
 use crate::validation::{EntryValidator, IGRA_DIVISIONS, PersonRecord, Report, RodeoEvent};
+use crate::xbase::DBaseRecord;
+use crate::xbase::record::SchemaCheck;
 
 type MyResult<T> = Result<T, Box<dyn Error>>;
 
@@ -54,23 +61,230 @@ async fn main() -> MyResult<()> {
             do_db_gen(personnel_path)?;
         }
         "show" => {
-            let dbt = xbase::try_from_path(personnel_path)?;
+            let rest: Vec<_> = args.collect();
+            let dbt = xbase::try_from_path(&personnel_path)?;
             dbt.print_fields();
+            println!("Codepage: {:?}", dbt.codepage());
+            println!("Last updated: {}", dbt.last_updated());
+
+            // Check the table against the named layout, or the one sharing the most column names,
+            // as long as the table has most of that layout's columns.
+            let layouts = validation::layouts();
+            let layout = match rest.first() {
+                Some(name) => Some(layouts.iter().find(|l| l.name == name)
+                    .ok_or("third arg should be 'personnel' or 'registration'")?),
+                None => layouts.iter()
+                    .map(|l| (l, l.fields.iter().filter(|e| dbt.fields().iter().any(|f| f.name == e.name)).count()))
+                    .filter(|(l, shared)| shared * 2 > l.fields.len())
+                    .max_by_key(|(_, shared)| *shared)
+                    .map(|(l, _)| l),
+            };
+            if let Some(layout) = layout {
+                let check = SchemaCheck::new(&layout.fields, layout.optional, dbt.fields());
+                println!("Schema check ({} layout):", layout.name);
+                for line in check.to_string().lines() {
+                    println!("  {line}");
+                }
+            }
+
+            let n_records = dbt.n_records();
+            let mut n_deleted = 0;
+            let mut records = dbt.records().include_deleted();
+            while let Some(record) = records.next() {
+                if record?.is_deleted() {
+                    n_deleted += 1;
+                }
+            }
+            println!("Records: {n_records} ({n_deleted} deleted)");
+
+            for path in xbase::ntx::find_indexes(&personnel_path)? {
+                match xbase::ntx::try_from_path(&path) {
+                    Ok(index) => println!("{}: {:?}", path.display(), index.header()),
+                    Err(err) => println!("{}: {err}", path.display()),
+                }
+            }
+        }
+        "check" => {
+            let data = std::fs::read(&personnel_path)?;
+            let repair_path = match args.next().as_deref() {
+                None => None,
+                Some("repair") => Some(args.next().ok_or("fourth arg should be a path to write the repaired table")?),
+                Some(_) => return Err("third arg should be 'repair'".into()),
+            };
+
+            let check = match &repair_path {
+                None => xbase::repair::check_table(&data)?,
+                Some(path) => {
+                    let (fixed, check) = xbase::repair::repair_table(&data)?;
+                    std::fs::write(path, fixed)?;
+                    check
+                }
+            };
+            for p in &check.problems {
+                println!("{p}");
+            }
+            println!("Found {} problem(s), {} field(s), and {} complete record(s).",
+                     check.problems.len(), check.fields.len(), check.n_records);
+            if let Some(path) = repair_path {
+                println!("Wrote the repaired table to {path}.");
+            }
+        }
+        "check_index" => {
+            let dbt = xbase::try_from_path(personnel_path)?;
+            let index_path = args.next().ok_or("third arg should be a path to an NTX file")?;
+            let mut index = xbase::ntx::try_from_path(index_path)?;
+            let problems = xbase::ntx::check_index(dbt, &mut index)?;
+
+            for p in &problems {
+                println!("{p}");
+            }
+            println!("Index '{}' has {} problem(s).", index.header().key_expression, problems.len());
+        }
+        "seek_index" => {
+            let mut dbt = xbase::try_from_path(personnel_path)?.random_access();
+            let index_path = args.next().ok_or("third arg should be a path to an NTX file")?;
+            let key = args.next().ok_or("fourth arg should be a key prefix")?;
+            let mut index = xbase::ntx::try_from_path(index_path)?;
+
+            for entry in index.seek(key.as_bytes())? {
+                let entry = entry?;
+                let status = match dbt.get(entry.record_number as usize) {
+                    Ok(Some(_)) => "",
+                    Ok(None) => " (deleted)",
+                    Err(xbase::DBaseErrorKind::RecordOutOfRange(..)) => " (missing)",
+                    Err(err) => return Err(err.into()),
+                };
+                println!("{:>8}: '{}'{status}", entry.record_number, entry.key_str()?);
+            }
+        }
+        "lookup" => {
+            let mut dbt = xbase::try_from_path(personnel_path)?.random_access();
+            let index_path = args.next().ok_or("third arg should be a path to an IGRA_NUM index")?;
+            let igra_num = args.next().ok_or("fourth arg should be an IGRA number")?;
+            let mut index = xbase::ntx::try_from_path(index_path)?;
+
+            match validation::lookup_person(&mut dbt, &mut index, &igra_num)? {
+                Some(person) => println!("{}", serde_json::to_string_pretty(&person)?),
+                None => println!("No person with IGRA number '{igra_num}'."),
+            }
+        }
+        "delete" | "undelete" => {
+            let record_number = args.next().ok_or("third arg should be a record number")?.parse()?;
+            let mut editor = xbase::edit_path(&personnel_path)?;
+            let delete = command == "delete";
+
+            if editor.is_deleted(record_number)? == delete {
+                println!("Record {record_number} is already {command}d.");
+            } else if delete {
+                editor.delete(record_number)?;
+                println!("Deleted record {record_number}.");
+            } else {
+                editor.undelete(record_number)?;
+                println!("Restored record {record_number}.");
+            }
+        }
+        "deleted" => {
+            let dbt = xbase::try_from_path(&personnel_path)?;
+            let mut records = dbt.records().include_deleted();
+
+            while let Some(record) = records.next() {
+                let record = record?;
+                if !record.is_deleted() {
+                    continue;
+                }
+
+                println!("Record {}:", record.record_number());
+                for field in record {
+                    let field = field?;
+                    println!("  {}: {:?}", field.name, field.value);
+                }
+            }
+        }
+        "export" => {
+            let format: xbase::record::RowFormat = args.next()
+                .ok_or("third arg should be the format: jsonl or csv")?
+                .parse()?;
+            let rest: Vec<_> = args.collect();
+            let include_deleted = rest.iter().any(|a| a == "deleted");
+            let columns = rest.iter()
+                .find(|a| *a != "deleted")
+                .map(|c| c.split(',').map(str::to_string).collect::<Vec<_>>());
+
+            let dbt = xbase::try_from_path(&personnel_path)?;
+            let n = xbase::record::export(dbt, format, columns.as_deref(), include_deleted, std::io::stdout().lock())?;
+            log::info!("Exported {n} records");
+        }
+        "import" => {
+            // The table argument gives the new table's layout: a DBF to copy, or a schema file.
+            let schema = xbase::record::Schema::from_path(&personnel_path)?;
+            let input_path = args.next().ok_or("third arg should be a CSV or JSON Lines file to import")?;
+            let output_path = args.next().ok_or("fourth arg should be a path to write the new table")?;
+            let format = xbase::record::RowFormat::from_path(&input_path);
+
+            // Build the table in memory, so a failed import doesn't leave a broken file behind.
+            let mut data = std::io::Cursor::new(Vec::new());
+            let (n, errors) = xbase::record::import(File::open(&input_path)?, format, &schema, &mut data)?;
+            std::fs::write(&output_path, data.into_inner())?;
+
+            for err in &errors {
+                eprintln!("Skipped {err}");
+            }
+            println!("Imported {n} records; skipped {} with errors.", errors.len());
+        }
+        "pack" => {
+            let mut editor = xbase::edit_path(&personnel_path)?;
+            let removed = editor.pack()?;
+            println!("Removed {removed} deleted record(s); {} remain.", editor.n_records());
+            if removed > 0 {
+                println!("Record numbers have changed, so rebuild this table's indexes with build_index.");
+            }
+        }
+        "set_field" => {
+            let record_number = args.next().ok_or("third arg should be a record number")?.parse()?;
+            let name = args.next().ok_or("fourth arg should be a field name")?;
+            let value = args.next().ok_or("fifth arg should be the new value")?;
+            let mut editor = xbase::edit_path(&personnel_path)?;
+
+            let field = editor.fields().iter()
+                .find(|f| f.name == name)
+                .ok_or_else(|| xbase::DBaseErrorKind::UnknownField(name.clone()))?
+                .parse_str(&value)?;
+            editor.set_field(record_number, &name, &field)?;
+        }
+        "save_person" => {
+            let person_path = args.next().ok_or("third arg should be a path to a JSON person record")?;
+            let mut person: PersonRecord = serde_json::from_reader(BufReader::new(File::open(person_path)?))?;
+            let mut editor = xbase::edit_path(&personnel_path)?.with_transliteration();
+
+            match validation::save_person(&mut editor, &mut person)? {
+                (n, true) => println!("Added {} as record {n}.", person.igra_number),
+                (n, false) => println!("Updated {} in record {n}.", person.igra_number),
+            }
         }
         "validate" => {
             let dbt = xbase::try_from_path(personnel_path)?;
             let target_path = args.next().ok_or("third arg should be a path")?;
+            let personnel_updated = dbt.last_updated();
             let people = validation::read_personnel(dbt)?;
             log::info!("Number of people in personnel database: {}", people.len());
+            let validator = EntryValidator::new(people, load_nicknames());
 
             let reg = validation::read_reg(target_path)?;
-            let report = do_validate(&people, &reg)?;
+            let report = do_validate(&validator, personnel_updated, &reg)?;
             let j = serde_json::to_string_pretty(&report)?;
             println!("{j}");
         }
         "read_reg" => {
             let dbt = xbase::try_from_path(personnel_path)?;
-            let registrations = validation::read_registrations(dbt)?;
+            let registrations = if args.next().is_some_and(|a| a == "lenient") {
+                let (registrations, warnings) = validation::read_registrations_lenient(dbt)?;
+                for w in warnings {
+                    eprintln!("warning: {w}");
+                }
+                registrations
+            } else {
+                validation::read_registrations(dbt)?
+            };
 
             for reg in registrations {
                 println!("{reg}");
@@ -79,22 +293,57 @@ async fn main() -> MyResult<()> {
         "write_reg" => {
             let reg_path = args.next().ok_or("third arg should be a path to json registration")?;
             let target_path = args.next().ok_or("fourth arg should be a path to write records")?;
+            let rest: Vec<_> = args.collect();
+            let index_expr = rest.iter().find(|a| *a != "notes").map_or("IGRA_NUM", |a| a.as_str());
+            let with_notes = rest.iter().any(|a| a == "notes");
             
             // todo: merge with existing db
             // let events_dbt = xbase::try_from_path(events_path)?;
             // let registrations = validation::read_registrations(events_dbt)?;
             
             let dbt = xbase::try_from_path(personnel_path)?;
-            let people = validation::read_personnel(dbt)?;
+            let codepage = dbt.codepage();
+            let header_year = dbt.header_year();
+            let personnel_updated = dbt.last_updated();
+            let validator = EntryValidator::new(validation::read_personnel(dbt)?, load_nicknames());
 
             let reg = validation::read_reg(reg_path)?;
-            let report = do_validate(&people, &reg)?;
+            let report = do_validate(&validator, personnel_updated, &reg)?;
 
             let registrations = report.online_to_dbase();
 
+            // Online registrations may have names the legacy app's codepage can't represent.
+            let index_path = xbase::ntx::index_path_for(&target_path);
             let tw = xbase::TableWriter::new(
-                BufWriter::new(File::create(target_path)?))?;
-            tw.write_records(&registrations)?;
+                BufWriter::new(File::create(&target_path)?))?
+                .with_codepage(codepage)
+                .with_header_year(header_year)
+                .with_transliteration()
+                .with_index(index_expr, false, BufWriter::new(File::create(index_path)?))?;
+
+            // Write the table even if every registration was rejected, so it can be opened as usual.
+            let blank = validation::RegistrationRecord::default();
+            if with_notes {
+                let format = xbase::memo::MemoFormat::DBase;
+                let memo_path = PathBuf::from(&target_path).with_extension(format.extension());
+                let mut tw = tw.with_memo(format, BufWriter::new(File::create(memo_path)?))
+                    .begin(validation::WithNotes(&blank).describe())?;
+                tw.write_iter(registrations.iter().map(validation::WithNotes))?;
+                tw.finish()?;
+            } else {
+                let mut tw = tw.begin(blank.describe())?;
+                tw.write_iter(&registrations)?;
+                tw.finish()?;
+            }
+        }
+        "build_index" => {
+            let dbt = xbase::try_from_path(personnel_path)?;
+            let expr = args.next().ok_or("third arg should be a key expression")?;
+            let index_path = args.next().ok_or("fourth arg should be a path to write the index")?;
+            let is_unique = args.next().is_some_and(|s| s == "unique");
+
+            let expr = xbase::ntx::KeyExpression::parse(&expr)?;
+            xbase::ntx::build_index(dbt, expr, is_unique, BufWriter::new(File::create(index_path)?))?;
         }
         "search" => {
             let dbt = xbase::try_from_path(personnel_path)?;
@@ -104,11 +353,11 @@ async fn main() -> MyResult<()> {
 
             let people = validation::read_personnel(dbt)?;
             log::info!("Number of people in personnel database: {}", people.len());
-            let validator = EntryValidator::new(&people);
+            let validator = EntryValidator::new(people, load_nicknames());
 
             let (igra, name) = validation::split_partner(&person);
             let (perfect, matches) = validator.find_person(
-                igra, &legal_first, &legal_last, &name);
+                igra, &legal_first, &legal_last, name);
 
             println!("IGRA #: {igra:?} | Name: {name} | Perfect Match: {perfect} | Num matches: {count}",
                      count = matches.len(),
@@ -126,17 +375,16 @@ async fn main() -> MyResult<()> {
             let dbt = xbase::try_from_path(personnel_path)?;
             let target_path = args.next().ok_or("third arg should be a path")?;
             let people = validation::read_personnel(dbt)?;
-            let fake_regs = generate_fake_reg(&people, 10)?;
+            let fake_regs = generate_fake_reg(&people.iter().collect::<Vec<_>>(), 10)?;
             let j = serde_json::to_string_pretty(&fake_regs)?;
             write!(BufWriter::new(File::create(target_path)?), "{j}")?;
         }
         "serve" => {
-            let dbt = xbase::try_from_path(personnel_path)?;
-            let people = validation::read_personnel(dbt)?;
+            let personnel = Personnel::load(&personnel_path, None).await?;
             let port = args.next()
                 .and_then(|var| var.parse::<u16>().ok())
-                .unwrap_or(8080 as u16);
-            do_serve(people, port).await?;
+                .unwrap_or(8080_u16);
+            do_serve(personnel, PathBuf::from(personnel_path), port).await?;
         }
         _ => { return Err("Unknown command".into()); }
     }
@@ -145,19 +393,124 @@ async fn main() -> MyResult<()> {
 }
 
 
+/// The table of equivalent first names, such as nicknames, used when searching for people.
+const NICKNAMES_PATH: &str = "./data/nicknames.txt";
+
+/// Read the nickname table.
+///
+/// People can still be found without it, so if it can't be read, this logs why and returns an empty table.
+/// The path is relative to the working directory, so the log names the file it actually looked for.
+fn load_nicknames() -> Nicknames {
+    let path = std::path::absolute(NICKNAMES_PATH).unwrap_or_else(|_| PathBuf::from(NICKNAMES_PATH));
+    match Nicknames::from_path(&path) {
+        Ok(nicknames) => {
+            log::info!("Read {} groups of equivalent names from {}", nicknames.n_groups(), path.display());
+            nicknames
+        }
+        Err(err) => {
+            log::warn!("Unable to read {}, so nicknames won't match: {err}", path.display());
+            Nicknames::default()
+        }
+    }
+}
+
+/// How often the server checks whether the personnel table has changed.
+const PERSONNEL_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The personnel table as it was when it was read.
+///
+/// When the table changes, a new one replaces it as a whole,
+/// so a request always sees a single version of the table.
+pub(crate) struct Personnel {
+    /// Searches the table's people, who it holds.
+    pub validator: EntryValidator,
+    /// When the personnel table was last updated by the legacy app, according to its header.
+    pub updated: NaiveDate,
+    /// The table file's modification time when it was read.
+    pub modified: SystemTime,
+}
+
+impl Personnel {
+    /// Read the personnel table at the given path without blocking the runtime.
+    ///
+    /// Given the `previous` version of the table, its search trees are updated
+    /// with the people who changed, rather than built again.
+    async fn load(path: impl AsRef<Path>, previous: Option<Arc<Personnel>>) -> MyResult<Personnel> {
+        // Get the time first, so a change made while reading is picked up by the next check.
+        let modified = tokio::fs::metadata(&path).await?.modified()?;
+        let dbt = xbase::stream::try_from_path(&path).await?;
+        let updated = dbt.last_updated();
+        log::info!("Personnel table last updated on {updated}");
+        // Don't refuse to start over columns the Clipper app added.
+        let (people, warnings) = validation::load_personnel(dbt).await?;
+        for w in warnings {
+            log::warn!("Personnel table: {w}");
+        }
+        log::info!("Number of people in personnel database: {}", people.len());
+
+        // Building the metric trees takes a while, so keep it off the async worker threads.
+        // The nicknames are read again each time, so changes to them apply with the next version of the table.
+        let validator = tokio::task::spawn_blocking(move || match previous {
+            Some(previous) => previous.validator.with_people(people, load_nicknames()),
+            None => EntryValidator::new(people, load_nicknames()),
+        }).await?;
+        log::info!("Built the personnel search trees");
+        Ok(Personnel { validator, updated, modified })
+    }
+}
+
 #[derive(Clone)]
 pub(crate) struct AppState {
-    pub people: Arc<Vec<PersonRecord>>,
+    personnel: Arc<RwLock<Arc<Personnel>>>,
 }
 
 impl AppState {
-    fn new(people: Vec<PersonRecord>) -> Self {
+    fn new(personnel: Personnel) -> Self {
         AppState {
-            people: Arc::new(people),
+            personnel: Arc::new(RwLock::new(Arc::new(personnel))),
+        }
+    }
+
+    /// Get the current version of the personnel table.
+    fn personnel(&self) -> Arc<Personnel> {
+        self.personnel.read().expect("personnel lock poisoned").clone()
+    }
+
+    /// Replace the personnel table, leaving requests using the old one unaffected.
+    fn set_personnel(&self, personnel: Personnel) {
+        *self.personnel.write().expect("personnel lock poisoned") = Arc::new(personnel);
+    }
+}
+
+/// Check the personnel table's modification time every so often,
+/// and reload it when it changes.
+///
+/// If the table can't be read, such as while the legacy app is writing it,
+/// the server keeps using the old one and tries again at the next check.
+async fn watch_personnel(path: PathBuf, state: AppState) {
+    let mut interval = tokio::time::interval(PERSONNEL_POLL_INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        if let Err(err) = reload_personnel(&path, &state).await {
+            log::warn!("Unable to reload {}: {err}", path.display());
         }
     }
 }
 
+/// Reload the personnel table if its modification time has changed since it was read,
+/// returning whether the new version replaced the old one.
+async fn reload_personnel(path: &Path, state: &AppState) -> MyResult<bool> {
+    let modified = tokio::fs::metadata(path).await?.modified()?;
+    if modified == state.personnel().modified {
+        return Ok(false);
+    }
+
+    log::info!("{} changed; reloading it", path.display());
+    state.set_personnel(Personnel::load(path, Some(state.personnel())).await?);
+    Ok(true)
+}
+
 impl<'a> IntoResponse for Report<'a> {
     fn into_response(self) -> axum::response::Response {
         match serde_json::to_string(&self) {
@@ -176,8 +529,9 @@ impl<'a> IntoResponse for Report<'a> {
     }
 }
 
-async fn do_serve(people: Vec<PersonRecord>, port: u16) -> MyResult<()> {
-    let state = AppState::new(people);
+async fn do_serve(personnel: Personnel, personnel_path: PathBuf, port: u16) -> MyResult<()> {
+    let state = AppState::new(personnel);
+    tokio::spawn(watch_personnel(personnel_path, state.clone()));
     let socket = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)), port);
 
     let config = if port == 443 {
@@ -198,6 +552,7 @@ async fn do_serve(people: Vec<PersonRecord>, port: u16) -> MyResult<()> {
         .route("/validate", post(handle_validate))
         .route("/generate", post(handle_generate))
         .route("/search", post(handle_search))
+        .route("/status", get(handle_status))
         .with_state(state)
         .fallback(handle_404);
 
@@ -259,13 +614,13 @@ async fn handle_404() -> &'static str {
 
 /// Validates the collection of Registrations using the current database.
 /// On success, returns a JSON response of results.
-async fn handle_validate<'a>(
+async fn handle_validate(
     State(state): State<AppState>,
     WithRejection(Json(payload), _): WithRejection<Json<Vec<Registration>>, ApiError>,
 ) -> impl IntoResponse
 {
-    let people = state.people.clone();
-    let j = do_validate(&people, &payload)
+    let personnel = state.personnel();
+    let j = do_validate(&personnel.validator, personnel.updated, &payload)
         .and_then(|r| { serde_json::to_string(&r).map_err(|e| e.into()) })
         .map_err(|e| json!({"err": e.to_string()}).to_string());
 
@@ -294,7 +649,7 @@ async fn handle_generate(
         return Err(ApiError::InvalidNumberOfPeople { amount: payload.num_people, min: 2, max: 100 });
     }
 
-    generate_fake_reg(&state.people.clone(), payload.num_people as usize)
+    generate_fake_reg(state.personnel().validator.people(), payload.num_people as usize)
         .map(|r| (StatusCode::OK, Json(r)))
         .map_err(|err| {
             log::error!("{:?}", err);
@@ -316,19 +671,18 @@ struct SearchResults<'a> {
 }
 
 /// Search for a person in the database. 
-async fn handle_search<'a>(
+async fn handle_search(
     State(state): State<AppState>,
     Json(payload): Json<SearchData>,
 ) -> impl IntoResponse 
 {
-    let people = state.people.clone();
-    let validator = EntryValidator::new(&people);
+    let personnel = state.personnel();
     let (igra, name) = validation::split_partner(&payload.performance_name);
-    let (is_perfect, mut best_matches) = validator.find_person(
+    let (is_perfect, mut best_matches) = personnel.validator.find_person(
         igra, 
         &payload.legal_first.unwrap_or("".to_string()),
         &payload.legal_last.unwrap_or("".to_string()), 
-        &name
+        name
     );
 
     best_matches.truncate(25);
@@ -344,30 +698,45 @@ async fn handle_search<'a>(
     )
 }
 
-/// Validates a collection of registrations against a collection of PersonRecords.
+/// Reports which version of the personnel table the server is using.
+async fn handle_status(State(state): State<AppState>) -> Json<serde_json::Value> {
+    let personnel = state.personnel();
+    Json(json!({
+        "records": personnel.validator.people().len(),
+        "personnel_updated": personnel.updated.to_string(),
+        "file_modified": chrono::DateTime::<chrono::Utc>::from(personnel.modified).to_rfc3339(),
+    }))
+}
+
+/// Validates a collection of registrations against the validator's PersonRecords.
 fn do_validate<'a>(
-    people: &'a Vec<PersonRecord>,
+    validator: &'a EntryValidator,
+    personnel_updated: NaiveDate,
     reg: &'a Vec<Registration>,
 ) -> MyResult<Report<'a>>
 {
     log::info!("Number of entries JSON file: {}", reg.len());
-    let validator = EntryValidator::new(&people);
-    Ok(validator.validate_entries(&reg))
+    let mut report = validator.validate_entries(reg);
+    report.check_personnel_date(personnel_updated, reg);
+    Ok(report)
 }
 
-/// Generate a database of random people, using the given table as a template for fields.
+/// Generate a database of random people, using the given table as a template for fields,
+/// along with an index on their IGRA numbers.
 fn do_db_gen<P>(target_path: P) -> MyResult<()>
     where P: AsRef<std::path::Path>,
 {
+    let index_path = xbase::ntx::index_path_for(&target_path);
     let tw = xbase::TableWriter::new(
-            BufWriter::new(File::create(target_path)?))?;
+            BufWriter::new(File::create(target_path)?))?
+        .with_index("IGRA_NUM", true, BufWriter::new(File::create(index_path)?))?;
     let people = generate_fake_db()?;
     Ok(tw.write_records(&people)?)
 }
 
 /// Generates `n` random `Registration`s from the given collection of people.
 /// Barring bugs in the implementation, this returns a valid collection of registrations.
-fn generate_fake_reg(people: &Vec<PersonRecord>, n: usize) -> MyResult<Vec<Registration>> {
+fn generate_fake_reg<P: Deref<Target=PersonRecord>>(people: &[P], n: usize) -> MyResult<Vec<Registration>> {
     let mut rng = thread_rng();
     let mut registrations = Vec::with_capacity(n);
     let today = chrono::Utc::now().naive_utc().date();
@@ -387,7 +756,7 @@ fn generate_fake_reg(people: &Vec<PersonRecord>, n: usize) -> MyResult<Vec<Regis
     let event_mod = event_names.len() - 2;
 
     // Determine who will register.
-    let participants: Vec<_> = people.choose_multiple(&mut rng, n).collect();
+    let participants: Vec<&PersonRecord> = people.choose_multiple(&mut rng, n).map(|p| &**p).collect();
     let n = participants.len(); // in case n > people.len()
 
     // This decides randomly among possible ways of writing the partner's name.
@@ -428,7 +797,7 @@ fn generate_fake_reg(people: &Vec<PersonRecord>, n: usize) -> MyResult<Vec<Regis
 
         // Register each person with their partners.
         let transaction_time = chrono::Utc::now().timestamp_millis();
-        for ((_, ref mut r), partners) in who.into_iter().zip(partner_names) {
+        for ((_, ref mut r), partners) in who.iter_mut().zip(partner_names) {
             if twice {
                 r.events.push(robin::Event { id, partners: partners.clone(), round: 1, transaction_time });
                 r.events.push(robin::Event { id, partners, round: 2, transaction_time });
@@ -562,27 +931,27 @@ fn generate_fake_reg(people: &Vec<PersonRecord>, n: usize) -> MyResult<Vec<Regis
 fn generate_fake_db() -> Result<Vec<PersonRecord>, Box<dyn Error>> {
     let first_names: Vec<_> = BufReader::new(
         File::open("./data/common_first_names.txt")?
-    ).lines().filter_map(|r| r.ok()).filter(|s| s.len() <= 10).collect();
+    ).lines().map_while(|r| r.ok()).filter(|s| s.len() <= 10).collect();
     let last_names: Vec<_> = BufReader::new(
         File::open("./data/common_last_names.txt")?
-    ).lines().filter_map(|r| r.ok()).filter(|s| s.len() <= 17).collect();
+    ).lines().map_while(|r| r.ok()).filter(|s| s.len() <= 17).collect();
     let cities: Vec<_> = BufReader::new(
         File::open("./data/common_cities.txt")?
-    ).lines().filter_map(|r| r.ok()).filter(|s| s.len() <= 18).collect();
+    ).lines().map_while(|r| r.ok()).filter(|s| s.len() <= 18).collect();
     let regions: Vec<_> = BufReader::new(
         File::open("./data/common_regions.txt")?
-    ).lines().filter_map(|r| r.ok()).filter(|s| s.len() <= 2).collect();
+    ).lines().map_while(|r| r.ok()).filter(|s| s.len() <= 2).collect();
     let associations: Vec<_> = BufReader::new(
         File::open("./data/associations.txt")?
-    ).lines().filter_map(|r| r.ok()).filter(|s| s.len() <= 5).collect();
+    ).lines().map_while(|r| r.ok()).filter(|s| s.len() <= 5).collect();
     // These two get built together, so they get truncated below.
     // The limit is 30.
     let streets: Vec<_> = BufReader::new(
         File::open("./data/common_streets.txt")?
-    ).lines().filter_map(|r| r.ok()).collect();
+    ).lines().map_while(|r| r.ok()).collect();
     let street_ends: Vec<_> = BufReader::new(
         File::open("./data/common_street_endings.txt")?
-    ).lines().filter_map(|r| r.ok()).collect();
+    ).lines().map_while(|r| r.ok()).collect();
 
     let mut rng = thread_rng();
 
@@ -649,3 +1018,100 @@ fn generate_fake_db() -> Result<Vec<PersonRecord>, Box<dyn Error>> {
 
     Ok(res)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use axum::body::HttpBody;
+
+    /// Write a personnel table of `n` people, PERSON0 DOE and so on.
+    fn write_people(path: &Path, n: usize) {
+        let people: Vec<PersonRecord> = (0..n)
+            .map(|i| PersonRecord {
+                igra_number: format!("{:04}", i + 1),
+                first_name: format!("PERSON{i}"),
+                last_name: "DOE".to_string(),
+                birthdate: "19800101".to_string(),
+                ..PersonRecord::default()
+            })
+            .collect();
+        xbase::TableWriter::new(File::create(path).unwrap()).unwrap().write_records(&people).unwrap();
+    }
+
+    /// Get a handler's response body as JSON.
+    async fn response_json(response: impl IntoResponse) -> serde_json::Value {
+        let mut body = response.into_response().into_body();
+        let mut data = Vec::new();
+        while let Some(chunk) = body.data().await {
+            data.extend_from_slice(&chunk.unwrap());
+        }
+        serde_json::from_slice(&data).unwrap()
+    }
+
+    #[tokio::test]
+    async fn status_shows_the_reloaded_personnel_table() {
+        let path = env::temp_dir().join(format!("converter-personnel-{}.dbf", std::process::id()));
+        let write_people = |n: usize| write_people(&path, n);
+        let status = |state: &AppState| handle_status(State(state.clone()));
+
+        write_people(3);
+        let state = AppState::new(Personnel::load(&path, None).await.unwrap());
+        assert_eq!(status(&state).await.0["records"], 3);
+        assert!(!reload_personnel(&path, &state).await.unwrap());
+
+        // Filesystem timestamps can be coarse, so make sure the rewritten table looks newer.
+        write_people(5);
+        let later = state.personnel().modified + Duration::from_secs(60);
+        File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+        assert!(reload_personnel(&path, &state).await.unwrap());
+        assert_eq!(status(&state).await.0["records"], 5);
+        assert!(!reload_personnel(&path, &state).await.unwrap());
+
+        // A table that can't be read leaves the old one in place.
+        std::fs::write(&path, b"not a table").unwrap();
+        File::options().write(true).open(&path).unwrap().set_modified(later + Duration::from_secs(60)).unwrap();
+        assert!(reload_personnel(&path, &state).await.is_err());
+        assert_eq!(status(&state).await.0["records"], 5);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+    #[tokio::test]
+    async fn requests_share_the_personnel_validator_until_a_reload() {
+        let path = env::temp_dir().join(format!("converter-shared-{}.dbf", std::process::id()));
+        write_people(&path, 3);
+        let state = AppState::new(Personnel::load(&path, None).await.unwrap());
+        let search = |state: &AppState| {
+            let payload = SearchData { performance_name: "Person4 Doe".to_string(), legal_first: None, legal_last: None };
+            let response = handle_search(State(state.clone()), Json(payload));
+            async {
+                // Ties come back in any order, so sort the matches.
+                let json = response_json(response.await).await;
+                let mut found: Vec<_> = json["best_matches"].as_array().unwrap().iter()
+                    .map(|p| p["igra_number"].as_str().unwrap().to_string())
+                    .collect();
+                found.sort();
+                (json["is_perfect"].as_bool().unwrap(), found)
+            }
+        };
+
+        // Searches and validation use the loaded validator, rather than building their own.
+        let loaded = state.personnel();
+        let first = search(&state).await;
+        let report = response_json(handle_validate(State(state.clone()), WithRejection(Json(Vec::new()), Default::default())).await).await;
+        assert!(report.get("err").is_none());
+        assert_eq!(search(&state).await, first);
+        assert!(!first.0);
+        assert!(Arc::ptr_eq(&loaded, &state.personnel()));
+
+        // A reload swaps in a new validator, while the old one is unchanged for requests still using it.
+        write_people(&path, 5);
+        let later = loaded.modified + Duration::from_secs(60);
+        File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+        assert!(reload_personnel(&path, &state).await.unwrap());
+        assert!(!Arc::ptr_eq(&loaded, &state.personnel()));
+        assert!(search(&state).await.0);
+        assert_eq!(loaded.validator.people().len(), 3);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,130 @@
+//! Equivalent first names, such as nicknames and diminutives.
+//!
+//! Registrants often give the name they go by rather than their legal one,
+//! e.g. "Bill" for WILLIAM or "Bobby Jo" for ROBERTA,
+//! which are too differently spelled to find with an edit distance.
+//!
+//! The table is read from a text file in which each line lists names that are the same first name,
+//! separated by commas, e.g. `WILLIAM, BILL, BILLY, WILL`.
+//! Two names are equivalent if they appear on the same line,
+//! so a name may appear on several lines without making those lines equivalent to each other:
+//! BOBBY can be short for either ROBERT or ROBERTA, but ROBERT isn't ROBERTA.
+//! Blank lines and anything following a `#` are ignored.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// A table of equivalent first names.
+#[derive(Debug, Default)]
+pub struct Nicknames {
+    /// The lines of equivalent names.
+    groups: Vec<Vec<String>>,
+    /// Each name's positions in `groups`.
+    by_name: HashMap<String, Vec<usize>>,
+}
+
+/// Uppercase a name and collapse its whitespace, so names are compared the way they're stored.
+fn normalize(name: &str) -> String {
+    name.split_whitespace()
+        .map(|part| part.to_uppercase())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl Nicknames {
+    /// Read the table from the file at the given path.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::read(BufReader::new(File::open(path)?))
+    }
+
+    /// Read the table from lines of comma-separated names.
+    pub fn read<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut nicknames = Nicknames::default();
+        for line in reader.lines() {
+            let line = line?;
+            let line = line.split_once('#').map_or(line.as_str(), |(names, _)| names);
+
+            let mut group: Vec<String> = line.split(',')
+                .map(normalize)
+                .filter(|name| !name.is_empty())
+                .collect();
+            group.sort_unstable();
+            group.dedup();
+            if group.len() < 2 {
+                continue;
+            }
+
+            let index = nicknames.groups.len();
+            for name in &group {
+                nicknames.by_name.entry(name.clone()).or_default().push(index);
+            }
+            nicknames.groups.push(group);
+        }
+        Ok(nicknames)
+    }
+
+    /// Get the number of lines of equivalent names.
+    pub fn n_groups(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Check if two first names are the same, ignoring case and whitespace,
+    /// or if the table lists them as equivalent.
+    pub fn same_name(&self, name: &str, other: &str) -> bool {
+        let name = normalize(name);
+        let other = normalize(other);
+        name == other || self.by_name.get(&name).is_some_and(|groups| {
+            groups.iter().any(|&g| self.groups[g].binary_search(&other).is_ok())
+        })
+    }
+
+    /// Get the names the table lists as equivalent to `name`, other than itself.
+    pub fn equivalents(&self, name: &str) -> Vec<&str> {
+        let name = normalize(name);
+        let mut found: Vec<&str> = self.by_name.get(&name)
+            .into_iter()
+            .flatten()
+            .flat_map(|&g| &self.groups[g])
+            .filter(|other| **other != name)
+            .map(String::as_str)
+            .collect();
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn names_on_a_line_are_equivalent() {
+        let table = "\
+            # Comments and blank lines are ignored.\n\
+            \n\
+            WILLIAM, BILL, BILLY, WILL\n\
+            ROBERT, BOB, BOBBY, ROB  # but not ROBERTA\n\
+            ROBERTA, BOBBIE, BOBBY, Bobby Jo\n\
+            LONELY\n";
+        let nicknames = Nicknames::read(table.as_bytes()).unwrap();
+        assert_eq!(nicknames.n_groups(), 3);
+
+        assert!(nicknames.same_name("Bill", "WILLIAM"));
+        assert!(nicknames.same_name("william ", "bill"));
+        assert!(nicknames.same_name("BOBBY  JO", "ROBERTA"));
+        assert!(nicknames.same_name("BOBBY", "ROBERT"));
+        assert!(nicknames.same_name("BOBBY", "ROBERTA"));
+        assert!(nicknames.same_name("Lonely", "LONELY"));
+        assert!(!nicknames.same_name("ROBERT", "ROBERTA"));
+        assert!(!nicknames.same_name("BILL", "BOB"));
+        assert!(!nicknames.same_name("LONELY", "BILL"));
+
+        assert_eq!(nicknames.equivalents("bobby"), ["BOB", "BOBBIE", "BOBBY JO", "ROB", "ROBERT", "ROBERTA"]);
+        assert_eq!(nicknames.equivalents("ROBERT"), ["BOB", "BOBBY", "ROB"]);
+        assert!(nicknames.equivalents("NOBODY").is_empty());
+    }
+}
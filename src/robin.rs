@@ -150,7 +150,7 @@ impl Registration {
     /// If there are no events or the max value cannot be converted, this returns None.
     pub fn estimate_payment_date(&self) -> Option<NaiveDate> {
         self.events.iter().map(|e| e.transaction_time).max()
-            .and_then(|t| NaiveDateTime::from_timestamp_millis(t))
+            .and_then(NaiveDateTime::from_timestamp_millis)
             .map(|d| d.date())
     }
 }
\ No newline at end of file
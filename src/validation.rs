@@ -1,36 +1,67 @@
 use std::clone::Clone;
-use eddie::DamerauLevenshtein;
+use strsim::damerau_levenshtein;
 use phf::{phf_map, phf_set};
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
-use std::collections::{hash_map, HashMap};
+use std::collections::{hash_map, BTreeSet, HashMap, HashSet};
 use std::error::Error;
 use std::fmt::{Display, Formatter};
 use std::fs::File;
 use std::io;
 use std::io::BufReader;
-use std::iter::{Iterator, zip};
+use std::iter::{self, Iterator, zip};
 use std::ops::Deref;
+use std::sync::Arc;
 use chrono::NaiveDate;
+use futures_util::TryStreamExt;
 use memchr::memchr;
+use tokio::io::AsyncRead;
 
 use crate::bktree;
 use crate::bktree::BKTree;
+use crate::nicknames::Nicknames;
+use crate::phonetic;
 use crate::robin::EventID::Known;
-use crate::robin::{Event, EventID, Registration};
-use crate::xbase::{DBaseRecord, DBaseResult, Decimal, Field, Header, TableReader, FieldDescriptor, FieldType};
+use crate::robin::{Contestant, Event, EventID, Registration};
+use crate::xbase::{ColumnGroup, DBaseErrorKind, DBaseRecord, DBaseResult, Decimal, Field, FieldValue, Header, TableReader, FieldDescriptor, FieldType, FromRecord, RandomAccess, SchemaWarning, TableEditor};
+use crate::xbase::ntx::NtxIndex;
+use crate::xbase::stream::AsyncTableReader;
 
 /// Read registration data from the JSON file at the given path.
 pub fn read_reg<P: AsRef<std::path::Path>>(path: P) -> Result<Vec<Registration>, Box<dyn Error>> {
     Ok(serde_json::from_reader(BufReader::new(File::open(path)?))?)
 }
 
-/// Implements a Damerau-Levenshtein metric on new-type versions of borrowed PersonRecords,
+/// A shared PersonRecord.
+///
+/// The metric trees hold these rather than borrowing the records,
+/// so an `EntryValidator` can own the records it searches and be shared between requests,
+/// and a new version of the table can reuse the records that didn't change.
+/// It dereferences to the record itself.
+#[derive(Clone)]
+pub struct PersonRef(Arc<PersonRecord>);
+
+impl Deref for PersonRef {
+    type Target = PersonRecord;
+
+    fn deref(&self) -> &PersonRecord {
+        &self.0
+    }
+}
+
+/// Refs are equal if they share the same record, so a tree entry can be removed using the record it holds.
+impl PartialEq for PersonRef {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Implements a Damerau-Levenshtein metric on new-type versions of `PersonRef`s,
 /// allowing the code below to generate metric trees from the database data using different fields.
 /// Those trees are used to find and rank nearby records when an exact target cannot be found.
 ///
 /// The syntax is `damlev_metric_impl! { MyNewType (some_record_property) }`,
-/// which creates the wrapper struct around `&'a PersonRecord`
+/// which creates the wrapper struct around `PersonRef`
 /// with a `Metric::dist` function that returns the distance between instances of the property.
 macro_rules! damlev_metric_impl {
     (
@@ -38,27 +69,251 @@ macro_rules! damlev_metric_impl {
             $field:ident
         )
     ) => {
-        pub struct $name<'a>(&'a PersonRecord);
+        #[derive(Clone, PartialEq)]
+        pub struct $name(PersonRef);
 
-        impl<'a> bktree::Metric for $name<'a> {
+        impl bktree::Metric for $name {
             type Output = usize;
 
             fn dist(&self, x: &Self) -> usize {
-                let damlev = DamerauLevenshtein::new();
-                damlev.distance(&self.0.$field, &x.0.$field)
+                damerau_levenshtein(&self.0.$field, &x.0.$field)
+            }
+        }
+
+        impl PersonField for $name {
+            fn field(person: &PersonRecord) -> &str {
+                &person.$field
+            }
+
+            fn person(&self) -> &PersonRef {
+                &self.0
             }
         }
     };
 }
 
+/// A metric on one field of a person, as created by `damlev_metric_impl!`.
+trait PersonField: bktree::Metric<Output=usize> + Sized {
+    /// Get the field the metric measures.
+    fn field(person: &PersonRecord) -> &str;
+
+    /// Get the person this entry refers to.
+    fn person(&self) -> &PersonRef;
+}
+
 damlev_metric_impl! { ByIGRANum (igra_number) }
 damlev_metric_impl! { ByFirstName (legal_first) }
 damlev_metric_impl! { ByLastName (legal_last) }
 damlev_metric_impl! { ByPerformanceFirst(first_name) }
 damlev_metric_impl! { ByPerformanceLast(last_name) }
 
-// TODO: store a full name field on PersonRecord
-//  and create a metric for that.
+/// Characters ignored around the parts of a name.
+const NAME_TRIM: &[char] = &[' ', ','];
+
+/// Split a name written as either "First Last" or "Last, First" into `(first, last)`.
+///
+/// A name with a single part is treated as a first name, with an empty last name.
+fn split_name(name: &str) -> (&str, &str) {
+    name.split_once(',')
+        .map(|(l, f)| { (f, l) })
+        .or_else(|| name.split_once(' '))
+        .map(|(f, l)| { (f.trim_matches(NAME_TRIM), l.trim_matches(NAME_TRIM)) })
+        .map(|(f, l)| { if f.is_empty() { (l, f) } else { (f, l) } })
+        .unwrap_or((name.trim_matches(NAME_TRIM), ""))
+}
+
+/// How much an edit to each part of a `PersonKey` adds to the distance between two people.
+///
+/// Legal names, birthdates, and SSNs are what a registrant is matched on,
+/// whereas performance names change more freely, so a typo there counts for less.
+const LEGAL_NAME_WEIGHT: usize = 2;
+const PERFORMANCE_NAME_WEIGHT: usize = 1;
+const BIRTHDATE_WEIGHT: usize = 2;
+const SSN_WEIGHT: usize = 2;
+
+/// The most edits counted between two birthdates or SSNs.
+///
+/// An entirely wrong birthdate is as much evidence against a match as one with a few wrong digits,
+/// and capping it keeps a typo in one from outweighing correct names.
+/// (The smaller of a metric and a constant is still a metric, so the tree stays valid.)
+const MAX_NUMBER_EDITS: usize = 4;
+
+/// How many of the closest people `EntryValidator::find_registrant_candidates` considers.
+const NEAREST_PEOPLE: usize = 10;
+
+/// The evidence used to identify a person: their names, birthdate, and SSN,
+/// normalized so it can be compared between registrations and records.
+///
+/// The distance between two keys is the weighted sum of the Damerau-Levenshtein distances
+/// between their parts, which is a metric, so all of them can be indexed in a single tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PersonKey {
+    legal_first: String,
+    legal_last: String,
+    perf_first: String,
+    perf_last: String,
+    /// As `YYYYMMDD`.
+    birthdate: String,
+    /// Only the last 4 digits, however they're separated.
+    ssn: String,
+}
+
+impl PersonKey {
+    fn new(legal_first: &str, legal_last: &str, perf_first: &str, perf_last: &str, birthdate: &str, ssn: &str) -> Self {
+        let norm = |s: &str| s.trim_matches(NAME_TRIM).to_ascii_uppercase();
+        let ssn: String = ssn.chars().filter(char::is_ascii_digit).collect();
+        PersonKey {
+            legal_first: norm(legal_first),
+            legal_last: norm(legal_last),
+            perf_first: norm(perf_first),
+            perf_last: norm(perf_last),
+            birthdate: birthdate.trim().to_string(),
+            ssn: ssn[ssn.len().saturating_sub(4)..].to_string(),
+        }
+    }
+
+    /// The key for a record in the personnel table.
+    pub fn from_record(rec: &PersonRecord) -> Self {
+        PersonKey::new(&rec.legal_first, &rec.legal_last, &rec.first_name, &rec.last_name,
+                       &rec.birthdate, &rec.ssn)
+    }
+
+    /// The key for a registrant.
+    ///
+    /// Most people perform under their legal name, so that's used when they don't give a performance name.
+    pub fn from_contestant(who: &Contestant) -> Self {
+        let (p_first, p_last) = match split_name(&who.performance_name) {
+            ("", "") => (who.first_name.as_str(), who.last_name.as_str()),
+            names => names,
+        };
+        PersonKey::new(&who.first_name, &who.last_name, p_first, p_last, &who.dob.dos(), &who.ssn)
+    }
+
+    /// The weighted distance between two keys.
+    fn distance(&self, other: &PersonKey) -> usize {
+        let names = LEGAL_NAME_WEIGHT * (damerau_levenshtein(&self.legal_first, &other.legal_first)
+            + damerau_levenshtein(&self.legal_last, &other.legal_last))
+            + PERFORMANCE_NAME_WEIGHT * (damerau_levenshtein(&self.perf_first, &other.perf_first)
+            + damerau_levenshtein(&self.perf_last, &other.perf_last));
+        let numbers = BIRTHDATE_WEIGHT * damerau_levenshtein(&self.birthdate, &other.birthdate).min(MAX_NUMBER_EDITS)
+            + SSN_WEIGHT * damerau_levenshtein(&self.ssn, &other.ssn).min(MAX_NUMBER_EDITS);
+        names + numbers
+    }
+
+    /// Get a copy of this key with a different legal first name,
+    /// which also replaces the performance first name if it was the same.
+    fn with_first_name(&self, name: &str) -> PersonKey {
+        let name = name.to_ascii_uppercase();
+        PersonKey {
+            perf_first: if self.perf_first == self.legal_first { name.clone() } else { self.perf_first.clone() },
+            legal_first: name,
+            ..self.clone()
+        }
+    }
+
+    /// The farthest a record can be from this key and still be considered the same person.
+    ///
+    /// That allows a few typos in each name, like `near_names`, even if the birthdate and SSN are wrong,
+    /// or a substantially different name (such as after a name change) if the birthdate and SSN are right.
+    fn max_distance(&self) -> usize {
+        let typos = ((self.legal_first.chars().count() + self.legal_last.chars().count()) / 2).max(3);
+        (LEGAL_NAME_WEIGHT + PERFORMANCE_NAME_WEIGHT) * typos
+            + (BIRTHDATE_WEIGHT + SSN_WEIGHT) * MAX_NUMBER_EDITS
+    }
+}
+
+/// A metric on people using all of their `PersonKey`, so a registrant can be matched with a single search.
+#[derive(Clone, PartialEq)]
+pub struct ByPerson {
+    key: PersonKey,
+    person: PersonRef,
+}
+
+impl bktree::Metric for ByPerson {
+    type Output = usize;
+
+    fn dist(&self, x: &Self) -> usize {
+        self.key.distance(&x.key)
+    }
+}
+
+/// How many of the closest names `near_names` uses to decide how far to search.
+const NEAREST_NAMES: usize = 10;
+
+/// Find the entries of a name tree close enough to `name` to be a misspelling of it.
+///
+/// Rather than a fixed distance, this searches as far as the `NEAREST_NAMES`th closest entry,
+/// so a short name finds its nearest neighbors rather than hundreds of records.
+/// It always allows a single typo, but never more than half the name's length (or 3, if that's more),
+/// so long names can have more mistakes than short ones.
+fn near_names<'t, T, F>(tree: &'t BKTree<T, usize>, name: &str, dist: F) -> Vec<(usize, &'t T)>
+    where
+        T: bktree::Metric<Output=usize>,
+        F: Fn(&T) -> usize,
+{
+    let max_dist = (name.chars().count() / 2).max(3);
+    let radius = tree.find_k_nearest(NEAREST_NAMES, &dist)
+        .last()
+        .map_or(max_dist, |(d, _)| (*d).clamp(1, max_dist));
+    tree.find_by(radius, dist)
+}
+
+/// The farthest apart two names that sound alike are considered to be,
+/// so a name spelled by ear ranks with ones that have a typo or two.
+const SOUNDS_LIKE_DIST: usize = 2;
+
+/// An index of people by the Double Metaphone codes of one of their names,
+/// so names that sound alike can be found even when they're spelled too differently for `near_names`.
+#[derive(Clone)]
+struct PhoneticIndex(HashMap<String, Vec<PersonRef>>);
+
+impl PhoneticIndex {
+    /// Index people by the field that `T` measures.
+    fn new<T: PersonField>(people: &[PersonRef]) -> Self {
+        let mut index = PhoneticIndex(HashMap::new());
+        for person in people {
+            index.insert::<T>(person);
+        }
+        index
+    }
+
+    /// The codes a name is indexed by: its primary code, and its alternate one if that's different.
+    fn codes(name: &str) -> impl Iterator<Item=String> {
+        let (primary, alternate) = phonetic::double_metaphone(name);
+        let alternate = Some(alternate).filter(|a| *a != primary);
+        Some(primary).into_iter().chain(alternate).filter(|code| !code.is_empty())
+    }
+
+    /// Add a person by the field that `T` measures.
+    fn insert<T: PersonField>(&mut self, person: &PersonRef) {
+        for code in Self::codes(T::field(person)) {
+            self.0.entry(code).or_default().push(person.clone());
+        }
+    }
+
+    /// Remove a person added by `insert`.
+    fn remove<T: PersonField>(&mut self, person: &PersonRef) {
+        for code in Self::codes(T::field(person)) {
+            if let Some(people) = self.0.get_mut(&code) {
+                people.retain(|p| p != person);
+                if people.is_empty() {
+                    self.0.remove(&code);
+                }
+            }
+        }
+    }
+
+    /// Find each person whose name shares a code with `name`.
+    fn sounds_like(&self, name: &str) -> Vec<&PersonRef> {
+        let mut found: Vec<&PersonRef> = Self::codes(name)
+            .filter_map(|code| self.0.get(&code))
+            .flatten()
+            .collect();
+        found.sort_unstable_by_key(|person| Arc::as_ptr(&person.0));
+        found.dedup();
+        found
+    }
+}
 
 /// Counts the number of times a key is inserted and tracks the sum of their distances.
 struct DistCounter<T>(HashMap<T, (u64, usize)>);
@@ -143,14 +398,25 @@ impl<T> Deref for DistCounter<T> {
 ///   - For each value entered, can we find a matching member
 ///     based on IGRA #, name, or a combination?
 ///   - For found listed partners, did that partner register & list this person?
-pub struct EntryValidator<'a> {
-    by_igra_num: BKTree<ByIGRANum<'a>, usize>,
-    by_first_name: BKTree<ByFirstName<'a>, usize>,
-    by_last_name: BKTree<ByLastName<'a>, usize>,
-    by_perf_first: BKTree<ByPerformanceFirst<'a>, usize>,
-    by_perf_last: BKTree<ByPerformanceLast<'a>, usize>,
-
-    damlev: DamerauLevenshtein,
+///
+/// Building one takes a while for a large database, so it's built once per version of the table
+/// and then shared, rather than built for each search.
+pub struct EntryValidator {
+    people: Vec<PersonRef>,
+
+    by_igra_num: BKTree<ByIGRANum, usize>,
+    by_first_name: BKTree<ByFirstName, usize>,
+    by_last_name: BKTree<ByLastName, usize>,
+    by_perf_first: BKTree<ByPerformanceFirst, usize>,
+    by_perf_last: BKTree<ByPerformanceLast, usize>,
+    by_person: BKTree<ByPerson, usize>,
+
+    sounds_like_first: PhoneticIndex,
+    sounds_like_last: PhoneticIndex,
+    sounds_like_perf_first: PhoneticIndex,
+    sounds_like_perf_last: PhoneticIndex,
+
+    nicknames: Nicknames,
 }
 
 /// This is the report structure returned from validation.
@@ -163,6 +429,10 @@ pub struct Report<'a> {
     /// Relevant records include entries matched to a single record
     /// as well as close matches to information given in entries.
     pub relevant: HashMap<&'a str, &'a PersonRecord>,
+    /// When the personnel table was last updated, as `YYYY-MM-DD`, if known.
+    pub personnel_updated: Option<String>,
+    /// Problems with the validation as a whole, rather than with any one entry.
+    pub warnings: Vec<String>,
 }
 
 /// Checks if two strings are equal ignoring ascii case and leading/trailing whitespace.
@@ -197,50 +467,152 @@ pub fn split_partner(s: &str) -> (Option<&str>, &str) {
             let (name, num) = s.split_at(end);
             (Some(num.trim()), name.trim_matches(ignored))
         }
-        (None, None) => (Some(s), &""),
+        (None, None) => (Some(s), ""),
         _ => (None, s.trim_matches(ignored)),
     }
 }
 
 
-impl<'a> EntryValidator<'a> {
-    pub(crate) fn new(people: &'a Vec<PersonRecord>) -> Self {
-        let mut ev = EntryValidator {
-            by_igra_num: BKTree::new(),
-            by_first_name: BKTree::new(),
-            by_last_name: BKTree::new(),
-            by_perf_first: BKTree::new(),
-            by_perf_last: BKTree::new(),
-            damlev: DamerauLevenshtein::new(),
+impl EntryValidator {
+    pub(crate) fn new(people: Vec<PersonRecord>, nicknames: Nicknames) -> Self {
+        Self::build(people.into_iter().map(|p| PersonRef(Arc::new(p))).collect(), nicknames)
+    }
+
+    /// Make a validator for a new version of the personnel table, starting from this one.
+    ///
+    /// Records that didn't change are shared with this validator,
+    /// and only the people who were added, removed, or changed are updated in its trees,
+    /// which is much faster than building them again when the legacy app edited a few records.
+    /// If more than half of the people changed, the trees are built from scratch instead,
+    /// since they'd otherwise be mostly removed and unbalanced entries.
+    pub(crate) fn with_people(&self, people: Vec<PersonRecord>, nicknames: Nicknames) -> Self {
+        let mut old: HashMap<&str, Vec<&PersonRef>> = HashMap::new();
+        for person in &self.people {
+            old.entry(person.igra_number.as_str()).or_default().push(person);
+        }
+
+        // Reuse the old records that have the same values in every column.
+        let people: Vec<(PersonRef, bool)> = people.into_iter()
+            .map(|person| {
+                let record = person.to_record();
+                let same = old.get_mut(person.igra_number.as_str()).and_then(|candidates| {
+                    let i = candidates.iter().position(|c| c.to_record() == record)?;
+                    Some(candidates.swap_remove(i).clone())
+                });
+                match same {
+                    Some(same) => (same, true),
+                    None => (PersonRef(Arc::new(person)), false),
+                }
+            })
+            .collect();
+
+        // The rest replace an old record with the same IGRA number, if there's one left, or are new.
+        let mut changes: Vec<(Option<&PersonRef>, Option<PersonRef>)> = people.iter()
+            .filter(|(_, reused)| !reused)
+            .map(|(person, _)| (old.get_mut(person.igra_number.as_str()).and_then(Vec::pop), Some(person.clone())))
+            .collect();
+        let people: Vec<PersonRef> = people.into_iter().map(|(person, _)| person).collect();
+        changes.extend(old.into_values().flatten().map(|removed| (Some(removed), None)));
+        log::info!("{} of {} people were added, removed, or changed", changes.len(), people.len());
+
+        if changes.len() > people.len() / 2 {
+            return Self::build(people, nicknames);
+        }
+        let mut validator = EntryValidator {
+            by_igra_num: self.by_igra_num.clone(),
+            by_first_name: self.by_first_name.clone(),
+            by_last_name: self.by_last_name.clone(),
+            by_perf_first: self.by_perf_first.clone(),
+            by_perf_last: self.by_perf_last.clone(),
+            by_person: self.by_person.clone(),
+            sounds_like_first: self.sounds_like_first.clone(),
+            sounds_like_last: self.sounds_like_last.clone(),
+            sounds_like_perf_first: self.sounds_like_perf_first.clone(),
+            sounds_like_perf_last: self.sounds_like_perf_last.clone(),
+            nicknames,
+            people,
         };
+        for (old, new) in changes {
+            validator.replace_person(old, new.as_ref());
+        }
+        validator
+    }
+
+    /// Replace a person in each tree and index, or only remove or only insert them when the other is `None`.
+    fn replace_person(&mut self, old: Option<&PersonRef>, new: Option<&PersonRef>) {
+        fn replace<T: bktree::Metric<Output=usize> + PartialEq>(tree: &mut BKTree<T, usize>, old: Option<T>, new: Option<T>) {
+            match (old, new) {
+                (Some(old), Some(new)) => { tree.update(&old, new); }
+                (Some(old), None) => { tree.remove(&old); }
+                (None, Some(new)) => tree.insert(new),
+                (None, None) => {}
+            }
+        }
+        let by_person = |person: &PersonRef| ByPerson { key: PersonKey::from_record(person), person: person.clone() };
+
+        replace(&mut self.by_igra_num, old.cloned().map(ByIGRANum), new.cloned().map(ByIGRANum));
+        replace(&mut self.by_first_name, old.cloned().map(ByFirstName), new.cloned().map(ByFirstName));
+        replace(&mut self.by_last_name, old.cloned().map(ByLastName), new.cloned().map(ByLastName));
+        replace(&mut self.by_perf_first, old.cloned().map(ByPerformanceFirst), new.cloned().map(ByPerformanceFirst));
+        replace(&mut self.by_perf_last, old.cloned().map(ByPerformanceLast), new.cloned().map(ByPerformanceLast));
+        replace(&mut self.by_person, old.map(by_person), new.map(by_person));
+
+        fn reindex<T: PersonField>(index: &mut PhoneticIndex, old: Option<&PersonRef>, new: Option<&PersonRef>) {
+            if let Some(old) = old {
+                index.remove::<T>(old);
+            }
+            if let Some(new) = new {
+                index.insert::<T>(new);
+            }
+        }
+        reindex::<ByFirstName>(&mut self.sounds_like_first, old, new);
+        reindex::<ByLastName>(&mut self.sounds_like_last, old, new);
+        reindex::<ByPerformanceFirst>(&mut self.sounds_like_perf_first, old, new);
+        reindex::<ByPerformanceLast>(&mut self.sounds_like_perf_last, old, new);
+    }
 
-        for p in people {
-            ev.by_igra_num.insert(ByIGRANum(&p));
-            ev.by_first_name.insert(ByFirstName(&p));
-            ev.by_last_name.insert(ByLastName(&p));
-            ev.by_perf_first.insert(ByPerformanceFirst(&p));
-            ev.by_perf_last.insert(ByPerformanceLast(&p));
+    /// Build a validator's trees and indexes from scratch.
+    fn build(people: Vec<PersonRef>, nicknames: Nicknames) -> Self {
+        // The people are usually sorted by IGRA number, so build each tree all at once
+        // rather than inserting them in that order.
+        let refs = || people.iter().cloned();
+        EntryValidator {
+            by_igra_num: refs().map(ByIGRANum).collect(),
+            by_first_name: refs().map(ByFirstName).collect(),
+            by_last_name: refs().map(ByLastName).collect(),
+            by_perf_first: refs().map(ByPerformanceFirst).collect(),
+            by_perf_last: refs().map(ByPerformanceLast).collect(),
+            by_person: refs().map(|person| ByPerson { key: PersonKey::from_record(&person), person }).collect(),
+            sounds_like_first: PhoneticIndex::new::<ByFirstName>(&people),
+            sounds_like_last: PhoneticIndex::new::<ByLastName>(&people),
+            sounds_like_perf_first: PhoneticIndex::new::<ByPerformanceFirst>(&people),
+            sounds_like_perf_last: PhoneticIndex::new::<ByPerformanceLast>(&people),
+            nicknames,
+            people,
         }
+    }
 
-        ev
+    /// The people this validator searches.
+    pub fn people(&self) -> &[PersonRef] {
+        &self.people
     }
 
     /// Validates the registration entries against the people database.
-    pub fn validate_entries(&self, entries: &'a Vec<Registration>) -> Report<'a> {
+    pub fn validate_entries<'a>(&'a self, entries: &'a Vec<Registration>) -> Report<'a> {
         let today = chrono::Utc::now().naive_utc().date();
 
         let mut results: Vec<Processed> = Vec::with_capacity(entries.len());
         let mut relevant = HashMap::<&str, &PersonRecord>::new();
 
         for r in entries {
-            let mut p = Processed::new(&r);
+            let mut p = Processed::new(r);
 
             // Validate their age is at least 18.
             if r.contestant
                 .dob
                 .naive_date()
                 .and_then(|d| today.years_since(d))
-                .map_or(true, |age| age < 18)
+                .is_none_or(|age| age < 18)
             {
                 p.issues.push(Suggestion {
                     problem: Problem::NotOldEnough,
@@ -293,23 +665,23 @@ impl<'a> EntryValidator<'a> {
                 let other = match &sugg.fix {
                     Fix::UseThisRecord(igra_num) | Fix::AddRegistration(igra_num) => self
                         .by_igra_num
-                        .find_closest(0, |r| self.damlev.distance(&r.0.igra_number, &igra_num.0)),
+                        .find_closest(0, |r| damerau_levenshtein(&r.0.igra_number, &igra_num.0)),
                     _ => None,
                 };
 
                 if let Some((0, o)) = other {
-                    relevant.insert(&o.0.igra_number, o.0);
+                    relevant.insert(&o.0.igra_number, &*o.0);
                 }
             }
 
             v.issues.append(mi);
         }
 
-        Report { results, relevant }
+        Report { results, relevant, personnel_updated: None, warnings: Vec::new() }
     }
 
-    fn validate_events(
-        &self,
+    fn validate_events<'a>(
+        &'a self,
         proc: &mut Processed<'a>,
         relevant: &mut HashMap<&'a str, &'a PersonRecord>,
     ) {
@@ -335,7 +707,7 @@ impl<'a> EntryValidator<'a> {
                 continue;
             };
 
-            self.validate_partners(proc, &event, db_event, relevant);
+            self.validate_partners(proc, event, db_event, relevant);
         }
     }
 
@@ -358,6 +730,7 @@ impl<'a> EntryValidator<'a> {
     /// - If it has a comma `,` we split it there assume we have something like "LastName, FirstName".
     /// - Otherwise, if it has a space ` ` we split it there and assume its like "FirstName LastName".
     /// - Otherwise, we just call it "FirstName".
+    ///
     /// Finally, we trim any leading whitespace or additional commas from each part,
     /// and if this leaves the first part empty, their values are swapped.
     /// Note that even if `performance` is be non-empty, these resulting parts might be (e.g. ",, ,").
@@ -371,18 +744,16 @@ impl<'a> EntryValidator<'a> {
     /// If we're only given two-part performance name P (e.g. likely a partner field),
     /// and we're matching against a record R that has an empty last_name or first_name,
     /// we'll accept `P == "R.first_name R.legal_last"` or `P == R.legal_first R.last_name`.
-    pub fn find_person<'b>(&'b self, igra_num: Option<&str>, first: &str, last: &str, performance: &str)
+    ///
+    /// Unlike `find_registrant_candidates`, this searches each name's tree rather than `by_person`:
+    /// partner fields and searches don't say whether a name is legal or performance,
+    /// nor give a birthdate or SSN, and each name may match by sound or as a nickname.
+    pub fn find_person<'a>(&'a self, igra_num: Option<&str>, first: &str, last: &str, performance: &str)
                            -> (bool, Vec<&'a PersonRecord>) {
-        let ignore_chars: &[_] = &[' ', ','];
 
-        let first = first.trim_matches(ignore_chars);
-        let last = last.trim_matches(ignore_chars);
-        let (p_first, p_last) = performance.split_once(',')
-            .map(|(l, f)| { (f, l) })
-            .or_else(|| performance.split_once(' '))
-            .map(|(f, l)| { (f.trim_matches(ignore_chars), l.trim_matches(ignore_chars)) })
-            .map(|(f, l)| { if f.is_empty() { (l, f) } else { (f, l) } })
-            .unwrap_or((performance.trim_matches(ignore_chars), ""));
+        let first = first.trim_matches(NAME_TRIM);
+        let last = last.trim_matches(NAME_TRIM);
+        let (p_first, p_last) = split_name(performance);
 
         let have_legal_input = !(first.is_empty() && last.is_empty());
         let have_perf_input = !(p_first.is_empty() && p_last.is_empty());
@@ -390,12 +761,13 @@ impl<'a> EntryValidator<'a> {
 
         // This function intentionally excludes things that are reasonable, but not specific enough.
         // In fact, it's likely a bit too broad, but that's the sort of thing we can edit in post :)
+        // First names listed as nicknames for each other count as the same name.
         let is_perfect = |rec: &PersonRecord| {
-            let l_lf_match = str_eq(&rec.legal_first, first);
+            let l_lf_match = self.nicknames.same_name(&rec.legal_first, first);
             let l_ll_match = str_eq(&rec.legal_last, last);
-            let p_pf_match = str_eq(&rec.first_name, p_first);
+            let p_pf_match = self.nicknames.same_name(&rec.first_name, p_first);
             let p_pl_match = str_eq(&rec.last_name, p_last);
-            let p_lf_match = str_eq(&rec.legal_first, p_first);
+            let p_lf_match = self.nicknames.same_name(&rec.legal_first, p_first);
             let p_ll_match = str_eq(&rec.legal_last, p_last);
 
             // When we don't need to match the performance name, things are easier.
@@ -418,41 +790,38 @@ impl<'a> EntryValidator<'a> {
         // When we have an IGRA number, try to take the fast path if possible.
         // With a search distance of 0, we'll expand very few nodes,
         // so an exact match can be verified very quickly.
-        let mut p_finder = if let Some(ref igra_num) = igra_num {
+        let mut p_finder = if let Some(igra_num) = igra_num {
             if let Some((_, found)) = self.by_igra_num.find_closest(
-                0, |x| self.damlev.distance(igra_num, &x.0.igra_number)) {
+                0, |x| damerau_levenshtein(igra_num, &x.0.igra_number)) {
 
                 // Return early if we consider this a perfect match.
-                if is_perfect(found.0) {
-                    return (true, vec![found.0]);
+                if is_perfect(&found.0) {
+                    return (true, vec![&*found.0]);
                 }
             }
 
             // Otherwise, we'll need to make a suggestion.
             let mut p_finder = DistCounter::<&PersonRecord>::new();
             self.by_igra_num
-                .find_by(1, |x| self.damlev.distance(igra_num, &x.0.igra_number))
-                .into_iter().for_each(|(d, r)| p_finder.insert(d, r.0));
+                .find_by(1, |x| damerau_levenshtein(igra_num, &x.0.igra_number))
+                .into_iter().for_each(|(d, r)| p_finder.insert(d, &*r.0));
             exp_hits += 1;
             p_finder
         } else {
             DistCounter::<&PersonRecord>::new()
         };
 
-        let search_dist = 3;
         if !first.is_empty() {
             let first = first.to_ascii_uppercase();
-            self.by_first_name
-                .find_by(search_dist, |x| self.damlev.distance(&first, &x.0.legal_first))
-                .into_iter().for_each(|(d, r)| p_finder.insert(d, r.0));
+            self.find_name(&self.by_first_name, &self.sounds_like_first, &first, &self.nicknames.equivalents(&first))
+                .into_iter().for_each(|(d, r)| p_finder.insert(d, r));
             exp_hits += 1;
         }
 
         if !last.is_empty() {
             let last = last.to_ascii_uppercase();
-            self.by_last_name
-                .find_by(search_dist, |x| self.damlev.distance(&last, &x.0.legal_last))
-                .into_iter().for_each(|(d, r)| p_finder.insert(d, r.0));
+            self.find_name(&self.by_last_name, &self.sounds_like_last, &last, &[])
+                .into_iter().for_each(|(d, r)| p_finder.insert(d, r));
             exp_hits += 1;
         }
 
@@ -464,20 +833,17 @@ impl<'a> EntryValidator<'a> {
                 p_first.clone()
             };
 
-            self.by_perf_first
-                .find_by(search_dist, |x| self.damlev.distance(&p_first, &x.0.first_name))
-                .into_iter().for_each(|(d, r)| p_finder.insert(d, r.0));
-            self.by_perf_last
-                .find_by(search_dist, |x| self.damlev.distance(&p_last, &x.0.last_name))
-                .into_iter().for_each(|(d, r)| p_finder.insert(d, r.0));
+            let p_first_nicknames = self.nicknames.equivalents(&p_first);
+            self.find_name(&self.by_perf_first, &self.sounds_like_perf_first, &p_first, &p_first_nicknames)
+                .into_iter().for_each(|(d, r)| p_finder.insert(d, r));
+            self.find_name(&self.by_perf_last, &self.sounds_like_perf_last, &p_last, &[])
+                .into_iter().for_each(|(d, r)| p_finder.insert(d, r));
 
             if first.is_empty() && last.is_empty() {
-                self.by_first_name
-                    .find_by(search_dist, |x| self.damlev.distance(&p_first, &x.0.legal_first))
-                    .into_iter().for_each(|(d, r)| p_finder.insert(d, r.0));
-                self.by_last_name
-                    .find_by(search_dist, |x| self.damlev.distance(&p_last, &x.0.legal_last))
-                    .into_iter().for_each(|(d, r)| p_finder.insert(d, r.0));
+                self.find_name(&self.by_first_name, &self.sounds_like_first, &p_first, &p_first_nicknames)
+                    .into_iter().for_each(|(d, r)| p_finder.insert(d, r));
+                self.find_name(&self.by_last_name, &self.sounds_like_last, &p_last, &[])
+                    .into_iter().for_each(|(d, r)| p_finder.insert(d, r));
                 exp_hits += 2;
             }
         }
@@ -506,8 +872,80 @@ impl<'a> EntryValidator<'a> {
         (false, possible)
     }
 
-    fn validate_partners(
-        &self,
+    /// Find the people with a name in `T`'s field that's either spelled like `name`, as in `near_names`,
+    /// or sounds like it according to `sounds_like`, along with the distance to each.
+    /// People whose name is one of the `equivalents`, such as a nickname for `name`, are found as well.
+    ///
+    /// Names that sound alike are at most `SOUNDS_LIKE_DIST` apart, however they're spelled,
+    /// and equivalent names count as exact matches.
+    fn find_name<'a, T: PersonField>(&'a self, tree: &'a BKTree<T, usize>, sounds_like: &'a PhoneticIndex, name: &str,
+                                     equivalents: &[&str]) -> Vec<(usize, &'a PersonRecord)> {
+        // Each person is found by the address of their shared record.
+        let key = |person: &'a PersonRecord| (person as *const PersonRecord, person);
+        let mut found: HashMap<_, usize> = near_names(tree, name, |x| damerau_levenshtein(name, T::field(x.person())))
+            .into_iter()
+            .map(|(d, x)| (key(x.person()), d))
+            .collect();
+
+        for person in sounds_like.sounds_like(name) {
+            let person = &**person;
+            found.entry(key(person))
+                .and_modify(|d| *d = (*d).min(SOUNDS_LIKE_DIST))
+                .or_insert_with(|| damerau_levenshtein(name, T::field(person)).min(SOUNDS_LIKE_DIST));
+        }
+
+        for equivalent in equivalents {
+            for (_, x) in tree.find_by(0, |x| damerau_levenshtein(equivalent, T::field(x.person()))) {
+                found.insert(key(x.person()), 0);
+            }
+        }
+
+        found.into_iter()
+            .map(|((_, person), d)| (d, person))
+            .collect()
+    }
+
+    /// Find the records that could belong to a registrant, closest first,
+    /// using everything they told us about themself at once.
+    ///
+    /// This searches for the `NEAREST_PEOPLE` records closest to `key`,
+    /// along with the record with the given IGRA number, if there is one,
+    /// and keeps those within the key's `max_distance`.
+    /// A nickname is too far from the name it stands for to find this way,
+    /// so it also searches with each name the key's first name is equivalent to.
+    pub fn find_registrant_candidates<'a>(&'a self, igra_num: Option<&str>, key: &PersonKey)
+                                          -> Vec<&'a PersonRecord> {
+        let max_dist = key.max_distance();
+        let keys: Vec<PersonKey> = iter::once(key.clone())
+            .chain(self.nicknames.equivalents(&key.legal_first).into_iter().map(|name| key.with_first_name(name)))
+            .collect();
+
+        let mut found: Vec<_> = keys.iter()
+            .flat_map(|key| self.by_person.find_k_nearest(NEAREST_PEOPLE, |x| key.distance(&x.key)))
+            .map(|(d, x)| (d, &*x.person))
+            .collect();
+
+        if let Some(igra_num) = igra_num {
+            let numbered = self.by_igra_num.find_closest(
+                0, |x| damerau_levenshtein(igra_num, &x.0.igra_number));
+            if let Some((_, numbered)) = numbered {
+                let numbered_key = PersonKey::from_record(&numbered.0);
+                let d = keys.iter().map(|key| key.distance(&numbered_key)).min().unwrap_or(usize::MAX);
+                found.push((d, &*numbered.0));
+            }
+        }
+
+        // Keep each person only at their closest distance.
+        found.sort_by_key(|(d, _)| *d);
+        let mut seen = HashSet::new();
+        found.into_iter()
+            .filter(|(d, p)| *d <= max_dist && seen.insert(&p.igra_number))
+            .map(|(_, p)| p)
+            .collect()
+    }
+
+    fn validate_partners<'a>(
+        &'a self,
         proc: &mut Processed<'a>,
         event: &Event,
         db_event: RodeoEvent,
@@ -577,8 +1015,8 @@ impl<'a> EntryValidator<'a> {
         }
     }
 
-    fn find_registrant(
-        &self,
+    fn find_registrant<'a>(
+        &'a self,
         proc: &mut Processed<'a>,
         relevant: &mut HashMap<&'a str, &'a PersonRecord>,
     ) {
@@ -612,9 +1050,9 @@ impl<'a> EntryValidator<'a> {
         }
 
         // Search for members that closely match the registration.
-        let (_, mut candidates) = self.find_person(
+        let mut candidates = self.find_registrant_candidates(
             if igra_num.is_empty() { None } else { Some(igra_num) },
-            &who.first_name, &who.last_name, &who.performance_name,
+            &PersonKey::from_contestant(who),
         );
 
         log::debug!("Found {} candidates for '{} {}' aka '{}' with num '{:?}'",
@@ -642,8 +1080,8 @@ impl<'a> EntryValidator<'a> {
         // but this only matters if they don't list an IGRA identifier.
         // If we have their IGRA number, this just gives us certainty it's not a typo.
         let exact = |member: &PersonRecord| {
-            str_eq(&member.legal_first, &first_name)
-                && str_eq(&member.legal_last, &last_name)
+            self.nicknames.same_name(&member.legal_first, first_name)
+                && str_eq(&member.legal_last, last_name)
                 && member.birthdate == dob
                 && member.ssn == ssn
         };
@@ -698,7 +1136,7 @@ impl<'a> EntryValidator<'a> {
             } else {
                 // Even though we don't have a perfect match,
                 // we only have a single probable match.
-                assert!(candidates.len() >= 1, "candidates should not be empty");
+                assert!(!candidates.is_empty(), "candidates should not be empty");
                 m = candidates[0];
 
                 proc.issues.push(Suggestion {
@@ -741,14 +1179,14 @@ impl<'a> EntryValidator<'a> {
 
         if let Some(assn) = who.association.member_assn.split_whitespace().next() {
             log::debug!("Association: {assn}");
-            check(proc, RegF::Association, &m.association, &assn);
+            check(proc, RegF::Association, &m.association, assn);
         } else {
             log::debug!("Association: {}", who.association.member_assn);
             check(proc, RegF::Association, &m.association, &who.association.member_assn);
         }
 
         if let Some((_, ssn)) = m.ssn.rsplit_once('-') {
-            check(proc, RegF::SSN, &ssn, &who.ssn)
+            check(proc, RegF::SSN, ssn, &who.ssn)
         } else {
             check(proc, RegF::SSN, &m.ssn, &who.ssn)
         }
@@ -815,7 +1253,7 @@ impl<'a> EntryValidator<'a> {
 
             // Most of the regions are 'normalized' to a full name,
             // but sometimes we just have a two-letter state abbreviation.
-            let region_matches = m.region().map_or(false, |db_region| {
+            let region_matches = m.region().is_some_and(|db_region| {
                 str_eq(db_region, &who.address.region)
             });
             if !(region_matches || str_eq(&m.state, &who.address.region)) {
@@ -921,11 +1359,27 @@ impl<'a> Processed<'a> {
     }
 }
 
+/// Truncate to at most `n` characters.
 fn at_most(s: &str, n: usize) -> String {
-    if s.len() >= n { &s[..n] } else { s }.to_string()
+    s.chars().take(n).collect()
 }
 
 impl<'a> Report<'a> {
+    /// Record when the personnel table was last updated,
+    /// with a warning if that's before the newest registration was paid,
+    /// since people who joined after the table was copied from the legacy app won't be found.
+    pub fn check_personnel_date(&mut self, updated: NaiveDate, registrations: &[Registration]) {
+        self.personnel_updated = Some(updated.format("%Y-%m-%d").to_string());
+
+        let newest = registrations.iter().filter_map(|r| r.estimate_payment_date()).max();
+        if let Some(newest) = newest.filter(|&d| d > updated) {
+            self.warnings.push(format!(
+                "The personnel table was last updated on {updated}, before the newest registration on {newest}; \
+                 people who joined since then won't be found."
+            ));
+        }
+    }
+
     /// Turn the processed records into their dBASE equivalent.
     ///
     /// Note that the dBASE records aren't necessarily valid,
@@ -939,7 +1393,7 @@ impl<'a> Report<'a> {
 
             let (prepaid_amount, prepaid_date) = if reg.payment.total > 0 {
                 (
-                    Some(Decimal::from_parts(((&reg.payment.total) / 100) as i32, (&reg.payment.total % 100) as u32)),
+                    Some(Decimal::new(reg.payment.total as i64, 2)), // in cents
                     Some(reg.estimate_payment_date().unwrap_or(today)),
                 )
             } else {
@@ -973,7 +1427,10 @@ impl<'a> Report<'a> {
                 } else {
                     None
                 }
-            }).collect();
+            }).collect::<Vec<_>>();
+            // Team events have their own columns, named for the days "SA" and "SU".
+            let (team_events, events) = events.into_iter()
+                .partition(|e| e.name.ends_with("_SA") || e.name.ends_with("_SU"));
 
             if let Some(db) = processed.found.and_then(|num| self.relevant.get(num)) {
                 RegistrationRecord {
@@ -988,9 +1445,11 @@ impl<'a> Report<'a> {
                     sex: db.sex.clone(),
                     // rodeo_association: at_most(rodeo_association, 2),
                     events,
+                    team_events,
                     stalls,
                     prepaid_amount,
                     prepaid_date,
+                    note_to_director: reg.contestant.note_to_director.clone(),
 
                     ..Default::default()
                 }
@@ -998,7 +1457,7 @@ impl<'a> Report<'a> {
                 let c = &reg.contestant;
                 let association = memchr(b' ', c.association.member_assn.as_bytes())
                     .map_or_else(|| at_most(&c.association.member_assn, 5),
-                                 |i| c.association.member_assn[0..i.min(5)].to_string());
+                                 |i| at_most(&c.association.member_assn[..i], 5));
                 let division = IGRA_DIVISIONS.get(&association).unwrap_or(&" ").to_string();
 
                 let (first_name, last_name) = if c.performance_name.is_empty() {
@@ -1021,9 +1480,11 @@ impl<'a> Report<'a> {
                     association,
                     division,
                     events,
+                    team_events,
                     stalls,
                     prepaid_amount,
                     prepaid_date,
+                    note_to_director: reg.contestant.note_to_director.clone(),
 
                     ..Default::default()
                 }
@@ -1057,7 +1518,7 @@ fn validate_cross_reg(
     for (person_b, a_events_with_b) in &entry_a.confirmed_partners {
         // Try to find the entry for B, the partner of A.
         let entry_b = entries.iter().find(|other| {
-            other.found.map_or(false, |other_igra_num| {
+            other.found.is_some_and(|other_igra_num| {
                 other_igra_num == person_b.igra_number
             })
         });
@@ -1081,7 +1542,7 @@ fn validate_cross_reg(
                 continue;
             }
 
-            let b_listed_a = b_to_a.map_or(false, |listings| {
+            let b_listed_a = b_to_a.is_some_and(|listings| {
                 listings
                     .iter()
                     .any(|(b_event, b_round, _)| b_event == event_a && b_round == round_a)
@@ -1106,7 +1567,7 @@ fn validate_cross_reg(
 }
 
 /// Registration fields.
-#[allow(unused)]
+#[allow(unused, clippy::upper_case_acronyms)]
 #[derive(Eq, Hash, PartialEq, Debug, Copy, Clone, Serialize, Deserialize)]
 pub enum RegF {
     IsMember,
@@ -1220,34 +1681,61 @@ pub struct Partner<'a> {
 }
 
 /// Personal data from the current (old, DOS-based) registration database.
+///
+/// The `dbase` attributes give the PERSONEL table layout, in column order.
+/// `RODEO_NUM` is ignored when reading, since it now must match `IGRA_NUM`, and isn't written.
 #[allow(unused)]
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize, DBaseRecord)]
+#[dbase(ignore = "RODEO_NUM")]
 pub struct PersonRecord {
+    #[dbase(name = "IGRA_NUM", length = 4)]
     pub igra_number: String,
+    #[dbase(name = "STATE_ASSN", length = 5)]
     pub association: String,
+    #[dbase(name = "BIRTH_DATE", length = 8)]
     pub birthdate: String,
+    #[dbase(length = 11)]
     pub ssn: String,
+    #[dbase(length = 1)]
     pub division: String,
+    #[dbase(length = 17)]
     pub last_name: String,
+    #[dbase(length = 10)]
     pub first_name: String,
+    #[dbase(length = 17)]
     pub legal_last: String,
+    #[dbase(name = "LEGALFIRST", length = 10)]
     pub legal_first: String,
+    #[dbase(length = 1)]
     pub id_checked: String,
+    #[dbase(length = 1)]
     pub sex: String,
 
+    #[dbase(length = 30)]
     pub address: String,
+    #[dbase(length = 18)]
     pub city: String,
+    #[dbase(length = 2)]
     pub state: String,
+    #[dbase(length = 10)]
     pub zip: String,
+    #[dbase(length = 13)]
     pub home_phone: String,
+    #[dbase(length = 13)]
     pub cell_phone: String,
+    #[dbase(name = "E_MAIL", length = 50)]
     pub email: String,
+    #[dbase(length = 1)]
     pub status: String,
 
+    #[dbase(name = "FIRSTRODEO", length = 8)]
     pub first_rodeo: String,
+    #[dbase(name = "LASTUPDATE", length = 8)]
     pub last_updated: String,
+    #[dbase(length = 8)]
     pub sort_date: String,
     #[serde(skip)]
+    #[dbase(name = "EXT_DOLLAR", length = 7, decimals = 2)]
     pub ext_dollars: Decimal,
 }
 
@@ -1275,65 +1763,99 @@ impl Eq for PersonRecord {}
 /// To better reflect how the table is actually used,
 /// this doesn't exactly match the table layout, nor hold all the same fields.
 ///
-/// When reading the table, `RODEO_NUM` is ignored.
-/// During writing, it's filled with `igra_number`, matching the 2020 rule change.
-///
-/// Event information is converted to its own `EventRecord` struct.
-/// During writing, events in that collection fill the relevant fields, and the rest are left blank.
-/// During reading, events for which a person is registered are collected in the `events` collection.
-/// A "T" or "S" column is converted to an `EventMetric::Time` or `EventMetric::Score`, respectively.
-/// That value is stored in the `outcome` field, though its initialized as `None`.
+/// The `dbase` attributes give the registration table layout, in column order.
+/// The IGRA number and event columns are stored by the `ColumnGroup`s named in the attributes:
+/// - When reading the table, `RODEO_NUM` is ignored.
+///   During writing, it's filled with `igra_number`, matching the 2020 rule change.
+/// - Event information is converted to its own `EventRecord` struct.
+///   During writing, events in that collection fill the relevant fields, and the rest are left blank.
+///   During reading, events for which a person is registered are collected in the `events` collection.
+///   A "T" or "S" column is converted to an `EventMetric::Time` or `EventMetric::Score`, respectively.
+///   That value is stored in the `outcome` field, though its initialized as `None`.
 #[allow(unused)]
-#[derive(Debug, Default)]
+#[derive(Debug, Default, DBaseRecord)]
 pub struct RegistrationRecord {
+    #[dbase(columns = IgraNumbers)]
     igra_number: String,
+    #[dbase(name = "STATE_ASSN", length = 5)]
     association: String,
+    #[dbase(length = 11)]
     ssn: String,
+    #[dbase(length = 1)]
     division: String,
+    #[dbase(length = 17)]
     last_name: String,
+    #[dbase(length = 10)]
     first_name: String,
+    #[dbase(length = 18)]
     city: String,
+    #[dbase(length = 2)]
     state: String,
+    #[dbase(length = 1)]
     sex: String,
 
+    #[dbase(columns = IndividualEvents)]
     events: Vec<EventRecord>,
 
     // I think these are either completely unused or used as scratch fields by the clipper app.
+    #[dbase(name = "RODEO_SCOR", length = 5, decimals = 1)]
     rodeo_score: Option<Decimal>,
+    #[dbase(length = 7, decimals = 2)]
     rodeo_time: Option<Decimal>,
+    #[dbase(name = "RODEO_ASSO", length = 2)]
     rodeo_association: String,
+
+    /// Team events come after the scratch fields, so they're kept apart from `events`.
+    #[dbase(columns = TeamEvents)]
+    team_events: Vec<EventRecord>,
+
+    // ?? In the few rodeo files I have, I see FLAG1 sometimes 'X', but not any instances of FLAG2 set.
+    // They might be another scratch space field used by the clipper application.
+    #[dbase(name = "FLAG1", length = 1)]
     flag_1: String,
+    #[dbase(name = "FLAG2", length = 1)]
     flag_2: String,
 
+    /// Number of stalls they requested.
+    #[dbase(name = "STALL_FLAG", length = 1)]
     stalls: Decimal,
+    #[dbase(length = 1)]
     extra_flag: String, // also seems unused
 
+    // Total points. "EXT" seems unused.
+    #[dbase(length = 3)]
     sat_points: Decimal,
+    #[dbase(length = 3)]
     sun_points: Decimal,
+    #[dbase(length = 3)]
     ext_points: Decimal,
+    #[dbase(length = 4)]
     tot_points: Decimal,
 
-    prepaid_amount: Option<Decimal>,
+    #[dbase(name = "PRE_DATE", length = 8)]
     prepaid_date: Option<NaiveDate>,
+    #[dbase(name = "PRE_PAID", length = 7, decimals = 2)]
+    prepaid_amount: Option<Decimal>,
 
+    // Total winnings. "EXT" seems unused.
+    #[dbase(name = "SAT_DOLLAR", length = 7, decimals = 2)]
     sat_dollars: Decimal,
+    #[dbase(name = "SUN_DOLLAR", length = 7, decimals = 2)]
     sun_dollars: Decimal,
+    #[dbase(name = "EXT_DOLLAR", length = 7, decimals = 2)]
     ext_dollars: Decimal,
+    #[dbase(name = "TOT_DOLLAR", length = 7, decimals = 2)]
     tot_dollars: Decimal,
+
+    /// Not part of the legacy layout; see `WithNotes`.
+    #[dbase(columns = DirectorNotes)]
+    note_to_director: String,
 }
 
 impl RegistrationRecord {
-    /// Return the event record matching the event name, if we have it.
-    fn get_event(&self, name: &str) -> Option<&EventRecord> {
-        self.events.iter().find(|e| e.name == name)
-    }
-
-    fn add_fields_for(&self, name: &str, entered_first: bool, n_partners: usize, data: &mut Vec<Field>) {
-        if let Some(e) = self.get_event(name) {
-            e.add_fields(entered_first, n_partners, data);
-        } else {
-            EventRecord::add_empty_fields(entered_first, n_partners, data);
-        }
+    /// All the events this person entered, individual events first.
+    fn all_events(&self) -> impl Iterator<Item = &EventRecord> {
+        self.events.iter().chain(&self.team_events)
     }
 }
 
@@ -1353,6 +1875,15 @@ pub struct EventRecord {
 }
 
 impl EventRecord {
+    /// Add data fields for the named event, which are empty if it isn't in `events`.
+    fn add_fields_for(events: &[EventRecord], name: &str, entered_first: bool, n_partners: usize, data: &mut Vec<Field>) {
+        if let Some(e) = events.iter().find(|e| e.name == name) {
+            e.add_fields(entered_first, n_partners, data);
+        } else {
+            EventRecord::add_empty_fields(entered_first, n_partners, data);
+        }
+    }
+
     /// Add data fields for this event, indicating it is entered.
     fn add_fields(&self, entered_first: bool, n_partners: usize, data: &mut Vec<Field>) {
         if entered_first {
@@ -1425,6 +1956,7 @@ pub enum EventMetric {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IGRANumber(String);
 
+#[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct LegalLast(String);
 
@@ -1444,602 +1976,310 @@ impl Display for LegalLast {
 pub fn read_personnel<R: io::Read>(
     table: TableReader<Header<R>>,
 ) -> DBaseResult<Vec<PersonRecord>> {
-    let mut people = Vec::<PersonRecord>::with_capacity(table.n_records());
-    let mut records = table.records();
-
-    while let Some(record) = records.next() {
-        let record = record?;
-
-        let mut person = PersonRecord::default();
-        for field in record {
-            let field = field?;
-            match (field.name, field.value) {
-                ("IGRA_NUM", Field::Character(s)) => person.igra_number = s,
-                // Ignore RODEO_NUM, which now must match IGRA_NUM.
-                ("STATE_ASSN", Field::Character(s)) => person.association = s,
-                ("BIRTH_DATE", Field::Character(s)) => person.birthdate = s,
-                ("SSN", Field::Character(s)) => person.ssn = s,
-                ("DIVISION", Field::Character(s)) => person.division = s,
-                ("LAST_NAME", Field::Character(s)) => person.last_name = s,
-                ("FIRST_NAME", Field::Character(s)) => person.first_name = s,
-                ("LEGAL_LAST", Field::Character(s)) => person.legal_last = s,
-                ("LEGALFIRST", Field::Character(s)) => person.legal_first = s,
-                ("ID_CHECKED", Field::Character(s)) => person.id_checked = s,
-                ("SEX", Field::Character(s)) => person.sex = s,
-                ("ADDRESS", Field::Character(s)) => person.address = s,
-                ("CITY", Field::Character(s)) => person.city = s,
-                ("STATE", Field::Character(s)) => person.state = s,
-                ("ZIP", Field::Character(s)) => person.zip = s,
-                ("HOME_PHONE", Field::Character(s)) => person.home_phone = s,
-                ("CELL_PHONE", Field::Character(s)) => person.cell_phone = s,
-                ("E_MAIL", Field::Character(s)) => person.email = s,
-                ("STATUS", Field::Character(s)) => person.status = s,
-                ("FIRSTRODEO", Field::Character(s)) => person.first_rodeo = s,
-                ("LASTUPDATE", Field::Character(s)) => person.last_updated = s,
-                ("SORT_DATE", Field::Character(s)) => person.sort_date = s,
-                ("EXT_DOLLAR", Field::Numeric(Some(n))) => person.ext_dollars = n,
-                ("EXT_DOLLAR", Field::Numeric(None)) => {}
-                (n, v) => {
-                    panic!("Unknown field: {n} with value '{v:?}'");
-                }
-            }
-        }
+    // TODO: add "full name" fields to the record & create them manually.
+    let mut people = table.read_all::<PersonRecord>()?;
+    people.sort_by(|a, b| a.igra_number.cmp(&b.igra_number));
+    Ok(people)
+}
 
-        // TODO: add "full name" fields to the record & create them manually.
+/// Read the personnel database like `read_personnel`, but without blocking the runtime,
+/// and return unexpected columns as warnings instead of failing.
+///
+/// Progress is logged as records are read.
+pub async fn load_personnel<R: AsyncRead + Unpin>(
+    table: AsyncTableReader<R>,
+) -> DBaseResult<(Vec<PersonRecord>, BTreeSet<SchemaWarning>)> {
+    let n_records = table.n_records();
+    let step = (n_records / 10).max(1);
+    let mut people = Vec::with_capacity(n_records);
+    let mut warnings = BTreeSet::new();
+
+    let records = table.records_lenient::<PersonRecord>();
+    futures_util::pin_mut!(records);
+    while let Some((person, record_warnings)) = records.try_next().await? {
         people.push(person);
+        warnings.extend(record_warnings);
+        if people.len() % step == 0 {
+            log::info!("Loaded {} of {n_records} personnel records", people.len());
+        }
     }
 
     people.sort_by(|a, b| a.igra_number.cmp(&b.igra_number));
-    Ok(people)
+    Ok((people, warnings))
 }
 
+/// A table layout that the converter reads, to check tables against.
+pub struct Layout {
+    pub name: &'static str,
+    pub fields: Vec<FieldDescriptor>,
+    /// Columns that tables may have or leave out.
+    pub optional: &'static [&'static str],
+}
 
-impl DBaseRecord for PersonRecord {
-    fn describe(&self) -> Vec<FieldDescriptor> {
-        vec![
-            FieldDescriptor {
-                name: "IGRA_NUM".to_string(),
-                field_type: FieldType::Character,
-                length: 4,
-                decimal_count: 0,
-                work_area_id: 0,
-                example: 1,
-            },
-            FieldDescriptor {
-                name: "STATE_ASSN".to_string(),
-                field_type: FieldType::Character,
-                length: 5,
-                decimal_count: 0,
-                work_area_id: 0,
-                example: 1,
-            },
-            FieldDescriptor {
-                name: "BIRTH_DATE".to_string(),
-                field_type: FieldType::Character,
-                length: 8,
-                decimal_count: 0,
-                work_area_id: 0,
-                example: 1,
-            },
-            FieldDescriptor {
-                name: "SSN".to_string(),
-                field_type: FieldType::Character,
-                length: 11,
-                decimal_count: 0,
-                work_area_id: 0,
-                example: 1,
-            },
-            FieldDescriptor {
-                name: "DIVISION".to_string(),
-                field_type: FieldType::Character,
-                length: 1,
-                decimal_count: 0,
-                work_area_id: 0,
-                example: 1,
-            },
-            FieldDescriptor {
-                name: "LAST_NAME".to_string(),
-                field_type: FieldType::Character,
-                length: 17,
-                decimal_count: 0,
-                work_area_id: 0,
-                example: 1,
-            },
-            FieldDescriptor {
-                name: "FIRST_NAME".to_string(),
-                field_type: FieldType::Character,
-                length: 10,
-                decimal_count: 0,
-                work_area_id: 0,
-                example: 1,
-            },
-            FieldDescriptor {
-                name: "LEGAL_LAST".to_string(),
-                field_type: FieldType::Character,
-                length: 17,
-                decimal_count: 0,
-                work_area_id: 0,
-                example: 1,
-            },
-            FieldDescriptor {
-                name: "LEGALFIRST".to_string(),
-                field_type: FieldType::Character,
-                length: 10,
-                decimal_count: 0,
-                work_area_id: 0,
-                example: 1,
-            },
-            FieldDescriptor {
-                name: "ID_CHECKED".to_string(),
-                field_type: FieldType::Character,
-                length: 1,
-                decimal_count: 0,
-                work_area_id: 0,
-                example: 1,
-            },
-            FieldDescriptor {
-                name: "SEX".to_string(),
-                field_type: FieldType::Character,
-                length: 1,
-                decimal_count: 0,
-                work_area_id: 0,
-                example: 1,
-            },
-            FieldDescriptor {
-                name: "ADDRESS".to_string(),
-                field_type: FieldType::Character,
-                length: 30,
-                decimal_count: 0,
-                work_area_id: 0,
-                example: 1,
-            },
-            FieldDescriptor {
-                name: "CITY".to_string(),
-                field_type: FieldType::Character,
-                length: 18,
-                decimal_count: 0,
-                work_area_id: 0,
-                example: 1,
-            },
-            FieldDescriptor {
-                name: "STATE".to_string(),
-                field_type: FieldType::Character,
-                length: 2,
-                decimal_count: 0,
-                work_area_id: 0,
-                example: 1,
-            },
-            FieldDescriptor {
-                name: "ZIP".to_string(),
-                field_type: FieldType::Character,
-                length: 10,
-                decimal_count: 0,
-                work_area_id: 0,
-                example: 1,
-            },
-            FieldDescriptor {
-                name: "HOME_PHONE".to_string(),
-                field_type: FieldType::Character,
-                length: 13,
-                decimal_count: 0,
-                work_area_id: 0,
-                example: 1,
-            },
-            FieldDescriptor {
-                name: "CELL_PHONE".to_string(),
-                field_type: FieldType::Character,
-                length: 13,
-                decimal_count: 0,
-                work_area_id: 0,
-                example: 1,
-            },
-            FieldDescriptor {
-                name: "E_MAIL".to_string(),
-                field_type: FieldType::Character,
-                length: 50,
-                decimal_count: 0,
-                work_area_id: 0,
-                example: 1,
-            },
-            FieldDescriptor {
-                name: "STATUS".to_string(),
-                field_type: FieldType::Character,
-                length: 1,
-                decimal_count: 0,
-                work_area_id: 0,
-                example: 1,
-            },
-            FieldDescriptor {
-                name: "FIRSTRODEO".to_string(),
-                field_type: FieldType::Character,
-                length: 8,
-                decimal_count: 0,
-                work_area_id: 0,
-                example: 1,
-            },
-            FieldDescriptor {
-                name: "LASTUPDATE".to_string(),
-                field_type: FieldType::Character,
-                length: 8,
-                decimal_count: 0,
-                work_area_id: 0,
-                example: 1,
-            },
-            FieldDescriptor {
-                name: "SORT_DATE".to_string(),
-                field_type: FieldType::Character,
-                length: 8,
-                decimal_count: 0,
-                work_area_id: 0,
-                example: 1,
-            },
-            FieldDescriptor {
-                name: "EXT_DOLLAR".to_string(),
-                field_type: FieldType::Numeric,
-                length: 7,
-                decimal_count: 2,
-                work_area_id: 0,
-                example: 1,
-            },
-        ]
+/// The layouts of the personnel and registration tables.
+pub fn layouts() -> Vec<Layout> {
+    vec![
+        Layout {
+            name: "personnel",
+            fields: PersonRecord::default().describe(),
+            optional: &["RODEO_NUM"],
+        },
+        Layout {
+            name: "registration",
+            fields: RegistrationRecord::default().describe(),
+            optional: &["NOTES"],
+        },
+    ]
+}
+
+/// Write a person back to the personnel table.
+///
+/// If a record with the same IGRA number exists, it's overwritten in place,
+/// keeping its `EXT_DOLLAR` value; otherwise, the person is appended as a new record.
+/// Returns the record number and whether the person was appended.
+pub fn save_person<F>(editor: &mut TableEditor<F>, person: &mut PersonRecord) -> DBaseResult<(usize, bool)>
+    where F: io::Read + io::Write + io::Seek
+{
+    for record_number in 1..=editor.n_records() {
+        let Some(record) = editor.get(record_number)? else {
+            continue;
+        };
+
+        let existing = PersonRecord::from_record(record)?;
+        if existing.igra_number == person.igra_number {
+            person.ext_dollars = existing.ext_dollars;
+            editor.update(record_number, person)?;
+            return Ok((record_number, false));
+        }
     }
 
-    fn to_record(&self) -> Vec<Field> {
-        vec![
-            Field::Character(self.igra_number.clone()),
-            Field::Character(self.association.clone()),
-            Field::Character(self.birthdate.clone()),
-            Field::Character(self.ssn.clone()),
-            Field::Character(self.division.clone()),
-            Field::Character(self.last_name.clone()),
-            Field::Character(self.first_name.clone()),
-            Field::Character(self.legal_last.clone()),
-            Field::Character(self.legal_first.clone()),
-            Field::Character(self.id_checked.clone()),
-            Field::Character(self.sex.clone()),
-            Field::Character(self.address.clone()),
-            Field::Character(self.city.clone()),
-            Field::Character(self.state.clone()),
-            Field::Character(self.zip.clone()),
-            Field::Character(self.home_phone.clone()),
-            Field::Character(self.cell_phone.clone()),
-            Field::Character(self.email.clone()),
-            Field::Character(self.status.clone()),
-            Field::Character(self.first_rodeo.clone()),
-            Field::Character(self.last_updated.clone()),
-            Field::Character(self.sort_date.clone()),
-            Field::Numeric(Some(self.ext_dollars.clone())),
-        ]
+    Ok((editor.append(person)?, true))
+}
+
+/// Look up a single person by IGRA number using an index on `IGRA_NUM`,
+/// reading only the matching record from the table.
+pub fn lookup_person<R, I>(
+    table: &mut TableReader<RandomAccess<R>>,
+    index: &mut NtxIndex<I>,
+    igra_number: &str,
+) -> DBaseResult<Option<PersonRecord>>
+    where R: io::Read + io::Seek,
+          I: io::Read + io::Seek,
+{
+    let Some(record_number) = index.find(igra_number.as_bytes())? else {
+        return Ok(None);
+    };
+
+    match table.get(record_number as usize)? {
+        Some(record) => PersonRecord::from_record(record).map(Some),
+        None => Ok(None),
     }
 }
 
-impl DBaseRecord for RegistrationRecord {
-    /// Describe the layout of a registration table.
-    ///
-    /// The events each have a series of properties, designated by a letter, applied to each day.
-    /// It's assumed they have the following meaning, though that's not totally clear:
-    /// - E: "Entered" -- This person entered this event.
-    /// - S: "Score" -- score received
-    /// - T: "Time" -- time taken
-    /// - P: "Points" -- points received
-    /// - D: "Dollars" -- dollars won
-    /// - W: "World" -- world points earned
-    fn describe(&self) -> Vec<FieldDescriptor> {
+
+/// Describe a column of the registration table.
+fn column(name: &str, field_type: FieldType, length: usize, decimal_count: u8) -> FieldDescriptor {
+    FieldDescriptor { name: name.to_string(), field_type, length, decimal_count, work_area_id: 0, example: 1 }
+}
+
+/// The `IGRA_NUM` and `RODEO_NUM` columns, which both hold the IGRA number.
+///
+/// `RODEO_NUM` is ignored when reading, since it now must match `IGRA_NUM`.
+struct IgraNumbers;
+
+impl ColumnGroup for IgraNumbers {
+    type Value = String;
+
+    fn describe() -> Vec<FieldDescriptor> {
         vec![
-            // General details
-            FieldDescriptor { name: "IGRA_NUM".to_string(), field_type: FieldType::Character, length: 4, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "RODEO_NUM".to_string(), field_type: FieldType::Character, length: 4, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "STATE_ASSN".to_string(), field_type: FieldType::Character, length: 5, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "SSN".to_string(), field_type: FieldType::Character, length: 11, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "DIVISION".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "LAST_NAME".to_string(), field_type: FieldType::Character, length: 17, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "FIRST_NAME".to_string(), field_type: FieldType::Character, length: 10, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "CITY".to_string(), field_type: FieldType::Character, length: 18, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "STATE".to_string(), field_type: FieldType::Character, length: 2, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "SEX".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-
-            // Bull Riding
-            FieldDescriptor { name: "BULL_E_SAT".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BULL_S_SAT".to_string(), field_type: FieldType::Numeric, length: 4, decimal_count: 1, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BULL_P_SAT".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BULL_D_SAT".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BULL_W_SAT".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BULL_E_SUN".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BULL_S_SUN".to_string(), field_type: FieldType::Numeric, length: 4, decimal_count: 1, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BULL_P_SUN".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BULL_D_SUN".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BULL_W_SUN".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-
-            // Bronc Riding
-            FieldDescriptor { name: "BRON_E_SAT".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BRON_S_SAT".to_string(), field_type: FieldType::Numeric, length: 4, decimal_count: 1, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BRON_P_SAT".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BRON_D_SAT".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BRON_W_SAT".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BRON_E_SUN".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BRON_S_SUN".to_string(), field_type: FieldType::Numeric, length: 4, decimal_count: 1, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BRON_P_SUN".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BRON_D_SUN".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BRON_W_SUN".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-
-            // Steer Riding (used to be "Wild Cow Riding")
-            FieldDescriptor { name: "WCOW_E_SAT".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "WCOW_S_SAT".to_string(), field_type: FieldType::Numeric, length: 4, decimal_count: 1, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "WCOW_P_SAT".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "WCOW_D_SAT".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "WCOW_W_SAT".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "WCOW_E_SUN".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "WCOW_S_SUN".to_string(), field_type: FieldType::Numeric, length: 4, decimal_count: 1, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "WCOW_P_SUN".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "WCOW_D_SUN".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "WCOW_W_SUN".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-
-            // Chute Dogging
-            FieldDescriptor { name: "CHUT_E_SAT".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "CHUT_T_SAT".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "CHUT_P_SAT".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "CHUT_D_SAT".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "CHUT_W_SAT".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "CHUT_E_SUN".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "CHUT_T_SUN".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "CHUT_P_SUN".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "CHUT_D_SUN".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "CHUT_W_SUN".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-
-            // Calf Roping on Foot
-            FieldDescriptor { name: "CALF_E_SAT".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "CALF_T_SAT".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "CALF_P_SAT".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "CALF_D_SAT".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "CALF_W_SAT".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "CALF_E_SUN".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "CALF_T_SUN".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "CALF_P_SUN".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "CALF_D_SUN".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "CALF_W_SUN".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-
-            // Break-away
-            FieldDescriptor { name: "BRAK_E_SAT".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BRAK_T_SAT".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BRAK_P_SAT".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BRAK_D_SAT".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BRAK_W_SAT".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BRAK_E_SUN".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BRAK_T_SUN".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BRAK_P_SUN".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BRAK_D_SUN".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BRAK_W_SUN".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-
-            // Barrel Racing
-            FieldDescriptor { name: "BARR_E_SAT".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BARR_T_SAT".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BARR_P_SAT".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BARR_D_SAT".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BARR_W_SAT".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BARR_E_SUN".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BARR_T_SUN".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BARR_P_SUN".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BARR_D_SUN".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "BARR_W_SUN".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-
-            // Pole Bending
-            FieldDescriptor { name: "POLE_E_SAT".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "POLE_T_SAT".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "POLE_P_SAT".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "POLE_D_SAT".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "POLE_W_SAT".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "POLE_E_SUN".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "POLE_T_SUN".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "POLE_P_SUN".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "POLE_D_SUN".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "POLE_W_SUN".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-
-            // Flag Racing
-            FieldDescriptor { name: "FLAG_E_SAT".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "FLAG_T_SAT".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "FLAG_P_SAT".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "FLAG_D_SAT".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "FLAG_W_SAT".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "FLAG_E_SUN".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "FLAG_T_SUN".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "FLAG_P_SUN".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "FLAG_D_SUN".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "FLAG_W_SUN".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-
-            // ?? I think these are some sort of scratch fields used by the Clipper program.
-            FieldDescriptor { name: "RODEO_SCOR".to_string(), field_type: FieldType::Numeric, length: 5, decimal_count: 1, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "RODEO_TIME".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "RODEO_ASSO".to_string(), field_type: FieldType::Character, length: 2, decimal_count: 0, work_area_id: 0, example: 1 },
-
-            // Team Roping
-            // This event is handled so weirdly to work around how other events are recorded
-            // combined with the fact you can participate twice per go, once as header and again as heeler.
-            // From what I can tell, HD1E is "X" if the person entered as Header, HD2E is the Heeler's IGRA #,
-            // and TIM1/PTS1/DOL1/WOR1 are time/points/dollars/world values when they were heading.
-            // Similarly, HL2E is "X" if  they enter as Heeler, HD2E is the Header's IGRA #,
-            // and TIM2/PTS2/DOL2/WOR2 are time/points/dollars/world values when they were heeling.
-            //
-            // NOTE: The "entered" and "partner" fields are swapped between the two entry types!
-            FieldDescriptor { name: "TR_HD1E_SA".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "TR_HL1E_SA".to_string(), field_type: FieldType::Character, length: 4, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "TR_TIM1_SA".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "TR_PTS1_SA".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "TR_DOL1_SA".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "TR_WOR1_SA".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-
-            FieldDescriptor { name: "TR_HD2E_SA".to_string(), field_type: FieldType::Character, length: 4, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "TR_HL2E_SA".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "TR_TIM2_SA".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "TR_PTS2_SA".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "TR_DOL2_SA".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "TR_WOR2_SA".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-
-            FieldDescriptor { name: "TR_HD1E_SU".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "TR_HL1E_SU".to_string(), field_type: FieldType::Character, length: 4, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "TR_TIM1_SU".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "TR_PTS1_SU".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "TR_DOL1_SU".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "TR_WOR1_SU".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-
-            FieldDescriptor { name: "TR_HD2E_SU".to_string(), field_type: FieldType::Character, length: 4, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "TR_HL2E_SU".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "TR_TIM2_SU".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "TR_PTS2_SU".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "TR_DOL2_SU".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "TR_WOR2_SU".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-
-            // Steer Decorating
-            FieldDescriptor { name: "ST_EVNT_SA".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "ST_PART_SA".to_string(), field_type: FieldType::Character, length: 4, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "ST_TIME_SA".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "ST_POIN_SA".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "ST_DOLL_SA".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "ST_WORL_SA".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "ST_EVNT_SU".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "ST_PART_SU".to_string(), field_type: FieldType::Character, length: 4, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "ST_TIME_SU".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "ST_POIN_SU".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "ST_DOLL_SU".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "ST_WORL_SU".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-
-            // Wild Drag Race
-            FieldDescriptor { name: "DR_EVNT_SA".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "DR_PAR1_SA".to_string(), field_type: FieldType::Character, length: 4, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "DR_PAR2_SA".to_string(), field_type: FieldType::Character, length: 4, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "DR_TIME_SA".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "DR_POIN_SA".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "DR_DOLL_SA".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "DR_WORL_SA".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "DR_EVNT_SU".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "DR_PAR1_SU".to_string(), field_type: FieldType::Character, length: 4, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "DR_PAR2_SU".to_string(), field_type: FieldType::Character, length: 4, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "DR_TIME_SU".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "DR_POIN_SU".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "DR_DOLL_SU".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "DR_WORL_SU".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-
-            // Goat Dressing
-            FieldDescriptor { name: "GO_EVNT_SA".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "GO_PART_SA".to_string(), field_type: FieldType::Character, length: 4, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "GO_TIME_SA".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "GO_POIN_SA".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "GO_DOLL_SA".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "GO_WORL_SA".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "GO_EVNT_SU".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "GO_PART_SU".to_string(), field_type: FieldType::Character, length: 4, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "GO_TIME_SU".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "GO_POIN_SU".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "GO_DOLL_SU".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "GO_WORL_SU".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-
-            // ?? From the Clipper program files, I think this is "Ribbon Roping".
-            // Maybe an old team event we don't do anymore?
-            FieldDescriptor { name: "RR_EVNT_SA".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "RR_PART_SA".to_string(), field_type: FieldType::Character, length: 4, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "RR_TIME_SA".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "RR_POIN_SA".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "RR_DOLL_SA".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "RR_WORL_SA".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "RR_EVNT_SU".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "RR_PART_SU".to_string(), field_type: FieldType::Character, length: 4, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "RR_TIME_SU".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "RR_POIN_SU".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "RR_DOLL_SU".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "RR_WORL_SU".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-
-            // ?? In the few rodeo files I have, I see FLAG1 sometimes 'X', but not any instances of FLAG2 set.
-            // They might be another scratch space field used by the clipper application.
-            FieldDescriptor { name: "FLAG1".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "FLAG2".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 19525, example: 1 },
-
-            // Number of stalls they requested.
-            FieldDescriptor { name: "STALL_FLAG".to_string(), field_type: FieldType::Numeric, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "EXTRA_FLAG".to_string(), field_type: FieldType::Character, length: 1, decimal_count: 0, work_area_id: 0, example: 1 },
-
-            // Total points. "EXT" seems unused.
-            FieldDescriptor { name: "SAT_POINTS".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "SUN_POINTS".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "EXT_POINTS".to_string(), field_type: FieldType::Numeric, length: 3, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "TOT_POINTS".to_string(), field_type: FieldType::Numeric, length: 4, decimal_count: 0, work_area_id: 0, example: 1 },
-
-            // Payment info.
-            FieldDescriptor { name: "PRE_DATE".to_string(), field_type: FieldType::Date, length: 8, decimal_count: 0, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "PRE_PAID".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-
-            // Total winnings. "EXT" seems unused.
-            FieldDescriptor { name: "SAT_DOLLAR".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "SUN_DOLLAR".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "EXT_DOLLAR".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
-            FieldDescriptor { name: "TOT_DOLLAR".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1 },
+            column("IGRA_NUM", FieldType::Character, 4, 0),
+            column("RODEO_NUM", FieldType::Character, 4, 0),
         ]
     }
 
-    fn to_record(&self) -> Vec<Field> {
-        let mut data = Vec::with_capacity(191);
-
-        data.push(Field::Character(self.igra_number.clone()));
-        data.push(Field::Character(self.igra_number.clone())); // RODEO_NUM is IGRA_NUM
-        data.push(Field::Character(self.association.clone()));
-        data.push(Field::Character(self.ssn.clone()));
-        data.push(Field::Character(self.division.clone()));
-        data.push(Field::Character(self.last_name.clone()));
-        data.push(Field::Character(self.first_name.clone()));
-        data.push(Field::Character(self.city.clone()));
-        data.push(Field::Character(self.state.clone()));
-        data.push(Field::Character(self.sex.clone()));
-
-        for event in [
-            "BULL_E_SAT", "BULL_E_SUN",
-            "BRON_E_SAT", "BRON_E_SUN",
-            "WCOW_E_SAT", "WCOW_E_SUN",
-            "CHUT_E_SAT", "CHUT_E_SUN",
-            "CALF_E_SAT", "CALF_E_SUN",
-            "BRAK_E_SAT", "BRAK_E_SUN",
-            "BARR_E_SAT", "BARR_E_SUN",
-            "POLE_E_SAT", "POLE_E_SUN",
-            "FLAG_E_SAT", "FLAG_E_SUN",
-        ] {
-            self.add_fields_for(event, true, 0, &mut data);
-        }
-
-        // Because the fields come in between, we need to split apart the logic for writing events.
-        data.push(Field::Numeric(self.rodeo_score));
-        data.push(Field::Numeric(self.rodeo_time));
-        data.push(Field::Character(self.rodeo_association.clone()));
+    fn to_fields(value: &String, data: &mut Vec<Field>) {
+        data.push(Field::Character(value.clone()));
+        data.push(Field::Character(value.clone())); // RODEO_NUM is IGRA_NUM
+    }
+
+    fn from_field<'a>(value: &mut String, field: FieldValue<'a>) -> DBaseResult<Option<FieldValue<'a>>> {
+        match (field.name, field.value) {
+            ("IGRA_NUM", Field::Character(s)) => *value = s,
+            ("IGRA_NUM", v) => return Err(DBaseErrorKind::UnexpectedValue("IGRA_NUM".to_string(), v)),
+            ("RODEO_NUM", _) => {} // ignored
+            (name, value) => return Ok(Some(FieldValue { name, value })),
+        }
+        Ok(None)
+    }
+}
+
+/// The individual events' abbreviations, in column order, and whether they're scored rather than timed:
+/// Bull Riding, Bronc Riding, Steer Riding (used to be "Wild Cow Riding"),
+/// Chute Dogging, Calf Roping on Foot, Break-away, Barrel Racing, Pole Bending, and Flag Racing.
+const INDIVIDUAL_EVENTS: [(&str, bool); 9] = [
+    ("BULL", true), ("BRON", true), ("WCOW", true),
+    ("CHUT", false), ("CALF", false), ("BRAK", false), ("BARR", false), ("POLE", false), ("FLAG", false),
+];
+
+/// The columns of the individual events, such as `BULL_E_SAT`.
+///
+/// The events each have a series of properties, designated by a letter, applied to each day.
+/// It's assumed they have the following meaning, though that's not totally clear:
+/// - E: "Entered" -- This person entered this event.
+/// - S: "Score" -- score received
+/// - T: "Time" -- time taken
+/// - P: "Points" -- points received
+/// - D: "Dollars" -- dollars won
+/// - W: "World" -- world points earned
+struct IndividualEvents;
+
+impl ColumnGroup for IndividualEvents {
+    type Value = Vec<EventRecord>;
+
+    fn describe() -> Vec<FieldDescriptor> {
+        let mut fields = Vec::with_capacity(INDIVIDUAL_EVENTS.len() * 10);
+        for (abbrev, scored) in INDIVIDUAL_EVENTS {
+            for day in ["SAT", "SUN"] {
+                fields.push(column(&format!("{abbrev}_E_{day}"), FieldType::Character, 1, 0));
+                if scored {
+                    fields.push(column(&format!("{abbrev}_S_{day}"), FieldType::Numeric, 4, 1));
+                } else {
+                    fields.push(column(&format!("{abbrev}_T_{day}"), FieldType::Numeric, 7, 2));
+                }
+                fields.push(column(&format!("{abbrev}_P_{day}"), FieldType::Numeric, 3, 0));
+                fields.push(column(&format!("{abbrev}_D_{day}"), FieldType::Numeric, 7, 2));
+                fields.push(column(&format!("{abbrev}_W_{day}"), FieldType::Numeric, 7, 2));
+            }
+        }
+        fields
+    }
+
+    fn to_fields(events: &Vec<EventRecord>, data: &mut Vec<Field>) {
+        for (abbrev, _) in INDIVIDUAL_EVENTS {
+            for day in ["SAT", "SUN"] {
+                EventRecord::add_fields_for(events, &format!("{abbrev}_E_{day}"), true, 0, data);
+            }
+        }
+    }
+
+    fn from_field<'a>(events: &mut Vec<EventRecord>, field: FieldValue<'a>) -> DBaseResult<Option<FieldValue<'a>>> {
+        read_event_field(events, ["SAT", "SUN"], field)
+    }
+}
+
+/// The team events' abbreviations besides Team Roping, in column order, and their number of partners:
+/// Steer Decorating, Wild Drag Race, Goat Dressing,
+/// and what I think is "Ribbon Roping" from the Clipper program files.
+/// Maybe an old team event we don't do anymore?
+const TEAM_EVENTS: [(&str, usize); 4] = [("ST", 1), ("DR", 2), ("GO", 1), ("RR", 1)];
 
+/// The columns of the team events, such as `ST_EVNT_SA`, which use longer property names.
+///
+/// Team Roping is handled so weirdly to work around how other events are recorded
+/// combined with the fact you can participate twice per go, once as header and again as heeler.
+/// From what I can tell, HD1E is "X" if the person entered as Header, HL1E is the Heeler's IGRA #,
+/// and TIM1/PTS1/DOL1/WOR1 are time/points/dollars/world values when they were heading.
+/// Similarly, HL2E is "X" if  they enter as Heeler, HD2E is the Header's IGRA #,
+/// and TIM2/PTS2/DOL2/WOR2 are time/points/dollars/world values when they were heeling.
+///
+/// NOTE: The "entered" and "partner" fields are swapped between the two entry types!
+struct TeamEvents;
+
+impl ColumnGroup for TeamEvents {
+    type Value = Vec<EventRecord>;
+
+    fn describe() -> Vec<FieldDescriptor> {
+        let mut fields = Vec::new();
+        for day in ["SA", "SU"] {
+            for n in [1, 2] {
+                // The entered column holds an "X", and the partner's holds an IGRA number.
+                let (header, heeler) = if n == 1 { (1, 4) } else { (4, 1) };
+                fields.push(column(&format!("TR_HD{n}E_{day}"), FieldType::Character, header, 0));
+                fields.push(column(&format!("TR_HL{n}E_{day}"), FieldType::Character, heeler, 0));
+                fields.push(column(&format!("TR_TIM{n}_{day}"), FieldType::Numeric, 7, 2));
+                fields.push(column(&format!("TR_PTS{n}_{day}"), FieldType::Numeric, 3, 0));
+                fields.push(column(&format!("TR_DOL{n}_{day}"), FieldType::Numeric, 7, 2));
+                fields.push(column(&format!("TR_WOR{n}_{day}"), FieldType::Numeric, 7, 2));
+            }
+        }
+        for (abbrev, n_partners) in TEAM_EVENTS {
+            for day in ["SA", "SU"] {
+                fields.push(column(&format!("{abbrev}_EVNT_{day}"), FieldType::Character, 1, 0));
+                if n_partners == 1 {
+                    fields.push(column(&format!("{abbrev}_PART_{day}"), FieldType::Character, 4, 0));
+                } else {
+                    for p in 1..=n_partners {
+                        fields.push(column(&format!("{abbrev}_PAR{p}_{day}"), FieldType::Character, 4, 0));
+                    }
+                }
+                fields.push(column(&format!("{abbrev}_TIME_{day}"), FieldType::Numeric, 7, 2));
+                fields.push(column(&format!("{abbrev}_POIN_{day}"), FieldType::Numeric, 3, 0));
+                fields.push(column(&format!("{abbrev}_DOLL_{day}"), FieldType::Numeric, 7, 2));
+                fields.push(column(&format!("{abbrev}_WORL_{day}"), FieldType::Numeric, 7, 2));
+            }
+        }
+        fields
+    }
+
+    fn to_fields(events: &Vec<EventRecord>, data: &mut Vec<Field>) {
         // The "2nd" instances of Team Roping swap the order of entered and partner.
-        self.add_fields_for("TR_HD1E_SA", true, 1, &mut data);
-        self.add_fields_for("TR_HD2E_SA", false, 1, &mut data);
-        self.add_fields_for("TR_HD1E_SU", true, 1, &mut data);
-        self.add_fields_for("TR_HD2E_SU", false, 1, &mut data);
-
-        for (event, n_partners) in [
-            ("ST_EVNT_SA", 1), ("ST_EVNT_SU", 1),
-            ("DR_EVNT_SA", 2), ("DR_EVNT_SU", 2),
-            ("GO_EVNT_SA", 1), ("GO_EVNT_SU", 1),
-            ("RR_EVNT_SA", 1), ("RR_EVNT_SU", 1),
-        ] {
-            self.add_fields_for(event, true, n_partners, &mut data);
-        }
-
-        data.push(Field::Character(self.flag_1.clone()));
-        data.push(Field::Character(self.flag_2.clone()));
-        data.push(Field::Numeric(Some(self.stalls)));
-        data.push(Field::Character(self.extra_flag.clone()));
-
-        data.push(Field::Numeric(Some(self.sat_points)));
-        data.push(Field::Numeric(Some(self.sun_points)));
-        data.push(Field::Numeric(Some(self.ext_points)));
-        data.push(Field::Numeric(Some(self.tot_points)));
-
-        data.push(Field::Date(self.prepaid_date));
-        data.push(Field::Numeric(self.prepaid_amount));
-
-        data.push(Field::Numeric(Some(self.sat_dollars)));
-        data.push(Field::Numeric(Some(self.sun_dollars)));
-        data.push(Field::Numeric(Some(self.ext_dollars)));
-        data.push(Field::Numeric(Some(self.tot_dollars)));
+        for day in ["SA", "SU"] {
+            EventRecord::add_fields_for(events, &format!("TR_HD1E_{day}"), true, 1, data);
+            EventRecord::add_fields_for(events, &format!("TR_HD2E_{day}"), false, 1, data);
+        }
+        for (abbrev, n_partners) in TEAM_EVENTS {
+            for day in ["SA", "SU"] {
+                EventRecord::add_fields_for(events, &format!("{abbrev}_EVNT_{day}"), true, n_partners, data);
+            }
+        }
+    }
+
+    fn from_field<'a>(events: &mut Vec<EventRecord>, field: FieldValue<'a>) -> DBaseResult<Option<FieldValue<'a>>> {
+        read_event_field(events, ["SA", "SU"], field)
+    }
+}
+
+/// The contestant's note to the director, in the `NOTES` memo of tables that have it.
+///
+/// The legacy layout doesn't have this column, so this describes none; `WithNotes` adds it.
+struct DirectorNotes;
+
+impl ColumnGroup for DirectorNotes {
+    type Value = String;
+
+    fn describe() -> Vec<FieldDescriptor> {
+        vec![]
+    }
+
+    fn to_fields(_value: &String, _data: &mut Vec<Field>) {}
+
+    fn from_field<'a>(value: &mut String, field: FieldValue<'a>) -> DBaseResult<Option<FieldValue<'a>>> {
+        match (field.name, field.value) {
+            ("NOTES", Field::MemoText(s)) => *value = s,
+            ("NOTES", Field::Memo(_)) => {} // no memo file
+            ("NOTES", v) => return Err(DBaseErrorKind::UnexpectedValue("NOTES".to_string(), v)),
+            (name, value) => return Ok(Some(FieldValue { name, value })),
+        }
+        Ok(None)
+    }
+}
+
+/// A registration record with the contestant's note to the director in an extra `NOTES` memo field.
+///
+/// The legacy app's layout doesn't have this field,
+/// so only use this for tables that keep notes, and write them with a memo file.
+pub struct WithNotes<'a>(pub &'a RegistrationRecord);
+
+impl DBaseRecord for WithNotes<'_> {
+    fn describe(&self) -> Vec<FieldDescriptor> {
+        let mut fields = self.0.describe();
+        fields.push(FieldDescriptor { name: "NOTES".to_string(), field_type: FieldType::Memo, length: 10, decimal_count: 0, work_area_id: 0, example: 1 });
+        fields
+    }
 
+    fn to_record(&self) -> Vec<Field> {
+        let mut data = self.0.to_record();
+        data.push(Field::MemoText(self.0.note_to_director.clone()));
         data
     }
 }
@@ -2048,157 +2288,135 @@ impl DBaseRecord for RegistrationRecord {
 pub fn read_registrations<R: io::Read>(
     table: TableReader<Header<R>>,
 ) -> DBaseResult<Vec<RegistrationRecord>> {
-    let mut registrations = Vec::<RegistrationRecord>::with_capacity(table.n_records());
-    let mut records = table.records();
-
-    while let Some(record) = records.next() {
-        let record = record?;
-
-        let mut entrant = RegistrationRecord::default();
-        for field in record {
-            let f = field?;
-
-            match (f.name, f.value) {
-                ("IGRA_NUM", Field::Character(s)) => entrant.igra_number = s,
-                ("RODEO_NUM", _) => {} // ignored
-                ("STATE_ASSN", Field::Character(s)) => entrant.association = s,
-                ("SSN", Field::Character(s)) => entrant.ssn = s,
-                ("DIVISION", Field::Character(s)) => entrant.division = s,
-                ("LAST_NAME", Field::Character(s)) => entrant.last_name = s,
-                ("FIRST_NAME", Field::Character(s)) => entrant.first_name = s,
-                ("CITY", Field::Character(s)) => entrant.city = s,
-                ("STATE", Field::Character(s)) => entrant.state = s,
-                ("SEX", Field::Character(s)) => entrant.sex = s,
-                // <individual events appear here in the table layout>
-                // ??
-                ("RODEO_SCOR", Field::Numeric(n)) => entrant.rodeo_score = n,
-                ("RODEO_TIME", Field::Numeric(n)) => entrant.rodeo_time = n,
-                ("RODEO_ASSO", Field::Character(s)) => entrant.rodeo_association = s,
-                // <team events appear here in the table layout>
-                // ??
-                ("FLAG1", Field::Character(s)) => entrant.flag_1 = s,
-                ("FLAG2", Field::Character(s)) => entrant.flag_2 = s,
-                // horse stalls
-                ("STALL_FLAG", Field::Numeric(Some(n))) => entrant.stalls = n,
-                ("STALL_FLAG", Field::Numeric(None)) => entrant.stalls = Decimal::from(0),
-                ("EXTRA_FLAG", Field::Character(s)) => entrant.extra_flag = s,
-                // points
-                ("SAT_POINTS", Field::Numeric(Some(n))) => entrant.sat_points = n,
-                ("SUN_POINTS", Field::Numeric(Some(n))) => entrant.sun_points = n,
-                ("EXT_POINTS", Field::Numeric(Some(n))) => entrant.ext_points = n,
-                ("TOT_POINTS", Field::Numeric(Some(n))) => entrant.tot_points = n,
-                // prepaid amount
-                ("PRE_DATE", Field::Date(d)) => entrant.prepaid_date = d,
-                ("PRE_PAID", Field::Numeric(val)) => entrant.prepaid_amount = val,
-                // winnings
-                ("SAT_DOLLAR", Field::Numeric(Some(n))) => entrant.sat_dollars = n,
-                ("SUN_DOLLAR", Field::Numeric(Some(n))) => entrant.sun_dollars = n,
-                ("EXT_DOLLAR", Field::Numeric(Some(n))) => entrant.ext_dollars = n,
-                ("TOT_DOLLAR", Field::Numeric(Some(n))) => entrant.tot_dollars = n,
-
-                // Peel apart other fields identified by pattern matching.
-                (event_field, val) => {
-                    let (abbrev, field, day) = event_field.split_once('_')
-                        .and_then(|(name, rest)| {
-                            match rest.split_once('_') {
-                                Some((field, day)) => Some((name, field, day)),
-                                _ => None,
-                            }
-                        })
-                        .expect(&*format!("Unknown field: '{event_field}' with value '{val:?}'"));
-
-
-                    // Extract the event name, if its a recognized form.
-                    let event = match day {
-                        "SAT" | "SUN" => {
-                            entrant.events.iter_mut()
-                                .find(|e| &e.name[..4] == abbrev && &e.name[7..] == day)
-                        }
-                        "SA" | "SU" => {
-                            match field {
-                                // Team Roping doesn't fit the pattern of the rest of the events.
-                                // Obnoxiously, 2 of the team roping events list partners before entry.
-                                // So, when we encounter HD2E, we don't have an event entry for it yet.
-                                // The next block will create the event if they listed a partner,
-                                // and we'll see that event when we reach HL2E
-                                // We assume that if they had a partner listed, they entered the event.
-                                // If they _do_ enter the event _without_ listing a partner,
-                                // we'll add the event instance when we see the "X" for entry.
-                                // Thankfully, the other fields all come after that point anyway.
-                                "HD2E" => { None }
-                                "HL2E" | "TIM2" | "PTS2" | "DOL2" | "WOR2" => {
-                                    entrant.events.iter_mut()
-                                        .find(|e| &e.name[..2] == abbrev
-                                            && &e.name[3..7] == "HD2E"
-                                            && &e.name[8..] == day
-                                        )
-                                }
-                                _ => {
-                                    entrant.events.iter_mut()
-                                        .find(|e| &e.name[..2] == abbrev && &e.name[8..] == day)
-                                }
-                            }
-                        }
-                        _ => None,
-                    };
-
-                    match (field, val, event) {
-                        ("E" | "EVNT" | "HD1E" | "HL2E", Field::Character(ref x), None) => {
-                            if x == "X" {
-                                entrant.events.push(EventRecord {
-                                    // TODO: translate the name into a KnownEvent
-                                    name: f.name.into(),
-                                    ..EventRecord::default()
-                                });
-                            }
-                        }
-                        // Create an event for HD2E if they listed a partner.
-                        ("HD2E", Field::Character(p), None) => {
-                            if !p.is_empty() {
-                                entrant.events.push(EventRecord {
-                                    name: f.name.into(),
-                                    partners: Some(vec![p]),
-                                    ..EventRecord::default()
-                                });
-                            }
-                        }
-                        ("HL2E", Field::Character(_), Some(_)) => {
-                            // See notes above about the weirdness of Team Roping.
-                        }
-                        (_, _, None) => {} // TODO: make this work better
-                        // Score or Time: distinguish whether one is recorded.
-                        ("S", Field::Numeric(Some(n)), Some(evnt)) => evnt.outcome = Some(EventMetric::Score(n)),
-                        ("T" | "TIME" | "TIM1" | "TIM2", Field::Numeric(Some(n)), Some(e)) => e.outcome = Some(EventMetric::Time(n)),
-                        // If the value is None, don't set the outcome field.
-                        ("S", Field::Numeric(None), Some(_)) => {}
-                        ("T" | "TIME" | "TIM1" | "TIM2", Field::Numeric(None), Some(_)) => {}
-                        // Extract points/dollars/world points if set
-                        ("P" | "POIN" | "PTS1" | "PTS2", Field::Numeric(Some(n)), Some(e)) => e.points = n,
-                        ("P" | "POIN" | "PTS1" | "PTS2", Field::Numeric(None), Some(_)) => {}
-                        ("D" | "DOLL" | "DOL1" | "DOL2", Field::Numeric(Some(n)), Some(e)) => e.dollars = n,
-                        ("D" | "DOLL" | "DOL1" | "DOL2", Field::Numeric(None), Some(_)) => {}
-                        ("W" | "WORL" | "WOR1" | "WOR2", Field::Numeric(Some(n)), Some(e)) => e.world = n,
-                        ("W" | "WORL" | "WOR1" | "WOR2", Field::Numeric(None), Some(_)) => {}
-                        // Grab partner IGRA numbers.
-                        ("PART" | "PAR1" | "PAR2" | "HL1E", Field::Character(p), Some(e)) => {
-                            if let Some(ref mut partners) = e.partners {
-                                partners.push(p);
-                            } else {
-                                e.partners = Some(vec![p]);
-                            }
-                        }
-                        (field, val, _) => {
-                            panic!("Unknown field: '{field}' with value '{val:?}' for event '{abbrev}' ('{event_field}')");
-                        }
-                    }
+    table.read_all()
+}
+
+/// Read registration/event records like `read_registrations`,
+/// but return unexpected columns as warnings instead of failing.
+pub fn read_registrations_lenient<R: io::Read>(
+    table: TableReader<Header<R>>,
+) -> DBaseResult<(Vec<RegistrationRecord>, BTreeSet<SchemaWarning>)> {
+    table.read_all_lenient()
+}
+
+/// Whether `field` is one of the per-event properties handled by `read_event_field`.
+fn is_event_property(field: &str) -> bool {
+    matches!(field,
+        "E" | "EVNT" | "HD1E" | "HD2E" | "HL1E" | "HL2E"
+        | "S" | "T" | "TIME" | "TIM1" | "TIM2"
+        | "P" | "POIN" | "PTS1" | "PTS2"
+        | "D" | "DOLL" | "DOL1" | "DOL2"
+        | "W" | "WORL" | "WOR1" | "WOR2"
+        | "PART" | "PAR1" | "PAR2"
+    )
+}
+
+/// Read an event column, named like `BULL_E_SAT` or `TR_HD1E_SA`, into `events`,
+/// adding the event when the column says the person entered it.
+///
+/// Returns the column back if it isn't named like an event column for one of the given days.
+fn read_event_field<'a>(
+    events: &mut Vec<EventRecord>,
+    days: [&str; 2],
+    f: FieldValue<'a>,
+) -> DBaseResult<Option<FieldValue<'a>>> {
+    let event_field = f.name;
+    let Some((abbrev, field, day)) = event_field.split_once('_')
+        .and_then(|(name, rest)| {
+            match rest.split_once('_') {
+                Some((field, day)) => Some((name, field, day)),
+                _ => None,
+            }
+        })
+        .filter(|(_, _, day)| days.contains(day)) else {
+        return Ok(Some(f));
+    };
+
+    // Extract the event name, if its a recognized form.
+    let event = match day {
+        "SAT" | "SUN" => {
+            events.iter_mut()
+                .find(|e| &e.name[..4] == abbrev && &e.name[7..] == day)
+        }
+        "SA" | "SU" => {
+            match field {
+                // Team Roping doesn't fit the pattern of the rest of the events.
+                // Obnoxiously, 2 of the team roping events list partners before entry.
+                // So, when we encounter HD2E, we don't have an event entry for it yet.
+                // The next block will create the event if they listed a partner,
+                // and we'll see that event when we reach HL2E
+                // We assume that if they had a partner listed, they entered the event.
+                // If they _do_ enter the event _without_ listing a partner,
+                // we'll add the event instance when we see the "X" for entry.
+                // Thankfully, the other fields all come after that point anyway.
+                "HD2E" => { None }
+                "HL2E" | "TIM2" | "PTS2" | "DOL2" | "WOR2" => {
+                    events.iter_mut()
+                        .find(|e| &e.name[..2] == abbrev
+                            && &e.name[3..7] == "HD2E"
+                            && &e.name[8..] == day
+                        )
+                }
+                _ => {
+                    events.iter_mut()
+                        .find(|e| &e.name[..2] == abbrev && &e.name[8..] == day)
                 }
             }
         }
+        _ => None,
+    };
 
-        registrations.push(entrant);
+    match (field, f.value, event) {
+        ("E" | "EVNT" | "HD1E" | "HL2E", Field::Character(ref x), None) => {
+            if x == "X" {
+                events.push(EventRecord {
+                    // TODO: translate the name into a KnownEvent
+                    name: event_field.into(),
+                    ..EventRecord::default()
+                });
+            }
+        }
+        // Create an event for HD2E if they listed a partner.
+        ("HD2E", Field::Character(p), None) => {
+            if !p.is_empty() {
+                events.push(EventRecord {
+                    name: event_field.into(),
+                    partners: Some(vec![p]),
+                    ..EventRecord::default()
+                });
+            }
+        }
+        ("HL2E", Field::Character(_), Some(_)) => {
+            // See notes above about the weirdness of Team Roping.
+        }
+        (_, _, None) => {} // TODO: make this work better
+        // Score or Time: distinguish whether one is recorded.
+        ("S", Field::Numeric(Some(n)), Some(evnt)) => evnt.outcome = Some(EventMetric::Score(n)),
+        ("T" | "TIME" | "TIM1" | "TIM2", Field::Numeric(Some(n)), Some(e)) => e.outcome = Some(EventMetric::Time(n)),
+        // If the value is None, don't set the outcome field.
+        ("S", Field::Numeric(None), Some(_)) => {}
+        ("T" | "TIME" | "TIM1" | "TIM2", Field::Numeric(None), Some(_)) => {}
+        // Extract points/dollars/world points if set
+        ("P" | "POIN" | "PTS1" | "PTS2", Field::Numeric(Some(n)), Some(e)) => e.points = n,
+        ("P" | "POIN" | "PTS1" | "PTS2", Field::Numeric(None), Some(_)) => {}
+        ("D" | "DOLL" | "DOL1" | "DOL2", Field::Numeric(Some(n)), Some(e)) => e.dollars = n,
+        ("D" | "DOLL" | "DOL1" | "DOL2", Field::Numeric(None), Some(_)) => {}
+        ("W" | "WORL" | "WOR1" | "WOR2", Field::Numeric(Some(n)), Some(e)) => e.world = n,
+        ("W" | "WORL" | "WOR1" | "WOR2", Field::Numeric(None), Some(_)) => {}
+        // Grab partner IGRA numbers.
+        ("PART" | "PAR1" | "PAR2" | "HL1E", Field::Character(p), Some(e)) => {
+            if let Some(ref mut partners) = e.partners {
+                partners.push(p);
+            } else {
+                e.partners = Some(vec![p]);
+            }
+        }
+        (field, val, _) if is_event_property(field) => {
+            return Err(DBaseErrorKind::UnexpectedValue(event_field.to_string(), val));
+        }
+        (_, value, _) => return Ok(Some(FieldValue { name: event_field, value })),
     }
-
-    Ok(registrations)
+    Ok(None)
 }
 
 impl Display for EventRecord {
@@ -2245,19 +2463,19 @@ impl Display for RegistrationRecord {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "{:4} {:6} {:10} {:17} {cat:7} {:18} {:2}  #={:2}  sat={:5}  sun={:5}  tot={:5}  ext={:5}  pnl={pnl:10.02}",
+            "{:4} {:6} {:10} {:17} {cat:7} {:18} {:2}  #={:2}  sat={:5}  sun={:5}  tot={:5}  ext={:5}  pnl={pnl:10.2}",
             self.igra_number,
             self.association,
             self.first_name,
             self.last_name,
             self.city,
             self.state,
-            self.events.len(),
+            self.all_events().count(),
             self.sat_points,
             self.sun_points,
             self.tot_points,
             self.ext_points,
-            pnl = self.tot_dollars.to_f64_lossy() - (self.events.len() as f64 * 30.0),
+            pnl = self.tot_dollars - Decimal::from(30 * self.all_events().count() as i64),
             cat = if self.sex == "M" { "COWBOY" } else { "COWGIRL" }
         )
     }
@@ -2545,11 +2763,11 @@ impl RodeoEvent {
                 | RodeoEvent::WildDragRace
                 | RodeoEvent::GoatDressing => {
                   if round == 1 {
-                      return Some(format!("{}_SA", self.event_record_prefix()));
+                      Some(format!("{}_SA", self.event_record_prefix()))
                   } else if round == 2 {
-                      return Some(format!("{}_SU", self.event_record_prefix()));
+                      Some(format!("{}_SU", self.event_record_prefix()))
                   } else {
-                      return None;
+                      None
                   }
             },
             RodeoEvent::CalfRopingOnFoot 
@@ -2562,11 +2780,11 @@ impl RodeoEvent {
                 | RodeoEvent::SteerRiding 
                 | RodeoEvent::BullRiding => { 
                   if round == 1 {
-                      return Some(format!("{}_SAT", self.event_record_prefix()));
+                      Some(format!("{}_SAT", self.event_record_prefix()))
                   } else if round == 2 {
-                      return Some(format!("{}_SUN", self.event_record_prefix()));
+                      Some(format!("{}_SUN", self.event_record_prefix()))
                   } else {
-                      return None;
+                      None
                   }
              },
         }
@@ -2575,10 +2793,245 @@ impl RodeoEvent {
 
 #[cfg(test)]
 mod test {
-    use super::RodeoEvent;
+    use std::io::Cursor;
+    use super::*;
+    use crate::xbase::TableWriter;
     #[test]
     fn name_from_event() {
         let name = RodeoEvent::TeamRopingHeader.construct_name(1);
         assert_eq!(name, Some("TR_HD1E_SA".into()));
     }
+
+    #[test]
+    fn split_names() {
+        assert_eq!(split_name("Freddie Mercury"), ("Freddie", "Mercury"));
+        assert_eq!(split_name("Mercury, Freddie"), ("Freddie", "Mercury"));
+        assert_eq!(split_name(" Freddie  "), ("Freddie", ""));
+        assert_eq!(split_name(", Mercury"), ("Mercury", ""));
+        assert_eq!(split_name(""), ("", ""));
+    }
+
+    /// A personnel record for someone who performs under their legal name.
+    fn person(igra_number: &str, first: &str, last: &str, birthdate: &str, ssn: &str) -> PersonRecord {
+        PersonRecord {
+            igra_number: igra_number.to_string(),
+            legal_first: first.to_string(),
+            legal_last: last.to_string(),
+            first_name: first.to_string(),
+            last_name: last.to_string(),
+            birthdate: birthdate.to_string(),
+            ssn: ssn.to_string(),
+            ..PersonRecord::default()
+        }
+    }
+
+    /// The IGRA numbers of the people in a tree whose field is exactly `name`.
+    fn named<T: PersonField>(tree: &BKTree<T, usize>, name: &str) -> Vec<String> {
+        tree.find_by(0, |x| damerau_levenshtein(name, T::field(x.person())))
+            .into_iter()
+            .map(|(_, x)| x.person().igra_number.clone())
+            .collect()
+    }
+
+    #[test]
+    fn people_are_found_by_names_that_sound_alike() {
+        let validator = EntryValidator::new(vec![
+            person("1001", "CATHERINE", "SMITH", "19800101", "000-00-1001"),
+            person("1002", "KATE", "SMALL", "19800101", "000-00-1002"),
+            person("1003", "KATHRYN", "JONES", "19800101", "000-00-1003"),
+        ], Nicknames::default());
+
+        // Kathryn is spelled too differently from Catherine to be a typo, but it sounds alike.
+        let (perfect, found) = validator.find_person(None, "Kathryn", "Smyth", "");
+        assert!(!perfect);
+        assert_eq!(found[0].igra_number, "1001");
+        let (_, found) = validator.find_person(None, "", "", "Kathryn Smyth");
+        assert_eq!(found[0].igra_number, "1001");
+    }
+
+    #[test]
+    fn new_people_update_the_trees() {
+        let names = ["ANN", "BOB", "CARA", "DAN", "EVE", "FAY", "GUS", "HAL"];
+        let people = || -> Vec<PersonRecord> {
+            names.iter().enumerate()
+                .map(|(i, name)| person(&format!("100{}", i + 1), name, "SMITH", "19800101", &format!("000-00-100{i}")))
+                .collect()
+        };
+        let validator = EntryValidator::new(people(), Nicknames::default());
+
+        // Bob changes his name, Cara leaves, and Ivy joins.
+        let mut changed = people();
+        changed[1].legal_first = "ROBERTA".to_string();
+        changed.remove(2);
+        changed.push(person("1009", "IVY", "JONES", "19900202", "000-00-1009"));
+        let updated = validator.with_people(changed, Nicknames::default());
+
+        assert_eq!(updated.people().len(), 8);
+        assert!(updated.people()[0] == validator.people()[0] && updated.people()[1] != validator.people()[1]);
+        assert!(named(&updated.by_first_name, "BOB").is_empty());
+        assert_eq!(named(&updated.by_first_name, "ROBERTA"), ["1002"]);
+        assert_eq!(named(&updated.by_perf_first, "BOB"), ["1002"]);
+        assert!(named(&updated.by_igra_num, "1003").is_empty());
+        assert_eq!(named(&updated.by_last_name, "JONES"), ["1009"]);
+        assert_eq!(named(&updated.by_last_name, "SMITH").len(), 7);
+        let sounds_like = |validator: &EntryValidator, name| -> Vec<String> {
+            let mut found: Vec<_> = validator.sounds_like_first.sounds_like(name).iter().map(|p| p.igra_number.clone()).collect();
+            found.sort();
+            found
+        };
+        assert!(sounds_like(&updated, "BOB").is_empty());
+        assert_eq!(sounds_like(&updated, "ROBERTA"), ["1002"]);
+        assert_eq!(sounds_like(&updated, "IVY"), ["1005", "1009"]);
+        let ivy = PersonKey::from_record(&updated.people()[7]);
+        assert_eq!(updated.find_registrant_candidates(None, &ivy)[0].igra_number, "1009");
+
+        // The old version is unchanged, for requests still using it.
+        assert_eq!(named(&validator.by_first_name, "BOB"), ["1002"]);
+        assert_eq!(sounds_like(&validator, "BOB"), ["1002"]);
+        assert_eq!(named(&validator.by_igra_num, "1003"), ["1003"]);
+
+        // Replacing most of the people builds the trees again.
+        let replaced: Vec<_> = names.iter().enumerate()
+            .map(|(i, name)| person(&format!("200{}", i + 1), name, "DOE", "19700101", "000-00-2000"))
+            .collect();
+        let rebuilt = updated.with_people(replaced, Nicknames::default());
+        assert!(named(&rebuilt.by_last_name, "SMITH").is_empty());
+        assert_eq!(named(&rebuilt.by_last_name, "DOE").len(), 8);
+    }
+
+    #[test]
+    fn person_keys_weigh_each_kind_of_evidence() {
+        let key = |first, last, birthdate, ssn| PersonKey::new(first, last, first, last, birthdate, ssn);
+        let ann = key("Ann", "Smith", "19800101", "000-00-1001");
+        assert_eq!(ann, PersonKey::from_record(&person("1001", "ANN", "SMITH", "19800101", "000-00-1001")));
+        assert_eq!(ann.distance(&ann), 0);
+        // However the SSN is written, only its last 4 digits are kept.
+        let ssn = |ssn| key("Ann", "Smith", "19800101", ssn).ssn;
+        for written in ["123-45-6789", "123456789", "123 45 6789", " 6789 "] {
+            assert_eq!(ssn(written), "6789");
+        }
+        assert_eq!(ssn("12"), "12");
+
+        // Each edit counts by the weight of the part it's in.
+        let perf = PersonKey::new("ANN", "SMITH", "ANNIE", "SMITH", "19800101", "1001");
+        assert_eq!(ann.distance(&perf), 2 * PERFORMANCE_NAME_WEIGHT);
+        let legal = PersonKey::new("ANNE", "SMITH", "ANN", "SMITH", "19800101", "1001");
+        assert_eq!(ann.distance(&legal), LEGAL_NAME_WEIGHT);
+        assert_eq!(ann.distance(&key("ANN", "SMITH", "19800110", "1001")), BIRTHDATE_WEIGHT);
+        assert_eq!(ann.distance(&key("ANN", "SMITH", "19800101", "1010")), SSN_WEIGHT);
+        let wrong_numbers = key("ANN", "SMITH", "20011231", "9876");
+        assert_eq!(ann.distance(&wrong_numbers), (BIRTHDATE_WEIGHT + SSN_WEIGHT) * MAX_NUMBER_EDITS);
+        assert_eq!(wrong_numbers.distance(&legal), legal.distance(&wrong_numbers));
+
+        // A few typos per name, plus entirely wrong numbers; short names still allow three.
+        assert_eq!(ann.max_distance(), 3 * 4 + 4 * 4);
+        assert_eq!(key("AL", "LI", "", "").max_distance(), 3 * 3 + 4 * 4);
+    }
+
+    #[test]
+    fn registrants_are_matched_by_all_their_evidence() {
+        let validator = EntryValidator::new(vec![
+            person("1001", "ANN", "SMITH", "19800101", "000-00-1001"),
+            person("1002", "BOB", "JONES", "19750505", "000-00-2002"),
+            person("1003", "CARA", "LEE", "19901212", "000-00-3003"),
+            person("1004", "DANIEL", "OLSEN", "19850315", "000-00-4004"),
+        ], Nicknames::default());
+        let candidates = |igra_num: Option<&str>, first, last, birthdate, ssn| -> Vec<String> {
+            validator.find_registrant_candidates(igra_num, &PersonKey::new(first, last, first, last, birthdate, ssn))
+                .into_iter()
+                .map(|p| p.igra_number.clone())
+                .collect()
+        };
+
+        // Ann took a new last name, but gave the right birthdate and SSN,
+        // so she's closer than Bob, who only shares the new name.
+        assert_eq!(candidates(None, "Ann", "Jones", "19800101", "000-00-1001"), ["1001", "1002"]);
+        // A typo in every part of her registration.
+        assert_eq!(candidates(None, "Anne", "Smyth", "19800110", "000-00-1010"), ["1001"]);
+        // Someone new, even if they give a member's IGRA number.
+        assert!(candidates(None, "Zed", "Quinn", "20011231", "000-00-9876").is_empty());
+        assert!(candidates(Some("1003"), "Zed", "Quinn", "20011231", "000-00-9876").is_empty());
+        // Daniel gives a short name and no birthdate or SSN, which counts against him, but not too far.
+        assert_eq!(candidates(Some("1004"), "Dan", "Olsen", "", ""), ["1004"]);
+    }
+
+    #[test]
+    fn registrants_are_matched_by_their_nicknames() {
+        let nicknames = Nicknames::read("WILLIAM, BILL, WILL\n".as_bytes()).unwrap();
+        let validator = EntryValidator::new(vec![
+            person("1001", "WILLIAM", "SMITH", "19800101", "XXX-XX-1001"),
+            person("1002", "WILMA", "SMITH", "19800101", "XXX-XX-1002"),
+        ], nicknames);
+        let registration = |first: &str, igra: &str| -> Registration {
+            serde_json::from_value(serde_json::json!({
+                "id": 1, "stalls": 0, "events": [], "payment": { "total": 0 },
+                "contestant": {
+                    "firstName": first, "lastName": "Smith", "performanceName": "",
+                    "dob": { "year": 1980, "month": 1, "day": 1 }, "age": 44, "gender": "Cowboys",
+                    "isMember": if igra.is_empty() { "no" } else { "yes" }, "ssn": "1001", "noteToDirector": "",
+                    "address": {
+                        "email": "", "addressLine1": "", "addressLine2": "", "city": "", "region": "",
+                        "country": "", "zipCode": "", "cellPhoneNo": "", "homePhoneNo": "",
+                    },
+                    "association": { "igra": igra, "memberAssn": "" },
+                },
+            })).unwrap()
+        };
+        let entries = vec![registration("Bill", "1001"), registration("Bill", ""), registration("Bob", "")];
+        let report = validator.validate_entries(&entries);
+        let found: Vec<_> = report.results.iter().map(|r| r.found).collect();
+        assert_eq!(found, [Some("1001"), Some("1001"), None]);
+
+        // Bill gave his IGRA number and everything else matches, so he's a perfect match.
+        assert!(!report.results[0].issues.iter().any(|i| matches!(i.problem, Problem::NoPerfectMatch)));
+        // Without it, he's probably the member who forgot, but Bob isn't.
+        assert!(report.results[1].issues.iter().any(|i| matches!(i.problem, Problem::MaybeAMember)));
+        assert!(report.results[2].issues.iter().any(|i| matches!(i.problem, Problem::NotAMember)));
+    }
+
+    #[test]
+    fn registrations_round_trip() {
+        // Points, dollars, and world points are only written for events with an outcome.
+        let event = |name: &str, partners: &[&str], outcome: Option<EventMetric>| EventRecord {
+            name: name.to_string(),
+            partners: if partners.is_empty() { None } else { Some(partners.iter().map(|p| p.to_string()).collect()) },
+            outcome,
+            dollars: if outcome.is_some() { Decimal::new(1250, 2) } else { Decimal::default() },
+            points: if outcome.is_some() { Decimal::from(3) } else { Decimal::default() },
+            world: if outcome.is_some() { Decimal::new(125, 2) } else { Decimal::default() },
+        };
+        let registration = RegistrationRecord {
+            igra_number: "1234".to_string(),
+            last_name: "DOE".to_string(),
+            first_name: "JO".to_string(),
+            events: vec![
+                event("BULL_E_SAT", &[], Some(EventMetric::Score(Decimal::new(715, 1)))),
+                event("POLE_E_SUN", &[], Some(EventMetric::Time(Decimal::new(2109, 2)))),
+            ],
+            rodeo_time: Some(Decimal::new(100, 2)),
+            team_events: vec![
+                event("TR_HD1E_SA", &["4552"], None),
+                event("TR_HD2E_SU", &["4723"], Some(EventMetric::Time(Decimal::new(987, 2)))),
+                event("DR_EVNT_SA", &["4552", "4723"], None),
+            ],
+            stalls: Decimal::from(2),
+            prepaid_date: NaiveDate::from_ymd_opt(2024, 5, 1),
+            prepaid_amount: Some(Decimal::new(9000, 2)),
+            sat_dollars: Decimal::new(1250, 2),
+            sun_dollars: Decimal::new(2500, 2),
+            ext_dollars: Decimal::new(0, 2),
+            tot_dollars: Decimal::new(3750, 2),
+            ..Default::default()
+        };
+
+        let fields = registration.describe();
+        assert_eq!(fields.len(), 191);
+        assert_eq!(fields[..2].iter().map(|f| f.name.as_str()).collect::<Vec<_>>(), ["IGRA_NUM", "RODEO_NUM"]);
+        assert_eq!(fields.last().unwrap().name, "TOT_DOLLAR");
+
+        let mut data = Vec::new();
+        TableWriter::new(&mut data).unwrap().write_records(&[&registration]).unwrap();
+        let read = read_registrations(TableReader::new(Cursor::new(&data)).unwrap()).unwrap();
+        assert_eq!(format!("{read:?}"), format!("{:?}", [registration]));
+    }
 }
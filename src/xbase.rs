@@ -1,19 +1,30 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
 use std::fmt::{Display, Formatter};
-use std::fs::File;
+use std::fs::{File, OpenOptions};
 use std::io;
-use std::io::BufReader;
-use std::iter::zip;
+use std::io::{BufReader, SeekFrom};
+use std::iter::{zip, Sum};
 use std::num::{ParseFloatError, ParseIntError};
+use std::ops::{Add, AddAssign, Mul, Neg, Range, Sub, SubAssign};
 use std::path::Path;
 use std::str::FromStr;
 
-use log;
-
 use binary_layout::prelude::*;
-use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
 use thiserror::Error;
+use crate::xbase::codepage::Codepage;
 use crate::xbase::DBaseErrorKind::{InvalidLastUpdated, UnknownFieldType, UnknownLogicalValue};
 
+pub mod codepage;
+pub mod memo;
+pub mod ntx;
+pub mod record;
+pub mod repair;
+pub mod stream;
+
 
 // 3 bytes representing YYMMDD, where YY is years since 1900.
 define_layout!(yymmdd, LittleEndian, {
@@ -90,32 +101,112 @@ define_layout!(clipper_index_entry, LittleEndian, {
     record_number: u32,  // in DBF
 });
 
-#[derive(Debug, Clone)]
+// dBASE memo files (.DBT) are a series of fixed-size blocks;
+// the DBF stores the block number where each memo starts.
+// Block 0 is the header.
+//
+// dBASE III uses 512 byte blocks and ends each memo with 0x1a 0x1a.
+// dBASE IV stores its block size in the header,
+// and starts each memo with 0xff 0xff 0x08 0x00 followed by its length (including that 8 byte prefix).
+define_layout!(dbase_memo_header, LittleEndian, {
+    next_block: u32, // next free block
+    reserved_1: [u8; 12],
+    version: u8, // 0x03 for dBASE III
+    reserved_2: [u8; 3],
+    block_size: u16, // only set by dBASE IV
+});
+
+define_layout!(dbase_memo_block, LittleEndian, {
+    signature: [u8; 4], // 0xff 0xff 0x08 0x00
+    length: u32,
+});
+
+// FoxPro memo files (.FPT) are similar, but they're big-endian,
+// the header takes 512 bytes regardless of block size,
+// and every memo starts with its type and length (not including that 8 byte prefix).
+define_layout!(foxpro_memo_header, BigEndian, {
+    next_block: u32, // next free block
+    reserved: [u8; 2],
+    block_size: u16,
+});
+
+define_layout!(foxpro_memo_block, BigEndian, {
+    block_type: u32, // 0 = picture, 1 = text
+    length: u32,
+});
+
+/// The type of a column.
+///
+/// When deserialized, e.g. from an import schema, types may also be given by their DBF letter.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum FieldType {
+    #[serde(alias = "C")]
     Character,
+    #[serde(alias = "D")]
     Date,
+    #[serde(alias = "F")]
     Float,
+    #[serde(alias = "L")]
     Boolean,
+    #[serde(alias = "M")]
     Memo,
+    #[serde(alias = "N")]
     Numeric,
+    // The types below are from Visual FoxPro.
+    #[serde(alias = "I")]
+    Integer,
+    #[serde(alias = "Y")]
+    Currency,
+    #[serde(alias = "T")]
+    DateTime,
+    #[serde(alias = "B")]
+    Double,
+    /// The hidden `_NullFlags` field.
+    #[serde(alias = "0")]
+    NullFlags,
+    #[serde(alias = "V")]
+    Varchar,
+}
+
+impl FieldType {
+    /// Whether this type is stored as binary data, rather than text.
+    fn is_binary(&self) -> bool {
+        matches!(self,
+            FieldType::Integer | FieldType::Currency | FieldType::DateTime | FieldType::Double | FieldType::NullFlags)
+    }
+
+    /// Whether this type is only supported by Visual FoxPro,
+    /// so tables using it must be written in its format.
+    fn is_foxpro(&self) -> bool {
+        self.is_binary() || matches!(self, FieldType::Varchar)
+    }
 }
 
+/// An exact fixed-point number: `mantissa` × 10<sup>-`exponent`</sup>.
+///
+/// `Numeric` and `Currency` fields hold these, so money can be summed and compared exactly.
+/// Values compare equal regardless of their number of decimal places, so `1.5 == 1.50`.
+/// Adding or subtracting keeps the larger number of decimal places, and multiplying adds them.
+/// Values have at most 18 decimal places, and arithmetic that overflows the mantissa panics;
+/// use `checked_add`, `checked_sub`, `checked_mul`, and `rescale` to get an error instead.
+///
+/// Values serialize as strings, such as `"-12.50"`, and deserialize from strings or numbers.
 #[derive(Debug, Default, Copy, Clone)]
 pub struct Decimal {
     mantissa: i64,
     exponent: u32,
 }
 
-impl Decimal {
-    pub fn from_parts(integral: i32, fractional: u32) -> Self {
-        let mut n = integral as i64;
-        let mut f = fractional;
-        while f > 0 {
-            n *= 10;
-            f /= 10;
-        }
+/// The most decimal places a `Decimal` can have, since 10^19 doesn't fit in an `i64`.
+const MAX_DECIMAL_EXPONENT: u32 = 18;
 
-        Self::from(n + fractional as i64)
+impl Decimal {
+    /// Create the value `mantissa` × 10<sup>-`exponent`</sup>, e.g., `Decimal::new(1250, 2)` is 12.50.
+    ///
+    /// Panics if `exponent` is more than 18.
+    pub fn new(mantissa: i64, exponent: u32) -> Self {
+        assert!(exponent <= MAX_DECIMAL_EXPONENT, "a decimal can't have {exponent} decimal places");
+        Decimal { mantissa, exponent }
     }
 
     /// Return the integral portion of the value
@@ -125,87 +216,319 @@ impl Decimal {
     }
 
     /// Return the fractional portion of the value
-    /// (i.e., the portion after the decimal point).
+    /// (i.e., the portion after the decimal point), ignoring the sign.
     pub fn fractional(&self) -> u64 {
-        if self.mantissa > 0 {
-            (self.mantissa % (10_i64.pow(self.exponent))) as u64
+        self.mantissa.unsigned_abs() % 10_u64.pow(self.exponent)
+    }
+
+    /// Return this value with the given number of decimal places,
+    /// rounding half away from zero if that drops digits, as dBASE does when storing numbers.
+    ///
+    /// Returns an error if there are more than 18 places, or the value is too large for them.
+    pub fn rescale(self, exponent: u32) -> DBaseResult<Decimal> {
+        let mantissa = if exponent > MAX_DECIMAL_EXPONENT {
+            None
+        } else if exponent >= self.exponent {
+            self.mantissa.checked_mul(10_i64.pow(exponent - self.exponent))
         } else {
-            (-self.mantissa % (10_i64.pow(self.exponent))) as u64
-        }
+            Some(self.round_to(exponent).mantissa)
+        };
+        mantissa
+            .map(|mantissa| Decimal { mantissa, exponent })
+            .ok_or_else(|| DBaseErrorKind::InvalidValue(format!("{self} can't have {exponent} decimal places")))
+    }
+
+    /// Multiply, or return an error if the product doesn't fit in a `Decimal`.
+    pub fn checked_mul(self, rhs: Decimal) -> DBaseResult<Decimal> {
+        let exponent = self.exponent + rhs.exponent;
+        self.mantissa.checked_mul(rhs.mantissa)
+            .filter(|_| exponent <= MAX_DECIMAL_EXPONENT)
+            .map(|mantissa| Decimal { mantissa, exponent })
+            .ok_or_else(|| DBaseErrorKind::InvalidValue(format!("{self} × {rhs} is too large for a decimal")))
+    }
+
+    /// Round to fewer decimal places, which can't overflow.
+    fn round_to(self, exponent: u32) -> Decimal {
+        let divisor = 10_i64.pow(self.exponent - exponent);
+        let (quotient, remainder) = (self.mantissa / divisor, self.mantissa % divisor);
+        let mantissa = if remainder.unsigned_abs() * 2 >= divisor as u64 {
+            quotient + self.mantissa.signum()
+        } else {
+            quotient
+        };
+        Decimal { mantissa, exponent }
+    }
+
+    /// Add, or return an error if the sum doesn't fit in a `Decimal`.
+    pub fn checked_add(self, rhs: Decimal) -> DBaseResult<Decimal> {
+        self.aligned(rhs)
+            .and_then(|(a, b, exponent)| Some(Decimal { mantissa: a.checked_add(b)?, exponent }))
+            .ok_or_else(|| DBaseErrorKind::InvalidValue(format!("{self} + {rhs} is too large for a decimal")))
     }
 
-    /// Return the value as an float, possibly loosing precision.
-    pub fn to_f64_lossy(&self) -> f64 {
-        return self.mantissa as f64 / (10_i64.pow(self.exponent) as f64);
+    /// Subtract, or return an error if the difference doesn't fit in a `Decimal`.
+    pub fn checked_sub(self, rhs: Decimal) -> DBaseResult<Decimal> {
+        self.aligned(rhs)
+            .and_then(|(a, b, exponent)| Some(Decimal { mantissa: a.checked_sub(b)?, exponent }))
+            .ok_or_else(|| DBaseErrorKind::InvalidValue(format!("{self} - {rhs} is too large for a decimal")))
+    }
+
+    /// The mantissas of both values, scaled to the larger number of decimal places,
+    /// or `None` if either doesn't fit.
+    fn aligned(self, other: Decimal) -> Option<(i64, i64, u32)> {
+        let exponent = self.exponent.max(other.exponent);
+        let scaled = |d: Decimal| d.rescale(exponent).ok().map(|d| d.mantissa);
+        Some((scaled(self)?, scaled(other)?, exponent))
     }
 }
 
 impl From<i64> for Decimal {
     fn from(value: i64) -> Self {
-        let mut exponent = 0;
-        let mut av = value.abs();
-        while av > 0 && av % 10 == 0 {
-            exponent += 1;
-            av /= 10;
-        }
+        Decimal { mantissa: value, exponent: 0 }
+    }
+}
 
-        Decimal{
-            mantissa: if value > 0 { av } else { -av },
-            exponent,
-        }
+impl FromStr for Decimal {
+    type Err = DBaseErrorKind;
+
+    /// Parse a number like `-12.50`, keeping its number of decimal places.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_decimal(s.trim())
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Scale in 128 bits, so comparisons can't overflow.
+        let exponent = self.exponent.max(other.exponent);
+        let scaled = |d: &Decimal| d.mantissa as i128 * 10_i128.pow(exponent - d.exponent);
+        scaled(self).cmp(&scaled(other))
+    }
+}
+
+impl Add for Decimal {
+    type Output = Decimal;
+
+    fn add(self, rhs: Decimal) -> Decimal {
+        self.checked_add(rhs).expect("decimal overflow")
+    }
+}
+
+impl Sub for Decimal {
+    type Output = Decimal;
+
+    fn sub(self, rhs: Decimal) -> Decimal {
+        self.checked_sub(rhs).expect("decimal overflow")
+    }
+}
+
+impl Mul for Decimal {
+    type Output = Decimal;
+
+    fn mul(self, rhs: Decimal) -> Decimal {
+        self.checked_mul(rhs).expect("decimal overflow")
+    }
+}
+
+impl Neg for Decimal {
+    type Output = Decimal;
+
+    fn neg(self) -> Decimal {
+        Decimal { mantissa: -self.mantissa, exponent: self.exponent }
+    }
+}
+
+impl AddAssign for Decimal {
+    fn add_assign(&mut self, rhs: Decimal) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Decimal {
+    fn sub_assign(&mut self, rhs: Decimal) {
+        *self = *self - rhs;
+    }
+}
+
+impl Sum for Decimal {
+    fn sum<I: Iterator<Item=Decimal>>(iter: I) -> Decimal {
+        iter.fold(Decimal::default(), Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Decimal> for Decimal {
+    fn sum<I: Iterator<Item=&'a Decimal>>(iter: I) -> Decimal {
+        iter.copied().sum()
     }
 }
 
+/// Writes the value with its decimal places, or rounded to the given precision, e.g. `{:8.2}`.
+/// Like other numbers, it's right-aligned within the given width.
 impl Display for Decimal {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        if let Some(str_width) = f.width() {
-            if self.exponent == 0 {
-                write!(f, "{:>str_width$}", self.integral())
-            } else {
-                let s = format!("{}.{:0width$}", self.integral(), self.fractional(), width = self.exponent as usize);
-                write!(f, "{s:>str_width$}")
-            }
+        // Pad with zeros rather than rescaling up, so any precision works.
+        let (d, padding) = match f.precision() {
+            Some(p) if (p as u32) < self.exponent => (self.round_to(p as u32), 0),
+            Some(p) => (*self, p - self.exponent as usize),
+            None => (*self, 0),
+        };
+        let sign = if d.mantissa < 0 { "-" } else { "" };
+        let s = if d.exponent == 0 && padding == 0 {
+            format!("{sign}{}", d.integral().unsigned_abs())
+        } else if d.exponent == 0 {
+            format!("{sign}{}.{:0<padding$}", d.integral().unsigned_abs(), "")
         } else {
-            if self.exponent == 0 {
-                write!(f, "{}", self.integral())
-            } else {
-                write!(f, "{}.{:0width$}", self.integral(), self.fractional(), width = self.exponent as usize)
+            format!("{sign}{}.{:0width$}{:0<padding$}", d.integral().unsigned_abs(), d.fractional(), "",
+                width = d.exponent as usize)
+        };
+
+        match f.width() {
+            Some(str_width) => write!(f, "{s:>str_width$}"),
+            None => f.write_str(&s),
+        }
+    }
+}
+
+impl Serialize for Decimal {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Decimal {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DecimalVisitor;
+
+        impl serde::de::Visitor<'_> for DecimalVisitor {
+            type Value = Decimal;
+
+            fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+                f.write_str("a decimal number or a string holding one")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Decimal, E> {
+                v.parse().map_err(E::custom)
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<Decimal, E> {
+                Ok(Decimal::from(v))
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Decimal, E> {
+                i64::try_from(v).map(Decimal::from).map_err(E::custom)
+            }
+
+            /// Floats are parsed from their shortest representation, so 0.1 is exactly 0.1.
+            fn visit_f64<E: serde::de::Error>(self, v: f64) -> Result<Decimal, E> {
+                v.to_string().parse().map_err(E::custom)
             }
         }
+
+        deserializer.deserialize_any(DecimalVisitor)
     }
 }
 
 /// A DBase Field.
 ///
-/// These are the core dBASE data types, supported by most xbase programs.
+/// These are the core dBASE data types, supported by most xbase programs,
+/// along with the extra types Visual FoxPro added.
 /// Some xbase programs supported more data types,
 /// or allowed variations on these data types based on the field descriptor.
 #[allow(unused)]
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Field {
-    /// ASCII text, fewer than 254 characters, typically null-terminated, though may be padded with spaces.
+    /// Text, fewer than 254 characters, typically null-terminated, though may be padded with spaces.
     /// //
     /// Some programs extended this length by reusing the decimal count field as a high byte,
     /// but this library only concerns itself with the `length` alone.
     ///
     /// When reading `Character` fields, this library stops at the first null byte, trims spaces,
-    /// and validates that the content consists only of ASCII characters.
-    /// When writing `Character` fields, the content is padded with spaces.
+    /// and decodes the content from the table's codepage.
+    /// When writing `Character` fields, the content is encoded and padded with spaces.
     Character(String),
     Date(Option<NaiveDate>),
     Float(f64),
     Boolean(Option<bool>),
+    /// The block number of a memo in the table's memo file.
     Memo(Option<u64>),
     Numeric(Option<Decimal>),
+    /// The text of a memo.
+    ///
+    /// `Memo` fields are read as `MemoText` when the table's memo file is available,
+    /// and tables written with a memo file store `MemoText` fields in it.
+    MemoText(String),
+    /// A 4 byte, little-endian signed integer.
+    Integer(i32),
+    /// An 8 byte, little-endian signed integer, with 4 implied decimal places.
+    Currency(Decimal),
+    /// A Julian day number and milliseconds since midnight, both 4 byte little-endian integers.
+    /// Both are zero for an empty value.
+    DateTime(Option<NaiveDateTime>),
+    /// An 8 byte, little-endian float.
+    Double(f64),
+    /// The bits of the `_NullFlags` field, which track which fields are null
+    /// and which `Varchar` fields are shorter than their length.
+    ///
+    /// When writing, this is computed from the other fields, so its value is ignored.
+    NullFlags(Vec<u8>),
+    /// Text stored like `Character`, except that it may be shorter than its field,
+    /// in which case the last byte of the field holds its length.
+    Varchar(String),
+    /// The value of a nullable field that's null.
+    Null,
+}
+
+/// Shows a field's value in the format `FieldDescriptor::parse_str` accepts.
+///
+/// Empty and null values are shown as an empty string,
+/// and `Memo` fields show their block number.
+impl Display for Field {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Field::Character(s) | Field::MemoText(s) | Field::Varchar(s) => f.write_str(s),
+            Field::Date(Some(d)) => write!(f, "{}", d.format("%Y%m%d")),
+            Field::Float(n) | Field::Double(n) => write!(f, "{n}"),
+            Field::Boolean(Some(b)) => f.write_str(if *b { "T" } else { "F" }),
+            Field::Boolean(None) => f.write_str("?"),
+            Field::Memo(Some(block)) => write!(f, "{block}"),
+            Field::Numeric(Some(n)) | Field::Currency(n) => write!(f, "{n}"),
+            Field::Integer(i) => write!(f, "{i}"),
+            Field::DateTime(Some(dt)) => write!(f, "{}", dt.format("%Y-%m-%d %H:%M:%S")),
+            Field::NullFlags(bits) => bits.iter().try_for_each(|b| write!(f, "{b:02x}")),
+            Field::Date(None) | Field::Memo(None) | Field::Numeric(None) | Field::DateTime(None) | Field::Null => Ok(()),
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+/// Describes a column.
+///
+/// When deserialized, `field_type` may also be given as `type`,
+/// and everything after `length` defaults to 0.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FieldDescriptor {
     pub name: String,
+    #[serde(alias = "type")]
     pub field_type: FieldType,
     pub length: usize,
+    #[serde(default)]
     pub decimal_count: u8,
+    #[serde(default)]
     pub work_area_id: u16,
+    #[serde(default)]
     pub example: u8,
 }
 
@@ -221,12 +544,50 @@ pub enum DBaseErrorKind {
     UnknownLogicalValue(String),
     #[error("unknown field type: {:x}", .0)]
     UnknownFieldType(u8),
+    #[error("unsupported field type: {}", .0)]
+    UnsupportedFieldType(String),
     #[error("invalid last updated date: {:04}-{:02}-{:02}", .0, .1, .2)]
     InvalidLastUpdated(u16, u8, u8),
     #[error("a DBase table must have at least 1 record")]
     NoRecords,
     #[error("data exceeds field width: '{}'", .0)]
     DataExceedsLength(String),
+    #[error("invalid index header: {}", .0)]
+    InvalidIndexHeader(String),
+    #[error("invalid index page at {:#x}: {}", .0, .1)]
+    InvalidIndexPage(u32, String),
+    #[error("unsupported index key expression: '{}'", .0)]
+    UnsupportedKeyExpression(String),
+    #[error("index key refers to unknown field: '{}'", .0)]
+    UnknownKeyField(String),
+    #[error("record {} is out of range; the table has {} records", .0, .1)]
+    RecordOutOfRange(usize, usize),
+    #[error("the table has no field named '{}'", .0)]
+    UnknownField(String),
+    #[error("the table has an unexpected column: '{}'", .0)]
+    UnexpectedColumn(String),
+    #[error("expected {} fields, but the record has {}", .0, .1)]
+    FieldCountMismatch(usize, usize),
+    #[error("invalid memo at block {}: {}", .0, .1)]
+    InvalidMemo(u64, String),
+    #[error("memo text for field '{}' requires a memo file", .0)]
+    NoMemoFile(String),
+    #[error("'{}' can't be written in codepage {:?}", .0, .1)]
+    UnencodableText(String, Codepage),
+    #[error("field '{}' can't have length {} with its type", .0, .1)]
+    InvalidFieldLength(String, usize),
+    #[error("field '{}' isn't nullable", .0)]
+    NotNullable(String),
+    #[error("the table needs a _NullFlags field with at least {} bits", .0)]
+    MissingNullFlags(usize),
+    #[error("unexpected value for field '{}': {:?}", .0, .1)]
+    UnexpectedValue(String, Field),
+    #[error("invalid schema: {}", .0)]
+    InvalidSchema(String),
+    #[error("invalid value: {}", .0)]
+    InvalidValue(String),
+    #[error("damaged table: {}", .0)]
+    DamagedTable(String),
 
     #[error(transparent)]
     FloatConversionError(#[from] ParseFloatError),
@@ -238,12 +599,41 @@ pub enum DBaseErrorKind {
 
 pub type DBaseResult<T> = Result<T, DBaseErrorKind>;
 
+/// A mismatch between a table and the type reading it, which lenient reads tolerate.
+#[derive(Error, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SchemaWarning {
+    #[error("the table has an unexpected column: '{}'", .0)]
+    UnexpectedColumn(String),
+}
+
+/// Strict reads fail with the same problems lenient reads warn about.
+impl From<SchemaWarning> for DBaseErrorKind {
+    fn from(warning: SchemaWarning) -> Self {
+        match warning {
+            SchemaWarning::UnexpectedColumn(name) => DBaseErrorKind::UnexpectedColumn(name),
+        }
+    }
+}
+
 /// Converts a slice of bytes into a &str,
 /// stopping at the first NULL byte (if present),
 /// trimming trailing ASCII whitespace.
 ///
 /// This returns an error if the contents are not valid ASCII.
 fn data_to_string(data: &[u8]) -> DBaseResult<&str>{
+    let s = trim_data(data);
+
+    if !s.is_ascii() {
+        return Err(DBaseErrorKind::NonASCIIData(String::from_utf8_lossy(data).to_string()));
+    }
+
+    // SAFETY: s is ASCII, and therefore valid UTF-8.
+    let s = unsafe { std::str::from_utf8_unchecked(s) };
+    Ok(s)
+}
+
+/// Stop at the first NULL byte (if present), and trim trailing ASCII whitespace.
+fn trim_data(data: &[u8]) -> &[u8] {
     let mut s = memchr::memchr(b'\0', data).map_or(data, |null| { &data[..null] });
 
     {
@@ -258,16 +648,113 @@ fn data_to_string(data: &[u8]) -> DBaseResult<&str>{
         }
     }
 
-    if !s.is_ascii() {
-        return Err(DBaseErrorKind::NonASCIIData(String::from_utf8_lossy(data).to_string()));
+    s
+}
+
+/// Visual FoxPro keeps its field flags in the low byte of `work_area_id`.
+const FIELD_FLAG_SYSTEM: u16 = 0x01;
+const FIELD_FLAG_NULLABLE: u16 = 0x02;
+const FIELD_FLAG_BINARY: u16 = 0x04;
+
+/// Size of the database container path Visual FoxPro writes after the header's field descriptors.
+const FOXPRO_BACKLINK_SIZE: usize = 263;
+
+/// Julian day number of 0000-12-31, the day before chrono's day 1 of the common era.
+const JULIAN_DAY_OFFSET: i32 = 1_721_425;
+
+/// How to read and write the year of a table header's last updated date, which is a single byte.
+///
+/// dBASE stores the number of years since 1900, so 2024 is 124,
+/// but tools that weren't updated for Y2K, like the legacy app, store the last two digits, so 2024 is 24.
+/// Both can be read: values of 100 or more are years since 1900,
+/// and smaller values are two-digit years in the 2000s if they're less than `pivot`,
+/// and in the 1900s otherwise.
+///
+/// Readers keep the convention a table's header already uses when it's updated,
+/// but a year from 1980 to 1999 is stored the same way by both,
+/// so a table the legacy app last wrote in those years is switched to years since 1900 when it's edited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeaderYear {
+    /// The first two-digit year that's read as being in the 1900s.
+    pub pivot: u8,
+    /// Write two-digit years, rather than years since 1900.
+    pub two_digit: bool,
+}
+
+impl Default for HeaderYear {
+    /// Reads 00 to 79 as 2000 to 2079, and writes years since 1900, like dBASE.
+    fn default() -> Self {
+        HeaderYear { pivot: 80, two_digit: false }
     }
+}
 
-    // SAFETY: s is ASCII, and therefore valid UTF-8.
-    let s = unsafe { std::str::from_utf8_unchecked(s) };
-    Ok(s.trim_end_matches(' '))
+impl HeaderYear {
+    /// Get the full year for a header's year byte.
+    pub fn year(&self, yy: u8) -> i32 {
+        match yy {
+            100.. => 1900 + yy as i32,
+            _ if yy < self.pivot => 2000 + yy as i32,
+            _ => 1900 + yy as i32,
+        }
+    }
+
+    /// Get the header's year byte for a full year.
+    pub fn byte(&self, year: i32) -> u8 {
+        if self.two_digit {
+            year.rem_euclid(100) as u8
+        } else {
+            (year - 1900).clamp(0, u8::MAX as i32) as u8
+        }
+    }
+
+    /// Get the date for a header's year, month, and day bytes.
+    fn date(&self, [yy, mm, dd]: [u8; 3]) -> DBaseResult<NaiveDate> {
+        let year = self.year(yy);
+        NaiveDate::from_ymd_opt(year, mm as u32, dd as u32)
+            .ok_or(InvalidLastUpdated(year as u16, mm, dd))
+    }
 }
 
 impl FieldDescriptor {
+    /// Describe Visual FoxPro's hidden `_NullFlags` field, with room for `n_bits` flags.
+    ///
+    /// Tables with nullable or `Varchar` fields need one, after all the other fields.
+    #[allow(dead_code)]
+    pub fn null_flags(n_bits: usize) -> FieldDescriptor {
+        FieldDescriptor {
+            name: "_NullFlags".to_string(),
+            field_type: FieldType::NullFlags,
+            length: n_bits.div_ceil(8).max(1),
+            decimal_count: 0,
+            work_area_id: FIELD_FLAG_SYSTEM | FIELD_FLAG_BINARY,
+            example: 0,
+        }
+    }
+
+    /// Mark this field as nullable, so it can hold `Field::Null`.
+    #[allow(dead_code)]
+    pub fn nullable(mut self) -> Self {
+        self.work_area_id |= FIELD_FLAG_NULLABLE;
+        self
+    }
+
+    /// Whether this field can hold `Field::Null`.
+    pub fn is_nullable(&self) -> bool {
+        self.work_area_id & FIELD_FLAG_NULLABLE != 0
+    }
+
+    /// Check that a `Decimal` can hold this field's values,
+    /// which rules out `Numeric` fields with more than 18 decimal places.
+    fn check_decimal_count(&self) -> DBaseResult<()> {
+        if self.field_type == FieldType::Numeric && self.decimal_count as u32 > MAX_DECIMAL_EXPONENT {
+            return Err(DBaseErrorKind::InvalidSchema(format!(
+                "field '{}' has {} decimal places, but can have at most {MAX_DECIMAL_EXPONENT}",
+                self.name, self.decimal_count,
+            )));
+        }
+        Ok(())
+    }
+
     /// Extract a FieldDescriptor from a byte array.
     fn from_bytes(data: &[u8]) -> DBaseResult<FieldDescriptor> {
         let view = field_descriptor::View::new(data);
@@ -281,6 +768,14 @@ impl FieldDescriptor {
             b'L' => Ok(FieldType::Boolean),
             b'M' => Ok(FieldType::Memo),
             b'N' => Ok(FieldType::Numeric),
+            b'I' => Ok(FieldType::Integer),
+            b'Y' => Ok(FieldType::Currency),
+            b'T' => Ok(FieldType::DateTime),
+            b'B' => Ok(FieldType::Double),
+            b'0' => Ok(FieldType::NullFlags),
+            b'V' => Ok(FieldType::Varchar),
+            b'G' => Err(DBaseErrorKind::UnsupportedFieldType("General (OLE object)".to_string())),
+            b'P' => Err(DBaseErrorKind::UnsupportedFieldType("Picture".to_string())),
             uft => Err(UnknownFieldType(uft)),
         }?;
 
@@ -301,7 +796,7 @@ impl FieldDescriptor {
     fn to_bytes(&self, buf: &mut [u8]) -> DBaseResult<()> {
         let mut view = field_descriptor::View::new(buf);
         let name_len = self.name.len().min(11);
-        view.name_mut()[..name_len].copy_from_slice(&self.name[..name_len].as_bytes());
+        view.name_mut()[..name_len].copy_from_slice(&self.name.as_bytes()[..name_len]);
         view.f_type_mut().write(match self.field_type {
             FieldType::Character => { b'C' }
             FieldType::Date => { b'D' }
@@ -309,6 +804,12 @@ impl FieldDescriptor {
             FieldType::Boolean => { b'L' }
             FieldType::Memo => { b'M' }
             FieldType::Numeric => { b'N' }
+            FieldType::Integer => { b'I' }
+            FieldType::Currency => { b'Y' }
+            FieldType::DateTime => { b'T' }
+            FieldType::Double => { b'B' }
+            FieldType::NullFlags => { b'0' }
+            FieldType::Varchar => { b'V' }
         });
         view.length_mut().write(self.length as u8);
         view.decimal_count_mut().write(self.decimal_count);
@@ -320,50 +821,150 @@ impl FieldDescriptor {
     /// Write a `field` to the given writer using this description.
     ///
     /// Fields are padded with trailing whitespace to their data length when appropriate.
-    /// `Character` fields are checked to ensure they'll fit their length and are ASCII.
+    /// `Character` fields are encoded in the given codepage and checked to ensure they'll fit their length.
     /// `Boolean` fields are written as `T`, `F`, and `?` for true, false, and `None` (respectively).
-    fn write_field(&self, field: &Field, w: &mut impl io::Write) -> DBaseResult<()> {
+    fn write_field(&self, field: &Field, codepage: Codepage, w: &mut impl io::Write) -> DBaseResult<()> {
         log::trace!("Writing {} with {:?}", self.name, field);
 
         match field {
             Field::Character(s) => {
-                if s.len() > self.length {
-                    log::error!("Too long: {} > {} for field {}", s.len(), self.length, self.name);
+                let data = codepage.encode(s)?;
+                if data.len() > self.length {
+                    log::error!("Too long: {} > {} for field {}", data.len(), self.length, self.name);
                     return Err(DBaseErrorKind::DataExceedsLength(s.clone()))
                 }
-                if !s.is_ascii() {
-                    return Err(DBaseErrorKind::NonASCIIData(s.clone()))
+                w.write_all(&data)?;
+                write!(w, "{:1$}", "", self.length - data.len())?;
+            }
+            Field::Float(f) => {
+                let s = f.to_string();
+                if s.len() > self.length {
+                    return Err(DBaseErrorKind::DataExceedsLength(s));
                 }
-                write!(w, "{s:<0$.0$}", self.length)?;
+                write!(w, "{s:>0$}", self.length)?;
             }
-            Field::Float(f) => { write!(w, "{f:>0$}", self.length)?; }
-            Field::Boolean(Some(b)) => { w.write(if *b { &[b'T'] } else { &[b'F'] })?; }
-            Field::Boolean(None) => { w.write(&[b'?'])?; }
+            Field::Boolean(Some(b)) => { w.write_all(if *b { b"T" } else { b"F" })?; }
+            Field::Boolean(None) => { w.write_all(b"?")?; }
             Field::Numeric(Some(n)) => {
-                let s;
-                if self.decimal_count == 0 {
-                    s = format!("{}", n.integral());
-                } else {
-                    s = format!("{}.{:0w$}", n.integral(), n.fractional(), w=self.decimal_count as usize);
+                let s = n.rescale(self.decimal_count as u32)?.to_string();
+                if s.len() > self.length {
+                    return Err(DBaseErrorKind::DataExceedsLength(s));
                 }
                 write!(w, "{s:>0$}", self.length)?;
             }
+            Field::Memo(Some(id)) if self.has_binary_data() => {
+                let id = u32::try_from(*id).map_err(|_| DBaseErrorKind::DataExceedsLength(id.to_string()))?;
+                self.write_binary(&id.to_le_bytes(), w)?;
+            }
             Field::Memo(Some(id)) => { write!(w, "{id:>10}")?; }
+            Field::Memo(None) if self.has_binary_data() => { w.write_all(&vec![0; self.length])?; }
             Field::Date(Some(d)) => { write!(w, "{y:04}{m:02}{d:02}", y = d.year(), m = d.month(), d = d.day())?; }
             Field::Numeric(None) | Field::Memo(None) | Field::Date(None) => {
                 write!(w, "{:1$}", "", self.length)?;
             }
+            Field::MemoText(_) => { return Err(DBaseErrorKind::NoMemoFile(self.name.clone())); }
+            Field::Integer(i) => { self.write_binary(&i.to_le_bytes(), w)?; }
+            Field::Currency(c) => { self.write_binary(&c.rescale(4)?.mantissa.to_le_bytes(), w)?; }
+            Field::DateTime(Some(dt)) => {
+                let day = (dt.date().num_days_from_ce() + JULIAN_DAY_OFFSET) as u32;
+                let ms = dt.time().num_seconds_from_midnight() * 1000 + dt.time().nanosecond() / 1_000_000;
+                let mut data = [0; 8];
+                data[..4].copy_from_slice(&day.to_le_bytes());
+                data[4..].copy_from_slice(&ms.to_le_bytes());
+                self.write_binary(&data, w)?;
+            }
+            Field::Double(f) => { self.write_binary(&f.to_le_bytes(), w)?; }
+            Field::Varchar(s) => {
+                let mut data = codepage.encode(s)?;
+                if data.len() > self.length {
+                    log::error!("Too long: {} > {} for field {}", data.len(), self.length, self.name);
+                    return Err(DBaseErrorKind::DataExceedsLength(s.clone()))
+                }
+                if data.len() < self.length {
+                    // The last byte holds the length; the field's varlength flag says to use it.
+                    let len = data.len() as u8;
+                    data.resize(self.length, 0);
+                    data[self.length - 1] = len;
+                }
+                w.write_all(&data)?;
+            }
+            Field::DateTime(None) | Field::NullFlags(_) => { w.write_all(&vec![0; self.length])?; }
+            Field::Null if self.has_binary_data() => { w.write_all(&vec![0; self.length])?; }
+            Field::Null => { write!(w, "{:1$}", "", self.length)?; }
         };
 
         Ok(())
     }
 
+    /// Whether the field is stored as binary data.
+    ///
+    /// Besides the Visual FoxPro types, this includes its `Memo` fields,
+    /// which hold a 4 byte block number rather than 10 digits.
+    fn has_binary_data(&self) -> bool {
+        self.field_type.is_binary() || (self.field_type == FieldType::Memo && self.length == 4)
+    }
+
+    /// Write the little-endian bytes of a binary field, which must fill its length.
+    fn write_binary(&self, data: &[u8], w: &mut impl io::Write) -> DBaseResult<()> {
+        if data.len() != self.length {
+            return Err(DBaseErrorKind::InvalidFieldLength(self.name.clone(), self.length));
+        }
+        w.write_all(data)?;
+        Ok(())
+    }
+
+    /// Get the first `N` bytes of a binary field.
+    fn read_binary<const N: usize>(&self, data: &[u8]) -> DBaseResult<[u8; N]> {
+        data.get(..N)
+            .and_then(|d| d.try_into().ok())
+            .ok_or_else(|| DBaseErrorKind::InvalidFieldLength(self.name.clone(), self.length))
+    }
+
     /// Read a dBASE field from a byte slice.
-    pub fn read_field(&self, data: &[u8]) -> DBaseResult<Field> {
-        let val = data_to_string(&data[0..self.length])?;
+    ///
+    /// `Character` and `Varchar` fields are decoded from the given codepage,
+    /// and Visual FoxPro's binary types, including its 4 byte `Memo` fields, are little-endian;
+    /// everything else must be ASCII.
+    ///
+    /// This doesn't know about the record's `_NullFlags`,
+    /// so it never returns `Field::Null`, and `Varchar` fields are trimmed like `Character` fields.
+    pub fn read_field(&self, data: &[u8], codepage: Codepage) -> DBaseResult<Field> {
+        let data = &data[0..self.length];
+        let val = match self.field_type {
+            FieldType::Character | FieldType::Varchar => "",
+            _ if self.has_binary_data() => "",
+            _ => data_to_string(data)?,
+        };
+
         match self.field_type {
             FieldType::Character => {
-                Ok(Field::Character(val.to_string()))
+                Ok(Field::Character(codepage.decode(trim_data(data))))
+            }
+            FieldType::Varchar => {
+                Ok(Field::Varchar(codepage.decode(trim_data(data))))
+            }
+            FieldType::Integer => Ok(Field::Integer(i32::from_le_bytes(self.read_binary(data)?))),
+            FieldType::Currency => {
+                let mantissa = i64::from_le_bytes(self.read_binary(data)?);
+                Ok(Field::Currency(Decimal { mantissa, exponent: 4 }))
+            }
+            FieldType::Double => Ok(Field::Double(f64::from_le_bytes(self.read_binary(data)?))),
+            FieldType::NullFlags => Ok(Field::NullFlags(data.to_vec())),
+            FieldType::DateTime => {
+                let data: [u8; 8] = self.read_binary(data)?;
+                if data.iter().all(|b| *b == 0 || *b == b' ') {
+                    return Ok(Field::DateTime(None));
+                }
+                let day = u32::from_le_bytes(data[..4].try_into().unwrap());
+                let ms = u32::from_le_bytes(data[4..].try_into().unwrap());
+                let date = i32::try_from(day).ok()
+                    .and_then(|day| day.checked_sub(JULIAN_DAY_OFFSET))
+                    .and_then(NaiveDate::from_num_days_from_ce_opt);
+                let time = NaiveTime::from_num_seconds_from_midnight_opt(ms / 1000, (ms % 1000) * 1_000_000);
+                match (date, time) {
+                    (Some(date), Some(time)) => Ok(Field::DateTime(Some(date.and_time(time)))),
+                    _ => Err(DBaseErrorKind::InvalidDate(format!("Julian day {day}, {ms} ms"))),
+                }
             }
             FieldType::Date => {
                 if val.is_empty() {
@@ -382,40 +983,22 @@ impl FieldDescriptor {
                     return Ok(Field::Numeric(None));
                 }
 
-                fn empty_to_zero(err: ParseIntError) -> Result<i64, ParseIntError> {
-                    match err.kind() {
-                        std::num::IntErrorKind::Empty => Ok(0),
-                        _ => Err(err)
-                    }
-                }
-
-                let dec_point = val.find('.');
-                if dec_point.is_none() {
-                    let mantissa = i64::from_str(val).or_else(empty_to_zero)?;
-                    return Ok(Field::Numeric(Some(Decimal { mantissa, exponent: 0 })));
-                }
-
-                let (integral_s, fractional_s) = val.split_at(dec_point.unwrap());
-                let fractional_s = &fractional_s[1..];
-                let exponent = fractional_s.len() as u32;
-
-                log::trace!("val: {}, point: {:?} int: {}, frac: {}, exp: {}",
-                    val, dec_point, integral_s, fractional_s, exponent);
-
-                let integral = i64::from_str(&integral_s).or_else(empty_to_zero)?;
-                let fractional = i64::from_str(&fractional_s).or_else(empty_to_zero)?;
-                let mantissa = integral * (10_i64.pow(exponent)) + fractional;
-                Ok(Field::Numeric(Some(Decimal { mantissa, exponent })))
+                Ok(Field::Numeric(Some(parse_decimal(val)?)))
             }
             FieldType::Boolean => {
                 match val {
                     "y" | "Y" | "t" | "T" => { Ok(Field::Boolean(Some(true))) }
                     "n" | "N" | "f" | "F" => { Ok(Field::Boolean(Some(false))) }
-                    "?" => Ok(Field::Boolean(None)),
+                    "?" | "" => Ok(Field::Boolean(None)),
                     _ => Err(UnknownLogicalValue(val.to_string())),
                 }
             }
+            FieldType::Memo if self.has_binary_data() => {
+                let block = u32::from_le_bytes(self.read_binary(data)?);
+                Ok(Field::Memo(if block == 0 { None } else { Some(block.into()) }))
+            }
             FieldType::Memo => {
+                let val = val.trim_start_matches(' ');
                 if val.is_empty() {
                     Ok(Field::Memo(None))
                 } else {
@@ -424,105 +1007,788 @@ impl FieldDescriptor {
             }
         }
     }
+
+    /// Parse a field from a string, such as one typed on the command line.
+    ///
+    /// The string uses the same format as the table's data, but without padding.
+    /// Binary fields are parsed from their usual text representations,
+    /// with `DateTime` fields as `YYYY-MM-DD HH:MM:SS`.
+    /// `Date` fields may also be `YYYY-MM-DD`.
+    pub fn parse_str(&self, s: &str) -> DBaseResult<Field> {
+        match self.field_type {
+            FieldType::Date if s.len() == 10 => {
+                return NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                    .map(|d| Field::Date(Some(d)))
+                    .map_err(|_| DBaseErrorKind::InvalidDate(s.to_string()));
+            }
+            FieldType::Integer => return Ok(Field::Integer(i32::from_str(s)?)),
+            FieldType::Currency if s.is_empty() => return Ok(Field::Currency(Decimal::default())),
+            FieldType::Currency => return Ok(Field::Currency(parse_decimal(s)?)),
+            FieldType::Double => return Ok(Field::Double(f64::from_str(s)?)),
+            FieldType::DateTime if s.is_empty() => return Ok(Field::DateTime(None)),
+            FieldType::DateTime => {
+                return NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S")
+                    .map(|dt| Field::DateTime(Some(dt)))
+                    .map_err(|_| DBaseErrorKind::InvalidDate(s.to_string()));
+            }
+            _ => {}
+        }
+
+        if s.chars().count() > self.length {
+            return Err(DBaseErrorKind::DataExceedsLength(s.to_string()));
+        }
+        match self.field_type {
+            FieldType::Character => Ok(Field::Character(s.to_string())),
+            FieldType::Varchar => Ok(Field::Varchar(s.to_string())),
+            _ => self.read_field(format!("{s:<0$}", self.length).as_bytes(), Codepage::default()),
+        }
+    }
 }
 
-/// Describes a dBASE table.
+/// Parse a decimal number, such as `Numeric` field data.
 ///
-/// This is typically read from the header of a dBASE file
-/// or used to write one.
-#[derive(Debug)]
-#[allow(dead_code)]
-struct DBaseTable {
-    last_updated: NaiveDate,
-    flags: u8,
-    fields: Vec<FieldDescriptor>,
-    n_records: usize,
+/// Blank integral or fractional parts are zero, so `-.5` and `12.` are fine,
+/// but there must be at least one digit.
+fn parse_decimal(val: &str) -> DBaseResult<Decimal> {
+    fn empty_to_zero(err: ParseIntError) -> Result<i64, ParseIntError> {
+        match err.kind() {
+            std::num::IntErrorKind::Empty => Ok(0),
+            _ => Err(err)
+        }
+    }
+
+    let (negative, digits) = match val.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, val.strip_prefix('+').unwrap_or(val)),
+    };
+    let (integral_s, fractional_s) = digits.split_once('.').unwrap_or((digits, ""));
+    if !integral_s.bytes().chain(fractional_s.bytes()).all(|b| b.is_ascii_digit())
+        || integral_s.len() + fractional_s.len() == 0
+    {
+        return Err(DBaseErrorKind::InvalidValue(format!("not a decimal number: '{val}'")));
+    }
+    let exponent = fractional_s.len() as u32;
+    if exponent > MAX_DECIMAL_EXPONENT {
+        return Err(DBaseErrorKind::InvalidValue(format!("too many decimal places: '{val}'")));
+    }
+
+    log::trace!("val: {val}, int: {integral_s}, frac: {fractional_s}, exp: {exponent}");
+
+    let integral = i64::from_str(integral_s).or_else(empty_to_zero)?;
+    let fractional = i64::from_str(fractional_s).or_else(empty_to_zero)?;
+    let mantissa = integral.checked_mul(10_i64.pow(exponent))
+        .and_then(|n| n.checked_add(fractional))
+        .ok_or_else(|| DBaseErrorKind::InvalidValue(format!("too large for a decimal: '{val}'")))?;
+    Ok(Decimal { mantissa: if negative { -mantissa } else { mantissa }, exponent })
 }
 
+/// The bits a field has in the record's `_NullFlags` field.
+#[derive(Debug, Default, Clone, Copy)]
+struct FieldBits {
+    /// Set when the field is null.
+    null: Option<usize>,
+    /// Set when a `Varchar` field is shorter than its length.
+    varlength: Option<usize>,
+}
 
-pub struct TableWriter<S: TableWriterState> {
-    state: S,
+/// Where a Visual FoxPro table keeps each field's bits in the `_NullFlags` field.
+///
+/// Bits are assigned in field order: a field's null bit (if it's nullable),
+/// then its varlength bit (if it's a `Varchar`).
+/// Tables without either kind of field don't have any bits.
+#[derive(Debug, Default)]
+struct NullFlagLayout {
+    /// The position of `_NullFlags` within a record, after the deleted marker.
+    range: Option<Range<usize>>,
+    /// The bits of each field, in record order.
+    bits: Vec<FieldBits>,
 }
 
-impl<W> TableWriter<Header<W>>
-    where W: io::Write
-{
-    pub fn new(writer: W) -> DBaseResult<Self> {
-        Ok(TableWriter {
-            state: Header {
-                inner: writer,
-            },
-        })
-    }
+impl NullFlagLayout {
+    fn new(fields: &[FieldDescriptor]) -> DBaseResult<Self> {
+        let mut n_bits = 0;
+        let mut next_bit = |has_bit: bool| {
+            has_bit.then(|| { n_bits += 1; n_bits - 1 })
+        };
+        let bits = fields.iter()
+            .map(|f| FieldBits {
+                null: next_bit(f.is_nullable()),
+                varlength: next_bit(matches!(f.field_type, FieldType::Varchar)),
+            })
+            .collect();
+
+        let mut offset = 0;
+        let mut range = None;
+        for f in fields {
+            if matches!(f.field_type, FieldType::NullFlags) {
+                range = Some(offset..offset + f.length);
+            }
+            offset += f.length;
+        }
 
-    /// Write records.
-    ///
-    /// Each record must have the same number of fields,
-    /// and must match the order and type of the table's field descriptors.
-    pub fn write_records<I>(self, records: &[I]) -> DBaseResult<()>
-        where I: DBaseRecord
-    {
-        let n_records = records.len() as u32;
-        if n_records == 0 {
-            return Err(DBaseErrorKind::NoRecords);
+        if n_bits > range.as_ref().map_or(0, |r| r.len() * 8) {
+            return Err(DBaseErrorKind::MissingNullFlags(n_bits));
         }
-        let field_descriptors = records[0].describe();
-        
-        let record_size = 1 + field_descriptors.iter().fold(0, |s, f| s + f.length) as u16;
+        Ok(NullFlagLayout { range, bits })
+    }
+
+    /// Get the `_NullFlags` data from a record, without its deleted marker.
+    fn flags<'b>(&self, record: &'b [u8]) -> &'b [u8] {
+        self.range.clone().map_or(&[], |r| &record[r])
+    }
+
+    fn is_set(flags: &[u8], bit: Option<usize>) -> bool {
+        bit.is_some_and(|b| flags[b / 8] & (1 << (b % 8)) != 0)
+    }
+
+    fn put(flags: &mut [u8], bit: Option<usize>, value: bool) {
+        if let Some(b) = bit {
+            if value {
+                flags[b / 8] |= 1 << (b % 8);
+            } else {
+                flags[b / 8] &= !(1 << (b % 8));
+            }
+        }
+    }
+
+    /// Read the field at index `i`, using its bits in `flags`.
+    fn read_field(&self, i: usize, d: &FieldDescriptor, data: &[u8], flags: &[u8], codepage: Codepage)
+        -> DBaseResult<Field>
+    {
+        let bits = self.bits[i];
+        if NullFlagLayout::is_set(flags, bits.null) {
+            return Ok(Field::Null);
+        }
+        if NullFlagLayout::is_set(flags, bits.varlength) {
+            let data = &data[..d.length];
+            let len = data.last().map_or(0, |&n| (n as usize).min(d.length));
+            return Ok(Field::Varchar(codepage.decode(&data[..len])));
+        }
+        d.read_field(data, codepage)
+    }
+
+    /// Write the field at index `i`, setting or clearing its bits in `flags`.
+    fn write_field(&self, i: usize, d: &FieldDescriptor, f: &Field, codepage: Codepage,
+                   w: &mut Vec<u8>, flags: &mut [u8]) -> DBaseResult<()>
+    {
+        let bits = self.bits[i];
+        if matches!(f, Field::Null) && bits.null.is_none() {
+            return Err(DBaseErrorKind::NotNullable(d.name.clone()));
+        }
+        NullFlagLayout::put(flags, bits.null, matches!(f, Field::Null));
+
+        let start = w.len();
+        d.write_field(f, codepage, w)?;
+        let is_short = match f {
+            Field::Varchar(s) => codepage.encode(s)?.len() < d.length,
+            _ => false,
+        };
+        NullFlagLayout::put(flags, bits.varlength, is_short);
+        debug_assert_eq!(w.len() - start, d.length);
+        Ok(())
+    }
+
+    /// Serialize a record's fields, without the leading deleted marker,
+    /// filling in its `_NullFlags` field.
+    fn write_record(&self, descriptors: &[FieldDescriptor], fields: &[Field], codepage: Codepage,
+                    w: &mut Vec<u8>) -> DBaseResult<()>
+    {
+        let start = w.len();
+        let mut flags = vec![0; self.range.as_ref().map_or(0, |r| r.len())];
+        for (i, (d, f)) in zip(descriptors, fields).enumerate() {
+            self.write_field(i, d, f, codepage, w, &mut flags)?;
+        }
+        if let Some(r) = &self.range {
+            w[start + r.start..start + r.end].copy_from_slice(&flags);
+        }
+        Ok(())
+    }
+}
+
+/// Describes a dBASE table.
+///
+/// This is typically read from the header of a dBASE file
+/// or used to write one.
+#[derive(Debug)]
+#[allow(dead_code)]
+struct DBaseTable {
+    last_updated: NaiveDate,
+    header_year: HeaderYear,
+    flags: u8,
+    fields: Vec<FieldDescriptor>,
+    n_records: usize,
+    n_header_bytes: usize,
+    codepage: Codepage,
+    null_flags: NullFlagLayout,
+}
+
+pub struct TableWriter<S: TableWriterState> {
+    state: S,
+    indexes: Vec<IndexSpec>,
+    memo: Option<memo::MemoWriter<Box<dyn io::Write>>>,
+    codepage: Codepage,
+    transliterate: bool,
+    header_year: HeaderYear,
+}
+
+/// An index to build alongside a table.
+struct IndexSpec {
+    writer: Box<dyn io::Write>,
+    expr: ntx::KeyExpression,
+    is_unique: bool,
+}
+
+impl<W> TableWriter<Header<W>>
+    where W: io::Write
+{
+    pub fn new(writer: W) -> DBaseResult<Self> {
+        Ok(TableWriter {
+            state: Header {
+                inner: writer,
+            },
+            indexes: Vec::new(),
+            memo: None,
+            codepage: Codepage::default(),
+            transliterate: false,
+            header_year: HeaderYear::default(),
+        })
+    }
+
+    /// Also write a Clipper index on `expression` to `writer` when the records are written.
+    ///
+    /// The legacy app refuses to open (or shows the wrong rows for) a table with a stale index,
+    /// so tables meant for it should be written with the indexes it expects.
+    pub fn with_index(mut self, expression: &str, is_unique: bool, writer: impl io::Write + 'static)
+        -> DBaseResult<Self>
+    {
+        self.indexes.push(IndexSpec {
+            writer: Box::new(writer),
+            expr: ntx::KeyExpression::parse(expression)?,
+            is_unique,
+        });
+        Ok(self)
+    }
+
+    /// Write the text of `MemoText` fields to a memo file of the given format.
+    ///
+    /// Without a memo file, records with `MemoText` fields can't be written.
+    pub fn with_memo(mut self, format: memo::MemoFormat, writer: impl io::Write + 'static) -> Self {
+        self.memo = Some(memo::MemoWriter::new(Box::new(writer), format));
+        self
+    }
+
+    /// Encode text in the given codepage, rather than the default CP437.
+    pub fn with_codepage(mut self, codepage: Codepage) -> Self {
+        self.codepage = codepage;
+        self
+    }
+
+    /// Write the header's last updated year using the given rule, rather than as years since 1900.
+    pub fn with_header_year(mut self, header_year: HeaderYear) -> Self {
+        self.header_year = header_year;
+        self
+    }
+
+    /// Replace characters that the codepage can't represent with ASCII approximations,
+    /// rather than returning an error.
+    ///
+    /// See `Codepage::transliterate`.
+    pub fn with_transliteration(mut self) -> Self {
+        self.transliterate = true;
+        self
+    }
+
+    /// Write records.
+    ///
+    /// Each record must have the same number of fields,
+    /// and must match the order and type of the table's field descriptors.
+    /// The table's layout comes from the first record, so there must be at least one;
+    /// use `begin` to write a table that may be empty.
+    pub fn write_records<I>(self, records: &[I]) -> DBaseResult<()>
+        where I: DBaseRecord
+    {
+        let Some(first) = records.first() else {
+            return Err(DBaseErrorKind::NoRecords);
+        };
+        let mut writer = self.start(first.describe(), records.len(), 0)?;
+        for r in records {
+            writer.write_record(r)?;
+        }
+        writer.finish_records()?;
+        Ok(())
+    }
+
+    /// Write the header for a table with the given fields, then write its records one at a time.
+    ///
+    /// The header's record count is set when the writer is finished,
+    /// so records may come from an iterator, and there may be none.
+    /// This seeks back to the header, so the writer must be seekable.
+    pub fn begin(mut self, fields: Vec<FieldDescriptor>) -> DBaseResult<TableWriter<Writing<W>>>
+        where W: io::Seek
+    {
+        let header_pos = self.state.inner.stream_position()?;
+        self.start(fields, 0, header_pos)
+    }
+
+    /// Write the header, with the given record count, and switch to writing records.
+    fn start(self, fields: Vec<FieldDescriptor>, n_records: usize, header_pos: u64)
+        -> DBaseResult<TableWriter<Writing<W>>>
+    {
+        for f in &fields {
+            f.check_decimal_count()?;
+        }
+        let record_size = 1 + fields.iter().fold(0, |s, f| s + f.length) as u16;
         log::info!("Record size: {record_size}");
 
+        let indexes = self.indexes.into_iter()
+            .map(|i| ntx::NtxWriter::new(i.writer, i.expr, i.is_unique, &fields, self.codepage))
+            .collect::<DBaseResult<Vec<_>>>()?;
+        let null_flags = NullFlagLayout::new(&fields)?;
+
+        // Tables with Visual FoxPro's field types must be written in its format,
+        // which has a backlink to its database container after the field descriptors.
+        let is_foxpro = fields.iter().any(|f| f.field_type.is_foxpro() || f.is_nullable());
+        let has_varchar = fields.iter().any(|f| matches!(f.field_type, FieldType::Varchar));
+        let backlink_size = if is_foxpro { FOXPRO_BACKLINK_SIZE } else { 0 };
+
         let mut data: [u8; 32] = [0; 32];
         let mut view = dbase_header::View::new(&mut data);
         let mut writer = self.state.inner;
+        let memo = self.memo;
+        let codepage = self.codepage;
 
         // header
         {
             let today = chrono::Utc::now().naive_utc().date();
-            let flags = 0b0000_0011; // magic found in my tables
-            
+            let flags = match (is_foxpro, has_varchar) {
+                (true, false) => 0x30,
+                (true, true) => 0x32,
+                _ => memo.as_ref().map_or(0b0000_0011, |m| m.format().table_flags()), // magic found in my tables
+            };
+
             view.flags_mut().write(flags);
             {
                 let mut last_updated = view.last_updated_mut();
-                last_updated.year_mut().write((today.year() - 2000) as u8);
+                last_updated.year_mut().write(self.header_year.byte(today.year()));
                 last_updated.month_mut().write(today.month() as u8);
                 last_updated.day_mut().write(today.day() as u8);
             }
-            view.n_records_mut().write(n_records);
+            view.n_records_mut().write(n_records as u32);
             view.n_header_bytes_mut().write(
-                (field_descriptors.len() * 32 + 33) as u16
+                (fields.len() * 32 + 33 + backlink_size) as u16
             );
             view.n_record_bytes_mut().write(record_size);
+            if is_foxpro && memo.is_some() {
+                view.is_production_mut().write(0x02); // Visual FoxPro's "has memo" table flag
+            }
+            view.language_driver_id_mut().write(codepage.language_driver_id());
             writer.write_all(&data)?;
         }
 
         // field descriptors
         {
-            for f in &field_descriptors {
+            let mut displacement = 1;
+            for f in &fields {
                 data.fill(0);
                 f.to_bytes(&mut data)?;
+                if is_foxpro {
+                    // Visual FoxPro records each field's offset within the record.
+                    let mut view = field_descriptor::View::new(&mut data[..]);
+                    view.reserved_1_mut().copy_from_slice(&(displacement as u32).to_le_bytes());
+                }
+                displacement += f.length;
                 writer.write_all(&data)?;
             }
         }
 
         // terminator byte
         writer.write_all(&[0x0d])?;
+        writer.write_all(&vec![0; backlink_size])?;
+
+        Ok(TableWriter {
+            state: Writing {
+                inner: writer,
+                header_pos,
+                buf: Vec::with_capacity(record_size as usize),
+                fields,
+                null_flags,
+                indexes,
+                n_records: 0,
+            },
+            indexes: Vec::new(),
+            memo,
+            codepage,
+            transliterate: self.transliterate,
+            header_year: self.header_year,
+        })
+    }
+}
+
+impl<W> TableWriter<Writing<W>>
+    where W: io::Write
+{
+    /// Get the number of records written so far.
+    pub fn n_records(&self) -> usize {
+        self.state.n_records
+    }
 
-        // data
+    /// Write a record, which must match the order and type of the table's field descriptors.
+    pub fn write_record<I: DBaseRecord>(&mut self, record: &I) -> DBaseResult<()> {
+        let Writing { inner, buf, fields: descriptors, null_flags, indexes, n_records, .. } = &mut self.state;
+        let codepage = self.codepage;
+
+        let mut fields = record.to_record();
+        if self.transliterate {
+            transliterate_fields(&mut fields, codepage);
+        }
+        let stored = fields.iter()
+            .map(|f| match (f, self.memo.as_mut()) {
+                (Field::MemoText(text), Some(memo)) => Ok(Field::Memo(memo.add(text, codepage)?)),
+                _ => Ok(f.clone()),
+            })
+            .collect::<DBaseResult<Vec<_>>>()?;
+
+        buf.clear();
+        buf.push(0x20); // valid record
+        null_flags.write_record(descriptors, &stored, codepage, buf)?;
+        inner.write_all(buf)?;
+        *n_records += 1;
+        for index in indexes {
+            index.add_record(descriptors, &fields, *n_records as u32)?;
+        }
+        Ok(())
+    }
+
+    /// Write every record from an iterator.
+    pub fn write_iter<I, T>(&mut self, records: T) -> DBaseResult<()>
+        where I: DBaseRecord, T: IntoIterator<Item=I>
+    {
         for r in records {
-            writer.write(&[0x20])?; // valid record
-            for (d, f) in zip(field_descriptors.iter(), r.to_record()) {
-                d.write_field(&f, &mut writer)?;
-            }
+            self.write_record(&r)?;
         }
+        Ok(())
+    }
+
+    /// Write the end of the table, indexes, and memo file, but not the header's record count.
+    fn finish_records(self) -> DBaseResult<W> {
+        let mut writer = self.state.inner;
 
         // End of File
         writer.write_all(&[0x1a])?;
+        writer.flush()?;
+
+        for index in self.state.indexes {
+            index.finish()?;
+        }
+        if let Some(memo) = self.memo {
+            memo.finish()?;
+        }
 
+        Ok(writer)
+    }
+}
+
+impl<W> TableWriter<Writing<W>>
+    where W: io::Write + io::Seek
+{
+    /// Finish the table, setting the header's record count, and return the writer.
+    ///
+    /// The writer is left positioned at the end of the table.
+    pub fn finish(self) -> DBaseResult<W> {
+        let header_pos = self.state.header_pos;
+        let n_records = self.state.n_records as u32;
+        let mut writer = self.finish_records()?;
+
+        let end = writer.stream_position()?;
+        writer.seek(SeekFrom::Start(header_pos + 4))?;
+        writer.write_all(&n_records.to_le_bytes())?;
+        writer.seek(SeekFrom::Start(end))?;
+        writer.flush()?;
+        Ok(writer)
+    }
+}
+
+/// Replace the characters in text fields that the codepage can't represent.
+fn transliterate_fields(fields: &mut [Field], codepage: Codepage) {
+    for f in fields {
+        if let Field::Character(s) | Field::MemoText(s) | Field::Varchar(s) = f {
+            if let Cow::Owned(t) = codepage.transliterate(s) {
+                *s = t;
+            }
+        }
+    }
+}
+
+/// Edits an existing DBase table in place.
+///
+/// Records are addressed by their 1-based record number,
+/// which counts deleted records, so it matches the record numbers used by indexes.
+/// Every change also sets the header's last updated date to today.
+///
+/// Changes to the table don't update its indexes;
+/// use `ntx::build_index` to rebuild them when you're done.
+pub struct TableEditor<F: io::Read + io::Write + io::Seek> {
+    table: DBaseTable,
+    inner: F,
+    record_size: usize,
+    transliterate: bool,
+}
+
+/// Open the DBF table at the given path for editing.
+pub fn edit_path<P: AsRef<Path>>(path: P) -> DBaseResult<TableEditor<File>> {
+    let file = OpenOptions::new().read(true).write(true).open(path)?;
+    TableEditor::new(file)
+}
+
+impl<F> TableEditor<F>
+    where F: io::Read + io::Write + io::Seek
+{
+    /// Create a new TableEditor by reading the table header from `inner`.
+    pub fn new(mut inner: F) -> DBaseResult<Self> {
+        inner.seek(SeekFrom::Start(0))?;
+        let table = *TableReader::new(BufReader::new(&mut inner))?.table;
+        let record_size = 1 + table.fields.iter().fold(0, |s, f| s + f.length);
+
+        Ok(TableEditor { table, inner, record_size, transliterate: false })
+    }
+
+    /// Replace characters that the table's codepage can't represent with ASCII approximations,
+    /// rather than returning an error.
+    ///
+    /// See `Codepage::transliterate`.
+    pub fn with_transliteration(mut self) -> Self {
+        self.transliterate = true;
+        self
+    }
+
+    /// Get the number of records the DBF table holds.
+    pub fn n_records(&self) -> usize {
+        self.table.n_records
+    }
+
+    /// Get the table's field descriptors, in record order.
+    pub fn fields(&self) -> &[FieldDescriptor] {
+        &self.table.fields
+    }
+
+    /// Overwrite every field of an existing record.
+    ///
+    /// The record's fields must match the order and type of the table's field descriptors.
+    /// This doesn't change whether the record is marked deleted.
+    pub fn update<I: DBaseRecord>(&mut self, record_number: usize, record: &I) -> DBaseResult<()> {
+        let offset = self.record_offset(record_number)?;
+        let buf = self.record_bytes(record.to_record())?;
+
+        self.inner.seek(SeekFrom::Start((offset + 1) as u64))?;
+        self.inner.write_all(&buf)?;
+        self.touch()
+    }
+
+    /// Overwrite a single field of an existing record.
+    pub fn set_field(&mut self, record_number: usize, name: &str, value: &Field) -> DBaseResult<()> {
+        let offset = self.record_offset(record_number)?;
+
+        let mut field_offset = 1;
+        let mut descriptor = None;
+        for (i, f) in self.table.fields.iter().enumerate() {
+            if f.name == name {
+                descriptor = Some(i);
+                break;
+            }
+            field_offset += f.length;
+        }
+        let i = descriptor.ok_or_else(|| DBaseErrorKind::UnknownField(name.to_string()))?;
+        let descriptor = &self.table.fields[i];
+
+        let mut value = [value.clone()];
+        if self.transliterate {
+            transliterate_fields(&mut value, self.table.codepage);
+        }
+
+        // The field's null and varlength bits may need to change too.
+        let mut flags = vec![0; self.table.null_flags.range.as_ref().map_or(0, |r| r.len())];
+        if let Some(r) = &self.table.null_flags.range {
+            self.inner.seek(SeekFrom::Start((offset + 1 + r.start) as u64))?;
+            self.inner.read_exact(&mut flags)?;
+        }
+        let mut buf = Vec::with_capacity(descriptor.length);
+        self.table.null_flags.write_field(i, descriptor, &value[0], self.table.codepage, &mut buf, &mut flags)?;
+
+        self.inner.seek(SeekFrom::Start((offset + field_offset) as u64))?;
+        self.inner.write_all(&buf)?;
+        if let Some(r) = &self.table.null_flags.range {
+            self.inner.seek(SeekFrom::Start((offset + 1 + r.start) as u64))?;
+            self.inner.write_all(&flags)?;
+        }
+        self.touch()
+    }
+
+    /// Add a new record to the end of the table, returning its record number.
+    pub fn append<I: DBaseRecord>(&mut self, record: &I) -> DBaseResult<usize> {
+        let buf = self.record_bytes(record.to_record())?;
+        let offset = self.table.n_header_bytes + self.table.n_records * self.record_size;
+
+        self.inner.seek(SeekFrom::Start(offset as u64))?;
+        self.inner.write_all(&[0x20])?; // valid record
+        self.inner.write_all(&buf)?;
+        self.inner.write_all(&[0x1a])?; // End of File
+
+        self.table.n_records += 1;
+        self.touch()?;
+        Ok(self.table.n_records)
+    }
+
+    /// Mark a record as deleted.
+    ///
+    /// The record's data stays in the table until it's packed.
+    pub fn delete(&mut self, record_number: usize) -> DBaseResult<()> {
+        self.set_marker(record_number, 0x2a)
+    }
+
+    /// Clear a record's deleted marker.
+    pub fn undelete(&mut self, record_number: usize) -> DBaseResult<()> {
+        self.set_marker(record_number, 0x20)
+    }
+
+    /// Check whether a record is marked as deleted.
+    pub fn is_deleted(&mut self, record_number: usize) -> DBaseResult<bool> {
+        let offset = self.record_offset(record_number)?;
+        let mut marker: [u8; 1] = [0];
+        self.inner.seek(SeekFrom::Start(offset as u64))?;
+        self.inner.read_exact(&mut marker)?;
+        Ok(marker[0] == 0x2a)
+    }
+
+    /// Read the record with the given 1-based record number.
+    ///
+    /// Returns `None` if the record is marked deleted.
+    pub fn get(&mut self, record_number: usize) -> DBaseResult<Option<FieldIterator<'_>>> {
+        let offset = self.record_offset(record_number)?;
+        let mut buf = vec![0; self.record_size];
+        self.inner.seek(SeekFrom::Start(offset as u64))?;
+        self.inner.read_exact(&mut buf)?;
+
+        if buf[0] == 0x2a {
+            return Ok(None);
+        }
+
+        Ok(Some(FieldIterator {
+            table: &self.table,
+            memo: None,
+            record_number,
+            buf,
+            cur_field: 0,
+            cur_byte: 1,
+        }))
+    }
+
+    /// Move every record that isn't deleted toward the start of the table,
+    /// then write the new record count and End of File marker.
+    ///
+    /// Returns the number of records removed.
+    /// The source isn't truncated, so it may still hold data after the End of File marker.
+    fn compact(&mut self) -> DBaseResult<usize> {
+        let mut buf = vec![0; self.record_size];
+        let mut kept = 0;
+
+        for record_number in 1..=self.table.n_records {
+            let offset = self.record_offset(record_number)?;
+            self.inner.seek(SeekFrom::Start(offset as u64))?;
+            self.inner.read_exact(&mut buf)?;
+            if buf[0] == 0x2a {
+                continue;
+            }
+
+            kept += 1;
+            if kept != record_number {
+                let offset = self.record_offset(kept)?;
+                self.inner.seek(SeekFrom::Start(offset as u64))?;
+                self.inner.write_all(&buf)?;
+            }
+        }
+
+        let removed = self.table.n_records - kept;
+        self.table.n_records = kept;
+        self.inner.seek(SeekFrom::Start(self.data_len() as u64 - 1))?;
+        self.inner.write_all(&[0x1a])?; // End of File
+        self.touch()?;
+        Ok(removed)
+    }
+
+    /// Length of the header and records, plus the End of File marker.
+    fn data_len(&self) -> usize {
+        self.table.n_header_bytes + self.table.n_records * self.record_size + 1
+    }
+
+    fn set_marker(&mut self, record_number: usize, marker: u8) -> DBaseResult<()> {
+        let offset = self.record_offset(record_number)?;
+        self.inner.seek(SeekFrom::Start(offset as u64))?;
+        self.inner.write_all(&[marker])?;
+        self.touch()
+    }
+
+    /// Get the byte offset of a record, checking that it exists.
+    fn record_offset(&self, record_number: usize) -> DBaseResult<usize> {
+        if record_number == 0 || record_number > self.table.n_records {
+            return Err(DBaseErrorKind::RecordOutOfRange(record_number, self.table.n_records));
+        }
+        Ok(self.table.n_header_bytes + (record_number - 1) * self.record_size)
+    }
+
+    /// Serialize a record's fields, without the leading deleted marker.
+    fn record_bytes(&self, mut fields: Vec<Field>) -> DBaseResult<Vec<u8>> {
+        if fields.len() != self.table.fields.len() {
+            return Err(DBaseErrorKind::FieldCountMismatch(self.table.fields.len(), fields.len()));
+        }
+        if self.transliterate {
+            transliterate_fields(&mut fields, self.table.codepage);
+        }
+
+        let mut buf = Vec::with_capacity(self.record_size - 1);
+        self.table.null_flags.write_record(&self.table.fields, &fields, self.table.codepage, &mut buf)?;
+        Ok(buf)
+    }
+
+    /// Write the record count and today's date to the header.
+    fn touch(&mut self) -> DBaseResult<()> {
+        let mut data: [u8; 32] = [0; 32];
+        self.inner.seek(SeekFrom::Start(0))?;
+        self.inner.read_exact(&mut data)?;
+
+        let today = chrono::Utc::now().naive_utc().date();
+        let mut view = dbase_header::View::new(&mut data);
+        {
+            let mut last_updated = view.last_updated_mut();
+            last_updated.year_mut().write(self.table.header_year.byte(today.year()));
+            last_updated.month_mut().write(today.month() as u8);
+            last_updated.day_mut().write(today.day() as u8);
+        }
+        view.n_records_mut().write(self.table.n_records as u32);
+
+        self.inner.seek(SeekFrom::Start(0))?;
+        self.inner.write_all(&data)?;
+        self.table.last_updated = today;
         Ok(())
     }
 }
 
+impl TableEditor<File> {
+    /// Permanently remove deleted records, like Clipper's PACK command,
+    /// and truncate the file to fit the remaining records.
+    ///
+    /// Returns the number of records removed.
+    /// This renumbers the remaining records, so the table's indexes must be rebuilt afterwards.
+    pub fn pack(&mut self) -> DBaseResult<usize> {
+        let removed = self.compact()?;
+        self.inner.set_len(self.data_len() as u64)?;
+        Ok(removed)
+    }
+}
+
 /// Tells the `TableWriter` how to write a table from a collection of records.
+///
+/// For structs with a field per column, this can be derived along with `FromRecord`;
+/// see the `dbase-derive` crate.
 pub trait DBaseRecord {
     /// Describe the record format as a list of `FieldDescriptor`s.
     fn describe(&self) -> Vec<FieldDescriptor>;
@@ -531,6 +1797,203 @@ pub trait DBaseRecord {
     fn to_record(&self) -> Vec<Field>;
 }
 
+impl<T: DBaseRecord + ?Sized> DBaseRecord for &T {
+    fn describe(&self) -> Vec<FieldDescriptor> {
+        (**self).describe()
+    }
+
+    fn to_record(&self) -> Vec<Field> {
+        (**self).to_record()
+    }
+}
+
+pub use dbase_derive::DBaseRecord;
+
+/// Tells `TableReader::read_all` how to build a value from a table record.
+pub trait FromRecord: Sized {
+    fn from_record(fields: FieldIterator) -> DBaseResult<Self>;
+
+    /// Build a value like `from_record`, but tolerate schema mismatches,
+    /// such as columns the type doesn't know, by adding them to `warnings`.
+    ///
+    /// By default, nothing is tolerated.
+    fn from_record_lenient(fields: FieldIterator, _warnings: &mut Vec<SchemaWarning>) -> DBaseResult<Self> {
+        Self::from_record(fields)
+    }
+}
+
+/// Stores a field of a derived `DBaseRecord` in a group of columns,
+/// for layouts that don't have one column per field.
+///
+/// The field names the group with `#[dbase(columns = Group)]`.
+pub trait ColumnGroup {
+    /// The type of the struct field.
+    type Value;
+
+    /// Describe the group's columns, in table order.
+    fn describe() -> Vec<FieldDescriptor>;
+
+    /// Add the field's values for the group's columns to `data`, in the order given by `describe`.
+    fn to_fields(value: &Self::Value, data: &mut Vec<Field>);
+
+    /// Read a column into the field, or give it back if it isn't one of the group's.
+    fn from_field<'a>(value: &mut Self::Value, field: FieldValue<'a>) -> DBaseResult<Option<FieldValue<'a>>>;
+}
+
+/// Converts between a Rust type and the `Field` stored in a table,
+/// for the fields of a derived `DBaseRecord`.
+pub trait DBaseField: Sized {
+    /// The column type used when the field's attribute doesn't give one.
+    const FIELD_TYPE: FieldType;
+
+    /// Convert to a `Field` for a column of the given type.
+    fn to_field(&self, field_type: &FieldType) -> Field;
+
+    /// Convert from a `Field`, giving it back if it's the wrong kind.
+    fn from_field(field: Field) -> Result<Self, Field>;
+}
+
+/// Text columns, including memos.
+impl DBaseField for String {
+    const FIELD_TYPE: FieldType = FieldType::Character;
+
+    fn to_field(&self, field_type: &FieldType) -> Field {
+        match field_type {
+            FieldType::Memo => Field::MemoText(self.clone()),
+            FieldType::Varchar => Field::Varchar(self.clone()),
+            _ => Field::Character(self.clone()),
+        }
+    }
+
+    fn from_field(field: Field) -> Result<Self, Field> {
+        match field {
+            Field::Character(s) | Field::MemoText(s) | Field::Varchar(s) => Ok(s),
+            // Without a memo file, there's no text to read.
+            Field::Memo(_) | Field::Null => Ok(String::new()),
+            f => Err(f),
+        }
+    }
+}
+
+/// `Numeric` columns, which read as 0 when they're blank.
+impl DBaseField for Decimal {
+    const FIELD_TYPE: FieldType = FieldType::Numeric;
+
+    fn to_field(&self, field_type: &FieldType) -> Field {
+        match field_type {
+            FieldType::Currency => Field::Currency(*self),
+            _ => Field::Numeric(Some(*self)),
+        }
+    }
+
+    fn from_field(field: Field) -> Result<Self, Field> {
+        match field {
+            Field::Numeric(n) => Ok(n.unwrap_or_default()),
+            Field::Currency(n) => Ok(n),
+            f => Err(f),
+        }
+    }
+}
+
+/// `Numeric` columns that may be blank.
+impl DBaseField for Option<Decimal> {
+    const FIELD_TYPE: FieldType = FieldType::Numeric;
+
+    fn to_field(&self, _field_type: &FieldType) -> Field {
+        Field::Numeric(*self)
+    }
+
+    fn from_field(field: Field) -> Result<Self, Field> {
+        match field {
+            Field::Numeric(n) => Ok(n),
+            Field::Null => Ok(None),
+            f => Err(f),
+        }
+    }
+}
+
+impl DBaseField for Option<NaiveDate> {
+    const FIELD_TYPE: FieldType = FieldType::Date;
+
+    fn to_field(&self, _field_type: &FieldType) -> Field {
+        Field::Date(*self)
+    }
+
+    fn from_field(field: Field) -> Result<Self, Field> {
+        match field {
+            Field::Date(d) => Ok(d),
+            Field::Null => Ok(None),
+            f => Err(f),
+        }
+    }
+}
+
+impl DBaseField for Option<bool> {
+    const FIELD_TYPE: FieldType = FieldType::Boolean;
+
+    fn to_field(&self, _field_type: &FieldType) -> Field {
+        Field::Boolean(*self)
+    }
+
+    fn from_field(field: Field) -> Result<Self, Field> {
+        match field {
+            Field::Boolean(b) => Ok(b),
+            Field::Null => Ok(None),
+            f => Err(f),
+        }
+    }
+}
+
+impl DBaseField for i32 {
+    const FIELD_TYPE: FieldType = FieldType::Integer;
+
+    fn to_field(&self, _field_type: &FieldType) -> Field {
+        Field::Integer(*self)
+    }
+
+    fn from_field(field: Field) -> Result<Self, Field> {
+        match field {
+            Field::Integer(i) => Ok(i),
+            f => Err(f),
+        }
+    }
+}
+
+/// `Double` columns, or `Float` columns if the attribute says so.
+impl DBaseField for f64 {
+    const FIELD_TYPE: FieldType = FieldType::Double;
+
+    fn to_field(&self, field_type: &FieldType) -> Field {
+        match field_type {
+            FieldType::Float => Field::Float(*self),
+            _ => Field::Double(*self),
+        }
+    }
+
+    fn from_field(field: Field) -> Result<Self, Field> {
+        match field {
+            Field::Double(f) | Field::Float(f) => Ok(f),
+            f => Err(f),
+        }
+    }
+}
+
+impl DBaseField for Option<NaiveDateTime> {
+    const FIELD_TYPE: FieldType = FieldType::DateTime;
+
+    fn to_field(&self, _field_type: &FieldType) -> Field {
+        Field::DateTime(*self)
+    }
+
+    fn from_field(field: Field) -> Result<Self, Field> {
+        match field {
+            Field::DateTime(dt) => Ok(dt),
+            Field::Null => Ok(None),
+            f => Err(f),
+        }
+    }
+}
+
 /// Used to read a DBase table.
 ///
 /// The state parameter tracks the current state of the reader.
@@ -539,6 +2002,7 @@ pub trait DBaseRecord {
 pub struct TableReader<S: TableReaderState> {
     table: Box<DBaseTable>,
     state: S,
+    memo: Option<memo::MemoReader<Box<dyn memo::ReadSeek>>>,
 }
 
 /// Marker traits by for table reader states.
@@ -552,18 +2016,45 @@ pub struct Header<R> {
     inner: R,
 }
 
+/// State of a TableWriter after its header is written, while it's writing records.
+pub struct Writing<W> {
+    inner: W,
+    /// Where the header starts, to set its record count when finished.
+    header_pos: u64,
+    buf: Vec<u8>,
+    fields: Vec<FieldDescriptor>,
+    null_flags: NullFlagLayout,
+    indexes: Vec<ntx::NtxWriter<Box<dyn io::Write>>>,
+    n_records: usize,
+}
+
 impl<R> TableReaderState for Header<R> {}
 
 impl<R> TableWriterState for Header<R> {}
 
+impl<W> TableWriterState for Writing<W> {}
+
 /// There are no extra methods while in the Records state.
 impl<R: io::Read> TableReaderState for Records<R> {}
 
+impl<R: io::Read + io::Seek> TableReaderState for RandomAccess<R> {}
+
 /// Read a DBF table from the given path.
-pub fn try_from_path<P: AsRef<Path>>(path: P) -> DBaseResult<TableReader<Header<impl io::Read>>> {
-    let file = File::open(path)?;
+///
+/// If the table has `Memo` fields and a memo file with the same name,
+/// the memo file is opened too, so those fields are read as `MemoText`.
+pub fn try_from_path<P: AsRef<Path>>(path: P) -> DBaseResult<TableReader<Header<BufReader<File>>>> {
+    let file = File::open(&path)?;
     let reader = BufReader::new(file);
-    TableReader::<Header<BufReader<File>>>::new(reader)
+    let table = TableReader::<Header<BufReader<File>>>::new(reader)?;
+
+    if !table.fields().iter().any(|f| matches!(f.field_type, FieldType::Memo)) {
+        return Ok(table);
+    }
+    match memo::find_memo_file(&path) {
+        Some((memo_path, format)) => table.with_memo(BufReader::new(File::open(memo_path)?), format),
+        None => Ok(table),
+    }
 }
 
 impl<S: TableReaderState> TableReader<S> {
@@ -571,6 +2062,30 @@ impl<S: TableReaderState> TableReader<S> {
     pub fn n_records(&self) -> usize {
         self.table.n_records
     }
+
+    /// Get the table's field descriptors, in record order.
+    pub fn fields(&self) -> &[FieldDescriptor] {
+        &self.table.fields
+    }
+
+    /// Get the codepage used for the table's text.
+    pub fn codepage(&self) -> Codepage {
+        self.table.codepage
+    }
+
+    /// Get the date the table was last updated, according to its header.
+    ///
+    /// See `HeaderYear` for how the year is read.
+    pub fn last_updated(&self) -> NaiveDate {
+        self.table.last_updated
+    }
+
+    /// Get the rule for the header's last updated year.
+    ///
+    /// This writes years the way the table's header does, so edits keep the table's convention.
+    pub fn header_year(&self) -> HeaderYear {
+        self.table.header_year
+    }
 }
 
 impl<R> TableReader<Header<R>>
@@ -584,46 +2099,82 @@ impl<R> TableReader<Header<R>>
         let view = dbase_header::View::new(&data[..32]);
 
         let flags = view.flags().read();
-        let last_update_year = view.last_updated().year().read() as u16 + 2000;  // This "should" be `+ 1900` because it isn't updated for Y2K.
-        let last_update_month = view.last_updated().month().read();
-        let last_update_day = view.last_updated().day().read();
+        let last_updated_bytes = [
+            view.last_updated().year().read(),
+            view.last_updated().month().read(),
+            view.last_updated().day().read(),
+        ];
         let n_records = view.n_records().read() as usize;
         let n_header_bytes = view.n_header_bytes().read() as usize;
-        let n_fields = (n_header_bytes - 31) / 32;
+        let n_record_bytes = view.n_record_bytes().read() as usize;
+        let language_driver_id = view.language_driver_id().read();
+        let codepage = Codepage::from_language_driver(language_driver_id).unwrap_or_else(|| {
+            log::warn!("Unsupported language driver {language_driver_id:#x}; assuming CP437");
+            Codepage::default()
+        });
 
-        let last_updated = NaiveDate::from_ymd_opt(
-            last_update_year as i32,
-            last_update_month as u32,
-            last_update_day as u32,
-        ).ok_or(InvalidLastUpdated(last_update_year, last_update_month, last_update_day))?;
+        // Keep the table's convention for the year when it's updated.
+        let default = HeaderYear::default();
+        let header_year = HeaderYear { two_digit: last_updated_bytes[0] < default.pivot, ..default };
+        let last_updated = header_year.date(last_updated_bytes)?;
 
-        let mut fields = Vec::<FieldDescriptor>::with_capacity(n_fields);
-        for _ in 0..n_fields {
-            reader.read_exact(&mut data)?;
+        // Field descriptors continue until the terminator byte.
+        let mut fields = Vec::<FieldDescriptor>::new();
+        let mut header_len = data.len();
+        loop {
+            reader.read_exact(&mut data[..1])?;
+            header_len += 1;
+            if data[0] == 0x0d {
+                break;
+            }
+            if header_len + 31 > n_header_bytes {
+                return Err(DBaseErrorKind::InvalidHeaderTerminator { found: data[0] });
+            }
+            reader.read_exact(&mut data[1..])?;
+            header_len += 31;
             fields.push(FieldDescriptor::from_bytes(&data)?);
         }
 
+        // Records are read using the field lengths, so a different length means the header is damaged.
+        let record_size = 1 + fields.iter().map(|f| f.length).sum::<usize>();
+        if n_record_bytes != record_size {
+            log::warn!("Header gives a record length of {n_record_bytes}, but the fields need {record_size}; \
+                        the table may be damaged");
+        }
+
+        // Skip anything else in the header, such as Visual FoxPro's database container backlink.
+        let rest = n_header_bytes.saturating_sub(header_len) as u64;
+        io::copy(&mut <&mut R as io::Read>::take(&mut reader, rest), &mut io::sink())?;
+
         let table = DBaseTable {
             last_updated,
+            header_year,
+            null_flags: NullFlagLayout::new(&fields)?,
             fields,
             flags,
             n_records,
+            n_header_bytes,
+            codepage,
         };
 
-        let mut terminator: [u8; 1] = [0];
-        reader.read_exact(&mut terminator)?;
-        if terminator[0] != 0x0d {
-            return Err(DBaseErrorKind::InvalidHeaderTerminator { found: terminator[0] });
-        }
-
         Ok(TableReader {
             table: Box::new(table),
             state: Header {
                 inner: reader,
             },
+            memo: None,
         })
     }
 
+    /// Read `Memo` fields as `MemoText` from the given memo file.
+    pub fn with_memo(mut self, reader: impl io::Read + io::Seek + 'static, format: memo::MemoFormat)
+        -> DBaseResult<Self>
+    {
+        let reader: Box<dyn memo::ReadSeek> = Box::new(reader);
+        self.memo = Some(memo::MemoReader::new(reader, format)?);
+        Ok(self)
+    }
+
     /// Show fields from this table.
     pub fn print_fields(&self) {
         for f in &self.table.fields {
@@ -631,8 +2182,34 @@ impl<R> TableReader<Header<R>>
         }
     }
 
+    /// Read every record that isn't deleted as a `T`.
+    pub fn read_all<T: FromRecord>(self) -> DBaseResult<Vec<T>> {
+        let mut values = Vec::with_capacity(self.table.n_records);
+        let mut records = self.records();
+        while let Some(record) = records.next() {
+            values.push(T::from_record(record?)?);
+        }
+        Ok(values)
+    }
+
+    /// Read every record that isn't deleted as a `T`, like `read_all`,
+    /// but collect schema mismatches as warnings instead of failing.
+    ///
+    /// Each distinct warning is reported once, rather than once per record.
+    pub fn read_all_lenient<T: FromRecord>(self) -> DBaseResult<(Vec<T>, BTreeSet<SchemaWarning>)> {
+        let mut values = Vec::with_capacity(self.table.n_records);
+        let mut warnings = BTreeSet::new();
+        let mut record_warnings = Vec::new();
+        let mut records = self.records();
+        while let Some(record) = records.next() {
+            values.push(T::from_record_lenient(record?, &mut record_warnings)?);
+            warnings.extend(record_warnings.drain(..));
+        }
+        Ok((values, warnings))
+    }
+
     /// Begin reading records from the TableReader.
-    pub fn records<'a>(self) -> TableReader<Records<R>> {
+    pub fn records(self) -> TableReader<Records<R>> {
         let record_size = 1 + self.table.fields.iter().fold(0, |s, f| s + f.length);
         log::info!("Record size: {record_size}");
 
@@ -640,9 +2217,31 @@ impl<R> TableReader<Header<R>>
             table: self.table,
             state: Records {
                 record_size,
-                cur_record: 0,
+                record_number: 0,
+                include_deleted: false,
                 inner: self.state.inner,
             },
+            memo: self.memo,
+        }
+    }
+}
+
+impl<R> TableReader<Header<R>>
+    where R: io::Read + io::Seek
+{
+    /// Switch to reading records by record number from a seekable source.
+    pub fn random_access(self) -> TableReader<RandomAccess<R>> {
+        let record_size = 1 + self.table.fields.iter().fold(0, |s, f| s + f.length);
+
+        TableReader {
+            state: RandomAccess {
+                inner: self.state.inner,
+                record_size,
+                n_header_bytes: self.table.n_header_bytes,
+                record_count: None,
+            },
+            table: self.table,
+            memo: self.memo,
         }
     }
 }
@@ -653,13 +2252,27 @@ impl<R> TableReader<Header<R>>
 pub struct Records<R: io::Read> {
     inner: R,
     record_size: usize,
-    cur_record: usize,
+    /// Number of records read so far, including deleted ones.
+    record_number: usize,
+    include_deleted: bool,
+}
+
+/// When a Reader is in the RandomAccess state,
+/// you can jump directly to any record by its record number.
+#[derive(Debug)]
+pub struct RandomAccess<R: io::Read + io::Seek> {
+    inner: R,
+    record_size: usize,
+    n_header_bytes: usize,
+    record_count: Option<usize>,
 }
 
 /// An iterator over the fields of a single record.
 #[derive(Debug)]
 pub struct FieldIterator<'a> {
     table: &'a DBaseTable,
+    memo: Option<&'a mut memo::MemoReader<Box<dyn memo::ReadSeek>>>,
+    record_number: usize,
     buf: Vec<u8>,
     cur_field: usize,
     cur_byte: usize,
@@ -675,30 +2288,90 @@ pub struct FieldValue<'a> {
 /// While in the Records state, you can iterate over the table records.
 impl<R: io::Read> TableReader<Records<R>>
 {
+    /// Also return records marked deleted, which are normally skipped.
+    ///
+    /// Use `FieldIterator::is_deleted` to tell them apart.
+    pub fn include_deleted(mut self) -> Self {
+        self.state.include_deleted = true;
+        self
+    }
+
     /// Return Some(FieldIterator) over the next record,
     /// or None if there are no more records.
-    pub fn next(&mut self) -> Option<DBaseResult<FieldIterator>> {
+    pub fn next(&mut self) -> Option<DBaseResult<FieldIterator<'_>>> {
         const DELETED: u8 = 0x2a;
 
-        if self.state.cur_record == self.table.n_records {
-            return None;
-        }
-
         let mut buf = vec![0; self.state.record_size];
         loop {
+            // Deleted records count toward n_records, so stop based on every record read.
+            if self.state.record_number == self.table.n_records {
+                return None;
+            }
             if let Err(err) = self.state.inner.read_exact(&mut buf) {
                 return Some(Err(DBaseErrorKind::IOError(err)));
             }
-            if buf[0] != DELETED {
+            self.state.record_number += 1;
+            if buf[0] != DELETED || self.state.include_deleted {
                 break;
             }
-            log::info!("Record {} is deleted", self.state.cur_record);
+            log::info!("Record {} is deleted", self.state.record_number);
         }
 
-        self.state.cur_record += 1;
-
         Some(Ok(FieldIterator {
             table: &self.table,
+            memo: self.memo.as_mut(),
+            record_number: self.state.record_number,
+            buf,
+            cur_field: 0,
+            cur_byte: 1,
+        }))
+    }
+}
+
+impl<R> TableReader<RandomAccess<R>>
+    where R: io::Read + io::Seek
+{
+    /// Return the number of complete records actually present in the source.
+    ///
+    /// This is computed from the length of the source the first time it's called, then cached.
+    /// It won't exceed the header's record count, but can be smaller if the file was truncated.
+    pub fn record_count(&mut self) -> DBaseResult<usize> {
+        if let Some(n) = self.state.record_count {
+            return Ok(n);
+        }
+
+        let len = self.state.inner.seek(SeekFrom::End(0))? as usize;
+        let n = (len.saturating_sub(self.state.n_header_bytes) / self.state.record_size)
+            .min(self.table.n_records);
+        self.state.record_count = Some(n);
+        Ok(n)
+    }
+
+    /// Read the record with the given 1-based record number.
+    ///
+    /// Returns `None` if the record is marked deleted,
+    /// or `RecordOutOfRange` if there is no such record.
+    pub fn get(&mut self, record_number: usize) -> DBaseResult<Option<FieldIterator<'_>>> {
+        const DELETED: u8 = 0x2a;
+
+        let n_records = self.record_count()?;
+        if record_number == 0 || record_number > n_records {
+            return Err(DBaseErrorKind::RecordOutOfRange(record_number, n_records));
+        }
+
+        let offset = self.state.n_header_bytes + (record_number - 1) * self.state.record_size;
+        let mut buf = vec![0; self.state.record_size];
+        self.state.inner.seek(SeekFrom::Start(offset as u64))?;
+        self.state.inner.read_exact(&mut buf)?;
+
+        if buf[0] == DELETED {
+            return Ok(None);
+        }
+
+        Ok(Some(FieldIterator {
+            table: &self.table,
+            memo: self.memo.as_mut(),
+            record_number,
             buf,
             cur_field: 0,
             cur_byte: 1,
@@ -706,6 +2379,19 @@ impl<R: io::Read> TableReader<Records<R>>
     }
 }
 
+impl<'a> FieldIterator<'a> {
+    /// The 1-based position of this record in the table,
+    /// counting deleted records, as used by indexes and the legacy app.
+    pub fn record_number(&self) -> usize {
+        self.record_number
+    }
+
+    /// Whether this record is marked deleted.
+    pub fn is_deleted(&self) -> bool {
+        self.buf[0] == 0x2a
+    }
+}
+
 impl<'a> Iterator for FieldIterator<'a> {
     type Item = DBaseResult<FieldValue<'a>>;
 
@@ -717,18 +2403,691 @@ impl<'a> Iterator for FieldIterator<'a> {
         }
 
         let f = &self.table.fields[self.cur_field];
-        let r = f.read_field(&self.buf[self.cur_byte..]);
+        let null_flags = &self.table.null_flags;
+        let flags = null_flags.flags(&self.buf[1..]);
+        let r = null_flags.read_field(self.cur_field, f, &self.buf[self.cur_byte..], flags, self.table.codepage);
+        let r = match (r, self.memo.as_mut()) {
+            (Ok(Field::Memo(Some(block))), Some(memo)) => memo.read_text(block, self.table.codepage).map(Field::MemoText),
+            (Ok(Field::Memo(None)), Some(_)) => Ok(Field::MemoText(String::new())),
+            (r, _) => r,
+        };
+
+        // Move on even if this field can't be read, so each field gives one result.
+        self.cur_field += 1;
+        self.cur_byte += f.length;
+        Some(r.map(|value| FieldValue {
+            name: &f.name,
+            value,
+        }))
+    }
+}
+
+/// Every field type, for tests and fuzz targets that try each one.
+#[doc(hidden)]
+#[cfg_attr(not(test), allow(dead_code))]
+pub const FIELD_TYPES: [FieldType; 12] = [
+    FieldType::Character, FieldType::Date, FieldType::Float, FieldType::Boolean, FieldType::Memo,
+    FieldType::Numeric, FieldType::Integer, FieldType::Currency, FieldType::DateTime,
+    FieldType::Double, FieldType::NullFlags, FieldType::Varchar,
+];
+
+/// Read everything in a possibly malformed table, including deleted records, ignoring errors.
+///
+/// Tests and fuzz targets use this to check that the reader doesn't panic or loop forever.
+#[doc(hidden)]
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn read_everything(data: &[u8]) {
+    let Ok(reader) = TableReader::new(io::Cursor::new(data)) else {
+        return;
+    };
+    let n_records = reader.n_records();
+    let mut records = reader.records().include_deleted();
+    // A damaged table may claim more records than it has, so stop after the first error.
+    for _ in 0..=n_records {
+        match records.next() {
+            Some(Ok(record)) => record.for_each(drop),
+            _ => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+    use super::*;
+
+    struct Name(&'static str);
+
+    impl DBaseRecord for Name {
+        fn describe(&self) -> Vec<FieldDescriptor> {
+            vec![FieldDescriptor {
+                name: "NAME".to_string(),
+                field_type: FieldType::Character,
+                length: 10,
+                decimal_count: 0,
+                work_area_id: 0,
+                example: 0,
+            }]
+        }
+
+        fn to_record(&self) -> Vec<Field> {
+            vec![Field::Character(self.0.to_string())]
+        }
+    }
+
+    /// A table of Ann, Bob, and Zoe, with Bob marked deleted.
+    fn names_table() -> Vec<u8> {
+        let mut data = Vec::new();
+        TableWriter::new(&mut data).unwrap()
+            .write_records(&[Name("Ann"), Name("Bob"), Name("Zoe")]).unwrap();
+        let (n_header_bytes, record_size) = (32 + 32 + 1, 1 + 10);
+        data[n_header_bytes + record_size] = b'*';
+        data
+    }
+
+    fn first_value(record: Option<FieldIterator>) -> Option<String> {
+        record.map(|mut r| format!("{:?}", r.next().unwrap().unwrap().value))
+    }
+
+    #[test]
+    fn random_access_reads_records_by_number() {
+        let data = names_table();
+        let mut reader = TableReader::new(Cursor::new(&data)).unwrap().random_access();
+        assert_eq!(reader.record_count().unwrap(), 3);
+        // Read out of order, to make sure each read seeks to its record.
+        assert_eq!(first_value(reader.get(3).unwrap()).as_deref(), Some(r#"Character("Zoe")"#));
+        assert_eq!(first_value(reader.get(1).unwrap()).as_deref(), Some(r#"Character("Ann")"#));
+        assert_eq!(reader.get(3).unwrap().unwrap().record_number(), 3);
+        assert!(reader.get(2).unwrap().is_none(), "record 2 is deleted");
+        assert!(matches!(reader.get(0), Err(DBaseErrorKind::RecordOutOfRange(0, 3))));
+        assert!(matches!(reader.get(4), Err(DBaseErrorKind::RecordOutOfRange(4, 3))));
+
+        // A truncated table only has the records that are complete.
+        let truncated = &data[..data.len() - 1 - 11 - 1];
+        let mut reader = TableReader::new(Cursor::new(truncated)).unwrap().random_access();
+        assert_eq!(reader.record_count().unwrap(), 1);
+        assert!(first_value(reader.get(1).unwrap()).is_some());
+        assert!(matches!(reader.get(2), Err(DBaseErrorKind::RecordOutOfRange(2, 1))));
+    }
+
+    #[test]
+    fn editor_changes_records_in_place() {
+        struct NoFields;
+        impl DBaseRecord for NoFields {
+            fn describe(&self) -> Vec<FieldDescriptor> { vec![] }
+            fn to_record(&self) -> Vec<Field> { vec![] }
+        }
+
+        let mut data = names_table();
+        let mut editor = TableEditor::new(Cursor::new(&mut data)).unwrap();
+        assert_eq!(editor.n_records(), 3);
+        editor.set_field(1, "NAME", &Field::Character("Anne".to_string())).unwrap();
+        assert!(matches!(editor.set_field(1, "NOPE", &Field::Character(String::new())),
+            Err(DBaseErrorKind::UnknownField(name)) if name == "NOPE"));
+        assert_eq!(editor.append(&Name("Dee")).unwrap(), 4);
+        assert_eq!(editor.n_records(), 4);
+
+        assert!(editor.is_deleted(2).unwrap());
+        editor.undelete(2).unwrap();
+        editor.update(2, &Name("Bea")).unwrap();
+        editor.delete(3).unwrap();
+        assert!(editor.is_deleted(3).unwrap());
+        assert!(editor.get(3).unwrap().is_none());
+        assert!(matches!(editor.delete(5), Err(DBaseErrorKind::RecordOutOfRange(5, 4))));
+        assert!(matches!(editor.update(2, &NoFields), Err(DBaseErrorKind::FieldCountMismatch(1, 0))));
+        drop(editor);
+
+        let reader = TableReader::new(Cursor::new(&data)).unwrap();
+        assert_eq!(reader.n_records(), 4);
+        let mut records = reader.records();
+        let mut names = Vec::new();
+        while let Some(record) = records.next() {
+            let record = record.unwrap();
+            let number = record.record_number();
+            names.push((number, first_value(Some(record)).unwrap()));
+        }
+        assert_eq!(names, [
+            (1, r#"Character("Anne")"#.to_string()),
+            (2, r#"Character("Bea")"#.to_string()),
+            (4, r#"Character("Dee")"#.to_string()),
+        ]);
+        assert_eq!(data.last(), Some(&0x1a));
+    }
 
-        match r {
-            Err(err) => Some(Err(err)),
-            Ok(value) => {
-                self.cur_field += 1;
-                self.cur_byte += f.length;
-                Some(Ok(FieldValue {
-                    name: &f.name,
-                    value,
-                }))
+
+    #[test]
+    fn pack_removes_deleted_records() {
+        let path = std::env::temp_dir().join(format!("pack-{}.dbf", std::process::id()));
+        std::fs::write(&path, names_table()).unwrap();
+
+        let mut editor = edit_path(&path).unwrap();
+        editor.append(&Name("Dee")).unwrap();
+        editor.delete(1).unwrap();
+        assert_eq!(editor.pack().unwrap(), 2);
+        assert_eq!(editor.n_records(), 2);
+        drop(editor);
+
+        let data = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let reader = TableReader::new(Cursor::new(&data)).unwrap();
+        assert_eq!(reader.n_records(), 2);
+        assert_eq!(data.len(), reader.table.n_header_bytes + 2 * (1 + 10) + 1);
+        assert_eq!(data.last(), Some(&0x1a));
+
+        let mut records = reader.records();
+        let mut names = Vec::new();
+        while let Some(record) = records.next() {
+            names.push(first_value(Some(record.unwrap())).unwrap());
+        }
+        assert_eq!(names, [r#"Character("Zoe")"#, r#"Character("Dee")"#]);
+    }
+
+    struct Reading(Vec<Field>);
+
+    impl DBaseRecord for Reading {
+        fn describe(&self) -> Vec<FieldDescriptor> {
+            let field = |name: &str, field_type, length| FieldDescriptor {
+                name: name.to_string(),
+                field_type,
+                length,
+                decimal_count: 0,
+                work_area_id: 0,
+                example: 0,
+            };
+            vec![
+                field("COUNT", FieldType::Integer, 4),
+                field("FEE", FieldType::Currency, 8),
+                field("STAMP", FieldType::DateTime, 8),
+                field("TIME", FieldType::Double, 8).nullable(),
+                field("NAME", FieldType::Varchar, 10),
+                field("EVENT", FieldType::Character, 6).nullable(),
+                FieldDescriptor::null_flags(3),
+            ]
+        }
+
+        fn to_record(&self) -> Vec<Field> {
+            self.0.clone()
+        }
+    }
+
+    /// A record with any layout, such as one read from a table.
+    struct Row(Vec<FieldDescriptor>, Vec<Field>);
+
+    impl DBaseRecord for Row {
+        fn describe(&self) -> Vec<FieldDescriptor> {
+            self.0.clone()
+        }
+
+        fn to_record(&self) -> Vec<Field> {
+            self.1.clone()
+        }
+    }
+
+    /// Every type dBASE III tables can hold, with a record of empty values.
+    fn dbase_rows() -> Vec<Row> {
+        let field = |name: &str, field_type, length, decimal_count| FieldDescriptor {
+            name: name.to_string(), field_type, length, decimal_count, work_area_id: 0, example: 1,
+        };
+        let fields = vec![
+            field("NAME", FieldType::Character, 12, 0),
+            field("JOINED", FieldType::Date, 8, 0),
+            field("WEIGHT", FieldType::Float, 10, 3),
+            field("ACTIVE", FieldType::Boolean, 1, 0),
+            field("NOTES", FieldType::Memo, 10, 0),
+            field("DUES", FieldType::Numeric, 7, 2),
+        ];
+        vec![
+            Row(fields.clone(), vec![
+                Field::Character("Jo Doe".to_string()),
+                Field::Date(NaiveDate::from_ymd_opt(1999, 12, 31)),
+                Field::Float(72.125),
+                Field::Boolean(Some(true)),
+                Field::Memo(Some(12)),
+                Field::Numeric(Some(Decimal::new(-4550, 2))),
+            ]),
+            Row(fields, vec![
+                Field::Character(String::new()),
+                Field::Date(None),
+                Field::Float(0.0),
+                Field::Boolean(None),
+                Field::Memo(None),
+                Field::Numeric(None),
+            ]),
+        ]
+    }
+
+    /// Every Visual FoxPro type, with a record of empty and null values.
+    fn foxpro_rows() -> Vec<Row> {
+        let fields = Reading(vec![]).describe();
+        let stamp = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap().and_hms_milli_opt(23, 59, 59, 999).unwrap();
+        vec![
+            Row(fields.clone(), vec![
+                Field::Integer(i32::MIN),
+                Field::Currency(Decimal::new(-922_337_203_685_477, 4)),
+                Field::DateTime(Some(stamp)),
+                Field::Double(-0.1),
+                Field::Varchar("Zoë".to_string()),
+                Field::Character("Steer".to_string()),
+                Field::NullFlags(vec![0]),
+            ]),
+            Row(fields, vec![
+                Field::Integer(0),
+                Field::Currency(Decimal::new(0, 4)),
+                Field::DateTime(None),
+                Field::Null,
+                Field::Varchar(String::new()),
+                Field::Null,
+                Field::NullFlags(vec![0]),
+            ]),
+        ]
+    }
+
+    /// Read every record of a table, including deleted ones.
+    fn read_rows(data: &[u8]) -> Vec<Row> {
+        let reader = TableReader::new(Cursor::new(data)).unwrap();
+        let fields = reader.fields().to_vec();
+        let mut records = reader.records().include_deleted();
+        let mut rows = Vec::new();
+        while let Some(record) = records.next() {
+            let values = record.unwrap().map(|v| v.unwrap().value).collect();
+            rows.push(Row(fields.clone(), values));
+        }
+        rows
+    }
+
+    /// Write records like the given table, with its codepage, header year, and last updated date.
+    fn write_like(table: &[u8], rows: &[Row]) -> Vec<u8> {
+        let reader = TableReader::new(Cursor::new(table)).unwrap();
+        let mut data = Vec::new();
+        TableWriter::new(&mut data).unwrap()
+            .with_codepage(reader.codepage())
+            .with_header_year(reader.header_year())
+            .write_records(rows)
+            .unwrap();
+        data[1..4].copy_from_slice(&table[1..4]);
+        data
+    }
+
+    const DBASE_FIXTURE: &[u8] = include_bytes!("../tests/fixtures/dbase.dbf");
+    const FOXPRO_FIXTURE: &[u8] = include_bytes!("../tests/fixtures/foxpro.dbf");
+    const LEGACY_FIXTURE: &[u8] = include_bytes!("../tests/fixtures/legacy.dbf");
+
+    #[test]
+    fn every_field_type_round_trips() {
+        for (fixture, rows) in [(DBASE_FIXTURE, dbase_rows()), (FOXPRO_FIXTURE, foxpro_rows())] {
+            assert_eq!(write_like(fixture, &rows), fixture);
+
+            let read = read_rows(fixture);
+            assert_eq!(read.len(), rows.len());
+            for (r, expected) in read.iter().zip(&rows) {
+                assert_eq!(format!("{:?}", r.0), format!("{:?}", expected.0));
+                // The null flags are computed when writing, so only compare the other values.
+                let values = |row: &Row| row.1.iter()
+                    .filter(|v| !matches!(v, Field::NullFlags(_)))
+                    .map(|v| format!("{v:?}"))
+                    .collect::<Vec<_>>();
+                assert_eq!(values(r), values(expected));
             }
+            assert_eq!(write_like(fixture, &read), fixture);
+            assert!(repair::check_table(fixture).unwrap().problems.is_empty());
+        }
+    }
+
+    #[test]
+    fn legacy_tables_skip_deleted_records() {
+        let reader = TableReader::new(Cursor::new(LEGACY_FIXTURE)).unwrap();
+        assert_eq!(reader.n_records(), 3);
+        assert_eq!(reader.last_updated(), NaiveDate::from_ymd_opt(1999, 12, 31).unwrap());
+        assert_eq!(reader.codepage(), Codepage::default());
+        assert_eq!(reader.fields().iter().map(describe).collect::<Vec<_>>(), [
+            "NAME C10.0", "JOINED D8.0", "DUES N6.2", "ACTIVE L1.0",
+        ]);
+
+        let mut records = reader.records();
+        let mut names = Vec::new();
+        while let Some(record) = records.next() {
+            let record = record.unwrap();
+            assert!(!record.is_deleted());
+            names.push((record.record_number(), record.map(|v| format!("{:?}", v.unwrap().value)).collect::<Vec<_>>()));
+        }
+        assert_eq!(names, [
+            (1, vec![
+                r#"Character("Ann")"#.to_string(),
+                "Date(Some(1999-01-05))".to_string(),
+                "Numeric(Some(Decimal { mantissa: 1250, exponent: 2 }))".to_string(),
+                "Boolean(Some(true))".to_string(),
+            ]),
+            (3, vec![
+                r#"Character("Zoë")"#.to_string(),
+                "Date(None)".to_string(),
+                "Numeric(None)".to_string(),
+                "Boolean(None)".to_string(),
+            ]),
+        ]);
+
+        let all = read_rows(LEGACY_FIXTURE);
+        assert_eq!(all.len(), 3);
+        assert!(matches!(&all[1].1[0], Field::Character(name) if name == "Bob"));
+    }
+
+    #[test]
+    fn foxpro_memo_fields_hold_binary_block_numbers() {
+        let field = |name: &str, length| FieldDescriptor {
+            name: name.to_string(), field_type: FieldType::Memo, length, decimal_count: 0, work_area_id: 0, example: 0,
+        };
+        let fields = vec![field("NOTES", 4), field("HISTORY", 10)];
+        let rows = [
+            Row(fields.clone(), vec![Field::Memo(Some(70_000)), Field::Memo(Some(8))]),
+            Row(fields.clone(), vec![Field::Memo(None), Field::Memo(None)]),
+        ];
+        let mut data = Vec::new();
+        TableWriter::new(&mut data).unwrap().write_records(&rows).unwrap();
+
+        let reader = TableReader::new(Cursor::new(&data)).unwrap();
+        let first = reader.table.n_header_bytes + 1;
+        assert_eq!(&data[first..first + 4], &70_000_u32.to_le_bytes());
+        assert_eq!(&data[first + 4..first + 14], b"         8");
+        let values = read_rows(&data).iter()
+            .map(|r| format!("{:?}", r.1))
+            .collect::<Vec<_>>();
+        assert_eq!(values, [
+            "[Memo(Some(70000)), Memo(Some(8))]",
+            "[Memo(None), Memo(None)]",
+        ]);
+
+        let too_large = Row(fields, vec![Field::Memo(Some(u64::from(u32::MAX) + 1)), Field::Memo(None)]);
+        assert!(matches!(TableWriter::new(Vec::new()).unwrap().write_records(&[too_large]),
+            Err(DBaseErrorKind::DataExceedsLength(_))));
+
+        // General fields hold OLE objects, which this library doesn't support.
+        let mut general = LEGACY_FIXTURE.to_vec();
+        general[32 + 11] = b'G';
+        assert!(matches!(TableReader::new(Cursor::new(&general)), Err(DBaseErrorKind::UnsupportedFieldType(_))));
+    }
+
+    fn describe(f: &FieldDescriptor) -> String {
+        let letter = match f.field_type {
+            FieldType::Character => "C",
+            FieldType::Date => "D",
+            FieldType::Numeric => "N",
+            FieldType::Boolean => "L",
+            _ => "?",
+        };
+        format!("{} {letter}{}.{}", f.name, f.length, f.decimal_count)
+    }
+
+    #[test]
+    fn decimal_formatting_edge_cases() {
+        let d = |s: &str| s.parse::<Decimal>().unwrap();
+        assert_eq!(Decimal::new(0, 2).to_string(), "0.00");
+        assert_eq!(Decimal::new(-5, 3).to_string(), "-0.005");
+        assert_eq!(Decimal::new(i64::MIN, 0).to_string(), "-9223372036854775808");
+        assert_eq!(Decimal::new(i64::MAX, 18).to_string(), "9.223372036854775807");
+        assert_eq!(format!("{:.0}", d("2.5")), "3");
+        assert_eq!(format!("{:.0}", d("-2.5")), "-3");
+        assert_eq!(format!("{:.4}", d("1.5")), "1.5000");
+        assert_eq!(format!("{:.2}", d("7")), "7.00");
+        assert_eq!(format!("{:3}", d("-12.50")), "-12.50");
+        assert_eq!(format!("{:7}|", d("0.5")), "    0.5|");
+
+        let field = |length, decimal_count| FieldDescriptor {
+            name: "N".to_string(), field_type: FieldType::Numeric, length, decimal_count, work_area_id: 0, example: 1,
+        };
+        let write = |f: FieldDescriptor, value: &str| {
+            let mut data = Vec::new();
+            f.write_field(&Field::Numeric(Some(d(value))), Codepage::default(), &mut data).map(|_| data)
+        };
+        assert_eq!(write(field(4, 0), "2.5").unwrap(), b"   3");
+        assert_eq!(write(field(5, 2), "-0.999").unwrap(), b"-1.00");
+        assert!(write(field(4, 2), "-1.5").is_err());
+        assert!(write(field(24, 19), "1").is_err());
+
+        let begin = |f| TableWriter::new(Cursor::new(Vec::new())).unwrap().begin(vec![f]).map(|_| ());
+        assert!(matches!(begin(field(24, 19)), Err(DBaseErrorKind::InvalidSchema(_))));
+        assert!(begin(field(24, 18)).is_ok());
+
+        let read = |f: FieldDescriptor, data: &[u8]| match f.read_field(data, Codepage::default()) {
+            Ok(Field::Numeric(n)) => n.map(|n| n.to_string()),
+            other => panic!("unexpected {other:?}"),
+        };
+        assert_eq!(read(field(6, 2), b"      "), None);
+        assert_eq!(read(field(6, 2), b"  -.50"), Some("-0.50".to_string()));
+        assert_eq!(read(field(6, 2), b" 12.5 "), Some("12.5".to_string()));
+        assert!(field(6, 2).read_field(b"12..50", Codepage::default()).is_err());
+    }
+
+    #[test]
+    fn malformed_tables_do_not_panic() {
+        for fixture in [DBASE_FIXTURE, FOXPRO_FIXTURE, LEGACY_FIXTURE] {
+            for len in 0..fixture.len() {
+                read_everything(&fixture[..len]);
+            }
+            for i in 0..fixture.len() {
+                for b in [0x00, 0x01, 0x0d, 0x20, 0x2a, 0x30, 0x7f, 0xff] {
+                    let mut data = fixture.to_vec();
+                    data[i] = b;
+                    read_everything(&data);
+                }
+            }
+        }
+
+        let inputs: [&[u8]; 8] = [b"", b" ", b"\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff", b"-", b".", b"99999999", b"1e400", b"\0\0\0\0\0\0\0\x80"];
+        for field_type in FIELD_TYPES {
+            for decimal_count in [0, 2, 30, 255] {
+                for input in inputs {
+                    let field = FieldDescriptor {
+                        name: "F".to_string(), field_type: field_type.clone(), length: input.len(), decimal_count,
+                        work_area_id: 0, example: 1,
+                    };
+                    let _ = field.read_field(input, Codepage::default());
+                }
+            }
+        }
+    }
+
+    #[derive(Debug, Default, DBaseRecord)]
+    #[dbase(ignore = "OLD")]
+    struct Entry {
+        #[dbase(name = "ENTRANT", length = 4)]
+        igra_number: String,
+        #[dbase(length = 7, decimals = 2)]
+        fee: Decimal,
+        #[dbase(length = 8)]
+        paid: Option<NaiveDate>,
+        #[dbase(field_type = Float, length = 8)]
+        time: f64,
+        #[dbase(skip)]
+        checked: bool,
+    }
+
+    #[test]
+    fn derived_records_round_trip() {
+        let entries = [
+            Entry {
+                igra_number: "1234".to_string(),
+                fee: Decimal { mantissa: 4550, exponent: 2 },
+                paid: NaiveDate::from_ymd_opt(2023, 7, 14),
+                time: 12.5,
+                checked: true,
+            },
+            Entry {
+                igra_number: "0042".to_string(),
+                fee: Decimal { mantissa: 0, exponent: 2 },
+                ..Entry::default()
+            },
+        ];
+        assert_eq!(entries[0].describe().iter().map(|f| f.name.as_str()).collect::<Vec<_>>(),
+                   ["ENTRANT", "FEE", "PAID", "TIME"]);
+
+        let mut data = Vec::new();
+        TableWriter::new(&mut data).unwrap().write_records(&entries).unwrap();
+
+        let read = TableReader::new(Cursor::new(data)).unwrap().read_all::<Entry>().unwrap();
+        assert!(read.iter().all(|e| !e.checked));
+        let expected = entries.map(|e| Entry { checked: false, ..e });
+        assert_eq!(format!("{read:?}"), format!("{expected:?}"));
+    }
+
+    #[test]
+    fn streamed_tables_match_written_ones() {
+        let entries = (0..3)
+            .map(|i| Entry { igra_number: format!("{i:04}"), ..Entry::default() })
+            .collect::<Vec<_>>();
+        let mut written = Vec::new();
+        TableWriter::new(&mut written).unwrap().write_records(&entries).unwrap();
+
+        let mut tw = TableWriter::new(Cursor::new(Vec::new())).unwrap()
+            .begin(Entry::default().describe())
+            .unwrap();
+        tw.write_record(&entries[0]).unwrap();
+        tw.write_iter(&entries[1..]).unwrap();
+        assert_eq!(tw.n_records(), 3);
+        assert_eq!(tw.finish().unwrap().into_inner(), written);
+
+        let empty = TableWriter::new(Cursor::new(Vec::new())).unwrap()
+            .begin(Entry::default().describe())
+            .unwrap()
+            .finish()
+            .unwrap()
+            .into_inner();
+        let reader = TableReader::new(Cursor::new(&empty)).unwrap();
+        assert_eq!(reader.fields().len(), 4);
+        assert!(reader.read_all::<Entry>().unwrap().is_empty());
+        assert!(matches!(TableWriter::new(Vec::new()).unwrap().write_records::<Entry>(&[]),
+                         Err(DBaseErrorKind::NoRecords)));
+    }
+
+    #[test]
+    fn decimal_arithmetic_is_exact() {
+        let d = |s: &str| s.parse::<Decimal>().unwrap();
+        assert_eq!(Decimal::from(1200).to_string(), "1200");
+        assert_eq!(Decimal::from(-7).to_string(), "-7");
+        assert_eq!(d("-1.50").to_string(), "-1.50");
+        assert_eq!(d("-0.05").to_string(), "-0.05");
+        assert_eq!(d("-.5").integral(), 0);
+        assert!("1.-5".parse::<Decimal>().is_err() && "12a".parse::<Decimal>().is_err());
+        assert!(".".parse::<Decimal>().is_err() && "-".parse::<Decimal>().is_err() && "+.".parse::<Decimal>().is_err());
+
+        assert_eq!(d("0.1") + d("0.2"), d("0.3"));
+        assert_eq!((d("10") - d("12.50")).to_string(), "-2.50");
+        assert_eq!((d("1.5") * d("-2.25")).to_string(), "-3.375");
+        assert_eq!([d("45.50"), d("30"), d("-0.25")].iter().sum::<Decimal>().to_string(), "75.25");
+        assert!(d("1.5") == d("1.50") && d("-2") < d("-1.99") && d("0.30") > d("0.29"));
+
+        assert_eq!(d("1.005").rescale(2).unwrap().to_string(), "1.01");
+        assert_eq!(d("-1.005").rescale(2).unwrap().to_string(), "-1.01");
+        assert_eq!(d("1.004").rescale(2).unwrap().to_string(), "1.00");
+        assert_eq!(d("7").rescale(2).unwrap().to_string(), "7.00");
+        assert_eq!(format!("{:>8.1}", d("-0.25")), "    -0.3");
+
+        let big = Decimal::new(i64::MAX, 18);
+        assert!(d("1.5").rescale(19).is_err() && d("10").rescale(18).is_err());
+        assert!(big.checked_mul(d("0.1")).is_err() && d("0.5").checked_mul(d("2")).is_ok());
+        assert!(big.checked_add(big).is_err() && (-big).checked_sub(big).is_err() && big.checked_add(d("1")).is_err());
+        assert_eq!(big.checked_sub(big).unwrap(), Decimal::default());
+        assert_eq!(format!("{:.20}", d("1.5")), "1.50000000000000000000");
+        assert_eq!(format!("{:.20}", big), "9.22337203685477580700");
+
+        let field = FieldDescriptor {
+            name: "PAID".to_string(), field_type: FieldType::Numeric, length: 7, decimal_count: 2, work_area_id: 0, example: 1,
+        };
+        let mut data = Vec::new();
+        field.write_field(&Field::Numeric(Some(d("-0.5"))), Codepage::default(), &mut data).unwrap();
+        assert_eq!(data, b"  -0.50");
+        assert!(matches!(field.read_field(&data, Codepage::default()), Ok(Field::Numeric(Some(n))) if n == d("-0.5")));
+
+        let json = serde_json::to_string(&[d("-12.50")]).unwrap();
+        assert_eq!(json, r#"["-12.50"]"#);
+        let back: Vec<Decimal> = serde_json::from_str(r#"["-12.50", 3, 0.1]"#).unwrap();
+        assert_eq!(back, [d("-12.5"), d("3"), d("0.1")]);
+    }
+
+    #[test]
+    fn header_year_reads_both_conventions() {
+        let header_year = HeaderYear::default();
+        assert_eq!(header_year.year(124), 2024);
+        assert_eq!(header_year.year(24), 2024);
+        assert_eq!(header_year.year(95), 1995);
+        assert_eq!(header_year.byte(2024), 124);
+        assert_eq!(HeaderYear { two_digit: true, ..header_year }.byte(2024), 24);
+
+        // Edits keep the two-digit years written by the legacy app.
+        let mut data = Vec::new();
+        TableWriter::new(&mut data).unwrap()
+            .with_header_year(HeaderYear { two_digit: true, ..header_year })
+            .write_records(&[Entry::default()])
+            .unwrap();
+        let this_year = chrono::Utc::now().naive_utc().date().year();
+        assert_eq!(data[1] as i32, this_year % 100);
+
+        let mut editor = TableEditor::new(Cursor::new(&mut data)).unwrap();
+        editor.append(&Entry::default()).unwrap();
+        assert_eq!(data[1] as i32, this_year % 100);
+        let reader = TableReader::new(Cursor::new(&data)).unwrap();
+        assert_eq!(reader.last_updated().year(), this_year);
+    }
+
+    #[derive(Debug, Default, DBaseRecord)]
+    struct Entrant {
+        #[dbase(name = "ENTRANT", length = 4)]
+        igra_number: String,
+    }
+
+    #[test]
+    fn lenient_reads_warn_about_unexpected_columns() {
+        let entries = [Entry { igra_number: "1234".to_string(), ..Entry::default() }, Entry::default()];
+        let mut data = Vec::new();
+        TableWriter::new(&mut data).unwrap().write_records(&entries).unwrap();
+
+        let strict = TableReader::new(Cursor::new(&data)).unwrap().read_all::<Entrant>();
+        assert!(matches!(strict, Err(DBaseErrorKind::UnexpectedColumn(name)) if name == "FEE"));
+
+        let (read, warnings) = TableReader::new(Cursor::new(&data)).unwrap().read_all_lenient::<Entrant>().unwrap();
+        assert_eq!(read.len(), 2);
+        assert_eq!(read[0].igra_number, "1234");
+        assert_eq!(warnings.iter().map(|w| w.to_string()).collect::<Vec<_>>(), [
+            "the table has an unexpected column: 'FEE'",
+            "the table has an unexpected column: 'PAID'",
+            "the table has an unexpected column: 'TIME'",
+        ]);
+    }
+
+    #[test]
+    fn foxpro_types_round_trip() {
+        let stamp = NaiveDate::from_ymd_opt(2023, 7, 14).unwrap().and_hms_milli_opt(13, 5, 9, 250).unwrap();
+        let records = [
+            Reading(vec![
+                Field::Integer(-42),
+                Field::Currency(Decimal { mantissa: 255_000, exponent: 4 }),
+                Field::DateTime(Some(stamp)),
+                Field::Double(12.375),
+                Field::Varchar("Ann ".to_string()),
+                Field::Character("Barrel".to_string()),
+                Field::NullFlags(vec![]),
+            ]),
+            Reading(vec![
+                Field::Integer(7),
+                Field::Currency(Decimal { mantissa: -10_000, exponent: 4 }),
+                Field::DateTime(None),
+                Field::Null,
+                Field::Varchar("Bartholome".to_string()),
+                Field::Null,
+                Field::NullFlags(vec![]),
+            ]),
+        ];
+
+        let mut data = Vec::new();
+        TableWriter::new(&mut data).unwrap().write_records(&records).unwrap();
+        assert_eq!(data[0], 0x32);
+
+        let mut reader = TableReader::new(Cursor::new(data)).unwrap().records();
+        for expected in &records {
+            let values = reader.next().unwrap().unwrap()
+                .map(|v| v.unwrap().value)
+                .collect::<Vec<_>>();
+            // Field doesn't implement PartialEq, so compare the values' debug output.
+            assert_eq!(format!("{:?}", &values[..6]), format!("{:?}", &expected.0[..6]));
         }
+        assert!(reader.next().is_none());
     }
 }
@@ -0,0 +1,227 @@
+//! Codepages for text in DBF tables.
+//!
+//! dBASE stores text as single bytes in whatever codepage the table was created with,
+//! which the header records as a "language driver" ID.
+//! The bottom half of each supported codepage is ASCII, so only the top half needs a table.
+//! Tables without a language driver are assumed to use CP437, the original DOS codepage,
+//! which is what the legacy app's tables use.
+
+use std::borrow::Cow;
+
+use crate::xbase::{DBaseErrorKind, DBaseResult};
+
+/// A single-byte codepage used to encode text in a table.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Codepage {
+    /// DOS US, the default.
+    #[default]
+    Cp437,
+    /// DOS Multilingual (Latin I).
+    Cp850,
+    /// Windows ANSI (Latin I).
+    Cp1252,
+}
+
+impl Codepage {
+    /// Get the codepage for a header's language driver ID,
+    /// or `None` if it's one this library doesn't support.
+    ///
+    /// A language driver ID of `0` means the table doesn't specify one,
+    /// so it's treated as CP437.
+    pub fn from_language_driver(id: u8) -> Option<Self> {
+        match id {
+            0x00 | 0x01 => Some(Codepage::Cp437),
+            0x02 => Some(Codepage::Cp850),
+            0x03 | 0x57 => Some(Codepage::Cp1252),
+            _ => None,
+        }
+    }
+
+    /// The language driver ID to write in the header of a table using this codepage.
+    pub fn language_driver_id(&self) -> u8 {
+        match self {
+            Codepage::Cp437 => 0x01,
+            Codepage::Cp850 => 0x02,
+            Codepage::Cp1252 => 0x03,
+        }
+    }
+
+    /// Characters for bytes `0x80` to `0xff`.
+    fn high_half(&self) -> &'static [char; 128] {
+        match self {
+            Codepage::Cp437 => &CP437,
+            Codepage::Cp850 => &CP850,
+            Codepage::Cp1252 => &CP1252,
+        }
+    }
+
+    fn encode_char(&self, c: char) -> Option<u8> {
+        if c.is_ascii() {
+            return Some(c as u8);
+        }
+        self.high_half().iter().position(|&h| h == c).map(|i| 0x80 + i as u8)
+    }
+
+    /// Check whether this codepage has a byte for the given character.
+    pub fn can_encode(&self, c: char) -> bool {
+        self.encode_char(c).is_some()
+    }
+
+    /// Decode bytes from a table into a string.
+    ///
+    /// Every byte maps to some character, so this can't fail.
+    pub fn decode(&self, data: &[u8]) -> String {
+        let high = self.high_half();
+        data.iter()
+            .map(|&b| if b < 0x80 { b as char } else { high[(b - 0x80) as usize] })
+            .collect()
+    }
+
+    /// Encode a string for a table, one byte per character.
+    ///
+    /// This returns an error if the string has any characters the codepage can't represent;
+    /// use `transliterate` first to replace them.
+    pub fn encode(&self, s: &str) -> DBaseResult<Vec<u8>> {
+        s.chars()
+            .map(|c| self.encode_char(c))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| DBaseErrorKind::UnencodableText(s.to_string(), *self))
+    }
+
+    /// Replace characters this codepage can't represent with ASCII approximations,
+    /// e.g. `Łukasz` becomes `Lukasz`, and curly quotes become straight ones.
+    /// Characters that can be encoded are left alone,
+    /// and those without an approximation become `?`.
+    pub fn transliterate<'a>(&self, s: &'a str) -> Cow<'a, str> {
+        if s.chars().all(|c| self.can_encode(c)) {
+            return Cow::Borrowed(s);
+        }
+
+        let mut out = String::with_capacity(s.len());
+        for c in s.chars() {
+            if self.can_encode(c) {
+                out.push(c);
+            } else {
+                out.push_str(transliterate_char(c));
+            }
+        }
+        Cow::Owned(out)
+    }
+}
+
+/// An ASCII approximation of a character, or `?` if there isn't a good one.
+fn transliterate_char(c: char) -> &'static str {
+    match c {
+        'À' | 'Á' | 'Â' | 'Ã' | 'Ä' | 'Å' | 'Ā' | 'Ă' | 'Ą' => "A",
+        'Æ' => "AE",
+        'Ç' | 'Ć' | 'Ĉ' | 'Ċ' | 'Č' => "C",
+        'È' | 'É' | 'Ê' | 'Ë' | 'Ē' | 'Ĕ' | 'Ė' | 'Ę' | 'Ě' => "E",
+        'Ì' | 'Í' | 'Î' | 'Ï' | 'Ĩ' | 'Ī' | 'Ĭ' | 'Į' | 'İ' => "I",
+        'Ð' | 'Ď' | 'Đ' => "D",
+        'Ñ' | 'Ń' | 'Ņ' | 'Ň' | 'Ŋ' => "N",
+        'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ö' | 'Ø' | 'Ō' | 'Ŏ' | 'Ő' => "O",
+        '×' => "x",
+        'Ù' | 'Ú' | 'Û' | 'Ü' | 'Ũ' | 'Ū' | 'Ŭ' | 'Ů' | 'Ű' | 'Ų' => "U",
+        'Ý' | 'Ŷ' | 'Ÿ' => "Y",
+        'Þ' => "TH",
+        'ß' => "ss",
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'æ' => "ae",
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+        'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+        'ð' | 'ď' | 'đ' => "d",
+        'ñ' | 'ń' | 'ņ' | 'ň' | 'ŉ' | 'ŋ' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+        '÷' => "/",
+        'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+        'ý' | 'ÿ' | 'ŷ' => "y",
+        'þ' => "th",
+        'Ĝ' | 'Ğ' | 'Ġ' | 'Ģ' => "G",
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+        'Ĥ' | 'Ħ' => "H",
+        'ĥ' | 'ħ' => "h",
+        'Ĳ' => "IJ",
+        'ĳ' => "ij",
+        'Ĵ' => "J",
+        'ĵ' => "j",
+        'Ķ' => "K",
+        'ķ' | 'ĸ' => "k",
+        'Ĺ' | 'Ļ' | 'Ľ' | 'Ŀ' | 'Ł' => "L",
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+        'Œ' => "OE",
+        'œ' => "oe",
+        'Ŕ' | 'Ŗ' | 'Ř' => "R",
+        'ŕ' | 'ŗ' | 'ř' => "r",
+        'Ś' | 'Ŝ' | 'Ş' | 'Š' => "S",
+        'ś' | 'ŝ' | 'ş' | 'š' | 'ſ' => "s",
+        'Ţ' | 'Ť' | 'Ŧ' => "T",
+        'ţ' | 'ť' | 'ŧ' => "t",
+        'Ŵ' => "W",
+        'ŵ' => "w",
+        'Ź' | 'Ż' | 'Ž' => "Z",
+        'ź' | 'ż' | 'ž' => "z",
+        '\u{a0}' | '\u{2002}' | '\u{2003}' | '\u{2009}' => " ",
+        '\u{ad}' | '\u{2010}' | '\u{2011}' | '\u{2012}' | '\u{2013}' | '\u{2014}' | '\u{2212}' => "-",
+        '\u{2018}' | '\u{2019}' | '\u{201a}' | '\u{2032}' | '\u{b4}' => "'",
+        '\u{201c}' | '\u{201d}' | '\u{201e}' | '\u{2033}' | '\u{ab}' | '\u{bb}' => "\"",
+        '\u{2026}' => "...",
+        _ => "?",
+    }
+}
+
+const CP437: [char; 128] = [
+    'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å',
+    'É', 'æ', 'Æ', 'ô', 'ö', 'ò', 'û', 'ù', 'ÿ', 'Ö', 'Ü', '¢', '£', '¥', '₧', 'ƒ',
+    'á', 'í', 'ó', 'ú', 'ñ', 'Ñ', 'ª', 'º', '¿', '⌐', '¬', '½', '¼', '¡', '«', '»',
+    '░', '▒', '▓', '│', '┤', '╡', '╢', '╖', '╕', '╣', '║', '╗', '╝', '╜', '╛', '┐',
+    '└', '┴', '┬', '├', '─', '┼', '╞', '╟', '╚', '╔', '╩', '╦', '╠', '═', '╬', '╧',
+    '╨', '╤', '╥', '╙', '╘', '╒', '╓', '╫', '╪', '┘', '┌', '█', '▄', '▌', '▐', '▀',
+    'α', 'ß', 'Γ', 'π', 'Σ', 'σ', 'µ', 'τ', 'Φ', 'Θ', 'Ω', 'δ', '∞', 'φ', 'ε', '∩',
+    '≡', '±', '≥', '≤', '⌠', '⌡', '÷', '≈', '°', '∙', '·', '√', 'ⁿ', '²', '■', '\u{a0}',
+];
+
+const CP850: [char; 128] = [
+    'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å',
+    'É', 'æ', 'Æ', 'ô', 'ö', 'ò', 'û', 'ù', 'ÿ', 'Ö', 'Ü', 'ø', '£', 'Ø', '×', 'ƒ',
+    'á', 'í', 'ó', 'ú', 'ñ', 'Ñ', 'ª', 'º', '¿', '®', '¬', '½', '¼', '¡', '«', '»',
+    '░', '▒', '▓', '│', '┤', 'Á', 'Â', 'À', '©', '╣', '║', '╗', '╝', '¢', '¥', '┐',
+    '└', '┴', '┬', '├', '─', '┼', 'ã', 'Ã', '╚', '╔', '╩', '╦', '╠', '═', '╬', '¤',
+    'ð', 'Ð', 'Ê', 'Ë', 'È', 'ı', 'Í', 'Î', 'Ï', '┘', '┌', '█', '▄', '¦', 'Ì', '▀',
+    'Ó', 'ß', 'Ô', 'Ò', 'õ', 'Õ', 'µ', 'þ', 'Þ', 'Ú', 'Û', 'Ù', 'ý', 'Ý', '¯', '´',
+    '\u{ad}', '±', '‗', '¾', '¶', '§', '÷', '¸', '°', '¨', '·', '¹', '³', '²', '■', '\u{a0}',
+];
+
+const CP1252: [char; 128] = [
+    '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8d}', 'Ž', '\u{8f}',
+    '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9d}', 'ž', 'Ÿ',
+    '\u{a0}', '¡', '¢', '£', '¤', '¥', '¦', '§', '¨', '©', 'ª', '«', '¬', '\u{ad}', '®', '¯',
+    '°', '±', '²', '³', '´', 'µ', '¶', '·', '¸', '¹', 'º', '»', '¼', '½', '¾', '¿',
+    'À', 'Á', 'Â', 'Ã', 'Ä', 'Å', 'Æ', 'Ç', 'È', 'É', 'Ê', 'Ë', 'Ì', 'Í', 'Î', 'Ï',
+    'Ð', 'Ñ', 'Ò', 'Ó', 'Ô', 'Õ', 'Ö', '×', 'Ø', 'Ù', 'Ú', 'Û', 'Ü', 'Ý', 'Þ', 'ß',
+    'à', 'á', 'â', 'ã', 'ä', 'å', 'æ', 'ç', 'è', 'é', 'ê', 'ë', 'ì', 'í', 'î', 'ï',
+    'ð', 'ñ', 'ò', 'ó', 'ô', 'õ', 'ö', '÷', 'ø', 'ù', 'ú', 'û', 'ü', 'ý', 'þ', 'ÿ',
+];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip_and_transliterate() {
+        let names = "José Françoise Müller Ñúñez";
+        for cp in [Codepage::Cp437, Codepage::Cp850, Codepage::Cp1252] {
+            let encoded = cp.encode(names).unwrap();
+            assert_eq!(encoded.len(), names.chars().count());
+            assert_eq!(cp.decode(&encoded), names);
+        }
+
+        assert_eq!(Codepage::Cp437.encode("é").unwrap(), [0x82]);
+        assert_eq!(Codepage::Cp1252.encode("é").unwrap(), [0xe9]);
+        assert!(Codepage::Cp437.encode("Łukasz").is_err());
+
+        assert_eq!(Codepage::Cp437.transliterate("Łukasz Øberg"), "Lukasz Oberg");
+        assert_eq!(Codepage::Cp850.transliterate("Łukasz Øberg"), "Lukasz Øberg");
+        assert_eq!(Codepage::Cp437.transliterate("“Chuy” Peña"), "\"Chuy\" Peña");
+    }
+}
@@ -0,0 +1,310 @@
+//! dBASE (.DBT) and FoxPro (.FPT) memo file support.
+//!
+//! The layouts for these files are declared in the parent module.
+//! A memo file is a header followed by fixed-size blocks;
+//! each memo starts at the beginning of a block and may span several blocks.
+//! The DBF's `Memo` fields hold the number of a memo's first block, or spaces if there isn't one.
+//! Visual FoxPro tables may instead hold it as a 4 byte little-endian integer, with 0 if there isn't one.
+//!
+//! Text is stored in the table's codepage, the same as `Character` fields.
+//! dBASE's editor marks word-wrapped lines with a "soft" carriage return (`0x8d 0x0a`),
+//! which is removed when reading.
+
+use std::fmt::{Debug, Formatter};
+use std::io;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::xbase::codepage::Codepage;
+use crate::xbase::{
+    trim_data, dbase_memo_block, dbase_memo_header, foxpro_memo_block, foxpro_memo_header,
+    DBaseErrorKind, DBaseResult,
+};
+
+/// The header of every memo file format fits in this many bytes.
+const HEADER_SIZE: usize = 512;
+
+/// dBASE III memo files always use this block size.
+const DBASE_BLOCK_SIZE: usize = 512;
+
+/// FoxPro's default block size.
+const FOXPRO_BLOCK_SIZE: usize = 64;
+
+/// Memos larger than this are assumed to be corrupt (e.g., missing their terminator).
+const MAX_MEMO_SIZE: usize = 1 << 20;
+
+/// The flavor of a memo file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoFormat {
+    /// dBASE III or IV `.DBT` files.
+    DBase,
+    /// FoxPro `.FPT` files.
+    FoxPro,
+}
+
+impl MemoFormat {
+    /// The file extension for this format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            MemoFormat::DBase => "DBT",
+            MemoFormat::FoxPro => "FPT",
+        }
+    }
+
+    /// The DBF header flags for a table with this kind of memo file.
+    pub(crate) fn table_flags(&self) -> u8 {
+        match self {
+            MemoFormat::DBase => 0x83,
+            MemoFormat::FoxPro => 0xf5,
+        }
+    }
+}
+
+/// A source that's both readable and seekable, so memo readers can be boxed.
+pub trait ReadSeek: Read + Seek {}
+
+impl<T: Read + Seek> ReadSeek for T {}
+
+/// Find the memo file that goes with the DBF at the given path, if there is one.
+///
+/// It's expected to have the same name as the table, with a `.DBT` or `.FPT` extension.
+pub fn find_memo_file<P: AsRef<Path>>(dbf_path: P) -> Option<(PathBuf, MemoFormat)> {
+    [MemoFormat::DBase, MemoFormat::FoxPro].into_iter()
+        .flat_map(|format| {
+            let ext = format.extension();
+            [ext.to_string(), ext.to_ascii_lowercase()].map(|ext| (ext, format))
+        })
+        .map(|(ext, format)| (dbf_path.as_ref().with_extension(ext), format))
+        .find(|(path, _)| path.is_file())
+}
+
+/// Reads memos from a seekable source.
+pub struct MemoReader<R> {
+    inner: R,
+    format: MemoFormat,
+    block_size: usize,
+}
+
+impl<R> Debug for MemoReader<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MemoReader")
+            .field("format", &self.format)
+            .field("block_size", &self.block_size)
+            .finish()
+    }
+}
+
+impl<R: Read + Seek> MemoReader<R> {
+    /// Create a new MemoReader, reading the header from `inner`.
+    pub fn new(mut inner: R, format: MemoFormat) -> DBaseResult<Self> {
+        let mut data = [0; 24];
+        inner.seek(SeekFrom::Start(0))?;
+        inner.read_exact(&mut data)?;
+
+        let block_size = match format {
+            MemoFormat::DBase => {
+                let view = dbase_memo_header::View::new(&data[..]);
+                match view.block_size().read() as usize {
+                    // dBASE III doesn't set the block size.
+                    0 => DBASE_BLOCK_SIZE,
+                    _ if view.version().read() == 0x03 => DBASE_BLOCK_SIZE,
+                    n => n,
+                }
+            }
+            MemoFormat::FoxPro => {
+                let view = foxpro_memo_header::View::new(&data[..]);
+                view.block_size().read() as usize
+            }
+        };
+
+        if block_size == 0 {
+            return Err(DBaseErrorKind::InvalidMemo(0, "block size is 0".to_string()));
+        }
+
+        Ok(MemoReader { inner, format, block_size })
+    }
+
+    /// Read the raw bytes of the memo starting at the given block.
+    pub fn read(&mut self, block: u64) -> DBaseResult<Vec<u8>> {
+        if block == 0 {
+            return Err(DBaseErrorKind::InvalidMemo(block, "block 0 is the header".to_string()));
+        }
+
+        self.inner.seek(SeekFrom::Start(block * self.block_size as u64))?;
+        let mut prefix = [0; 8];
+        self.inner.read_exact(&mut prefix)?;
+
+        let length = match self.format {
+            MemoFormat::FoxPro => {
+                let view = foxpro_memo_block::View::new(&prefix[..]);
+                Some(view.length().read() as usize)
+            }
+            MemoFormat::DBase => {
+                let view = dbase_memo_block::View::new(&prefix[..]);
+                if view.signature() == &[0xff, 0xff, 0x08, 0x00] {
+                    Some((view.length().read() as usize).saturating_sub(prefix.len()))
+                } else {
+                    None
+                }
+            }
+        };
+
+        if let Some(length) = length {
+            if length > MAX_MEMO_SIZE {
+                return Err(DBaseErrorKind::InvalidMemo(block, format!("length {length} is too large")));
+            }
+            let mut data = vec![0; length];
+            self.inner.read_exact(&mut data)?;
+            return Ok(data);
+        }
+
+        // dBASE III memos don't have a length; they end at the first 0x1a.
+        let mut data = prefix.to_vec();
+        let mut chunk = vec![0; self.block_size];
+        loop {
+            if let Some(end) = memchr::memchr(0x1a, &data) {
+                data.truncate(end);
+                return Ok(data);
+            }
+            if data.len() > MAX_MEMO_SIZE {
+                return Err(DBaseErrorKind::InvalidMemo(block, "missing end of memo marker".to_string()));
+            }
+
+            let n = self.inner.read(&mut chunk)?;
+            if n == 0 {
+                return Ok(data);
+            }
+            data.extend_from_slice(&chunk[..n]);
+        }
+    }
+
+    /// Read the memo starting at the given block as text in the given codepage.
+    pub fn read_text(&mut self, block: u64, codepage: Codepage) -> DBaseResult<String> {
+        let data = self.read(block)?;
+
+        // Remove soft carriage returns.
+        let mut text = Vec::with_capacity(data.len());
+        let mut i = 0;
+        while i < data.len() {
+            if data[i] == 0x8d {
+                i += if data.get(i + 1) == Some(&0x0a) { 2 } else { 1 };
+                continue;
+            }
+            text.push(data[i]);
+            i += 1;
+        }
+
+        Ok(codepage.decode(trim_data(&text)))
+    }
+}
+
+/// Writes memos to a new memo file.
+///
+/// Memos are collected in memory, then written along with the header by `finish`.
+pub struct MemoWriter<W: io::Write> {
+    inner: W,
+    format: MemoFormat,
+    block_size: usize,
+    data: Vec<u8>,
+    next_block: u64,
+}
+
+impl<W: io::Write> MemoWriter<W> {
+    /// Create a new MemoWriter, which will write a memo file to `inner`.
+    pub fn new(inner: W, format: MemoFormat) -> Self {
+        let block_size = match format {
+            MemoFormat::DBase => DBASE_BLOCK_SIZE,
+            MemoFormat::FoxPro => FOXPRO_BLOCK_SIZE,
+        };
+
+        MemoWriter {
+            inner,
+            format,
+            block_size,
+            data: Vec::new(),
+            next_block: (HEADER_SIZE / block_size) as u64,
+        }
+    }
+
+    pub fn format(&self) -> MemoFormat {
+        self.format
+    }
+
+    /// Add a memo, encoded in the given codepage,
+    /// returning the block number to store in the table, or `None` if the text is empty.
+    pub fn add(&mut self, text: &str, codepage: Codepage) -> DBaseResult<Option<u64>> {
+        if text.is_empty() {
+            return Ok(None);
+        }
+        let text = codepage.encode(text)?;
+
+        let block = self.next_block;
+        match self.format {
+            MemoFormat::DBase => {
+                self.data.extend_from_slice(&text);
+                self.data.extend_from_slice(&[0x1a, 0x1a]);
+            }
+            MemoFormat::FoxPro => {
+                let mut prefix = [0; 8];
+                let mut view = foxpro_memo_block::View::new(&mut prefix[..]);
+                view.block_type_mut().write(1);
+                view.length_mut().write(text.len() as u32);
+                self.data.extend_from_slice(&prefix);
+                self.data.extend_from_slice(&text);
+            }
+        }
+
+        // Pad to the next block.
+        let padded = self.data.len().div_ceil(self.block_size) * self.block_size;
+        self.data.resize(padded, 0);
+        self.next_block = (HEADER_SIZE + self.data.len()) as u64 / self.block_size as u64;
+
+        Ok(Some(block))
+    }
+
+    /// Write the header and memos, then return the underlying writer.
+    pub fn finish(mut self) -> DBaseResult<W> {
+        let mut header = [0; HEADER_SIZE];
+        match self.format {
+            MemoFormat::DBase => {
+                let mut view = dbase_memo_header::View::new(&mut header[..]);
+                view.next_block_mut().write(self.next_block as u32);
+                view.version_mut().write(0x03);
+            }
+            MemoFormat::FoxPro => {
+                let mut view = foxpro_memo_header::View::new(&mut header[..]);
+                view.next_block_mut().write(self.next_block as u32);
+                view.block_size_mut().write(self.block_size as u16);
+            }
+        }
+
+        self.inner.write_all(&header)?;
+        self.inner.write_all(&self.data)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+    use super::*;
+
+    #[test]
+    fn write_then_read_memos() {
+        for format in [MemoFormat::DBase, MemoFormat::FoxPro] {
+            let long = "Please put me next to my partner. ".repeat(40);
+            let mut writer = MemoWriter::new(Cursor::new(Vec::new()), format);
+            let first = writer.add("Arriving Saturday morning.", Codepage::default()).unwrap().unwrap();
+            assert_eq!(writer.add("", Codepage::default()).unwrap(), None);
+            let second = writer.add(&long, Codepage::default()).unwrap().unwrap();
+            let third = writer.add("Last one", Codepage::default()).unwrap().unwrap();
+            assert!(first < second && second < third);
+
+            let mut reader = MemoReader::new(writer.finish().unwrap(), format).unwrap();
+            assert_eq!(reader.read_text(third, Codepage::default()).unwrap(), "Last one");
+            assert_eq!(reader.read_text(first, Codepage::default()).unwrap(), "Arriving Saturday morning.");
+            assert_eq!(reader.read_text(second, Codepage::default()).unwrap(), long.trim_end());
+        }
+    }
+}
@@ -0,0 +1,820 @@
+//! Clipper index (.NTX) support.
+//!
+//! The layouts for these files are declared in the parent module;
+//! see the notes there for a description of the file structure.
+//! In short, an index is a header page followed by a B-tree of 1024 byte pages,
+//! each holding a sorted list of `(left page, DBF record number, key)` entries
+//! and a final right-most page pointer.
+//! Page addresses are byte offsets from the start of the file, and `0` means "no page".
+//!
+//! Keys are stored as raw bytes, space-padded to the index's key size,
+//! and they're compared byte-wise, which matches Clipper's ordering for ASCII data.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::ops::Bound;
+use std::path::{Path, PathBuf};
+
+use crate::xbase::codepage::Codepage;
+use crate::xbase::{
+    clipper_index_entry, clipper_index_header, clipper_index_offset, clipper_index_page,
+    data_to_string, DBaseErrorKind, DBaseResult, Field, FieldDescriptor, FieldType, Header, TableReader,
+};
+
+/// Every page in a Clipper index, including the header, is this many bytes.
+pub const PAGE_SIZE: usize = 1024;
+
+/// Trees deeper than this are assumed to be corrupt (e.g., they have a cycle).
+/// Even with tiny pages, this is enough for far more records than a DBF can hold.
+const MAX_DEPTH: usize = 32;
+
+/// Clipper doesn't allow keys longer than this.
+const MAX_KEY_SIZE: usize = 250;
+
+/// Header information from a Clipper index.
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub struct NtxHeader {
+    pub signature: u8,
+    pub indexing_version: u8,
+    pub root_page: u32,
+    pub next_page: u32,
+    pub key_size: usize,
+    pub num_decimals: u16,
+    pub max_keys_per_page: u16,
+    pub half_page: u16,
+    /// The dBASE expression the index was built on, e.g. `IGRA_NUM`.
+    pub key_expression: String,
+    pub is_unique: bool,
+}
+
+/// A single key from the index and the (1-based) DBF record number it points to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexEntry {
+    pub key: Vec<u8>,
+    pub record_number: u32,
+}
+
+impl IndexEntry {
+    /// Return the key as a string with trailing spaces removed.
+    pub fn key_str(&self) -> DBaseResult<&str> {
+        data_to_string(&self.key)
+    }
+}
+
+/// A single page of the index, decoded.
+#[derive(Debug)]
+struct Page {
+    /// Pairs of `(left page address, entry)`.
+    entries: Vec<(u32, IndexEntry)>,
+    /// Address of the page holding keys larger than every key on this page.
+    right: u32,
+}
+
+impl Page {
+    /// Address of the child page to the left of entry `i`,
+    /// or the right-most child if `i` is past the last entry.
+    fn child(&self, i: usize) -> u32 {
+        self.entries.get(i).map_or(self.right, |(left, _)| *left)
+    }
+}
+
+/// Reads a Clipper index from a seekable source.
+///
+/// Pages are read on demand, so lookups only touch the pages along a single path through the tree.
+pub struct NtxIndex<R> {
+    inner: R,
+    header: NtxHeader,
+}
+
+/// Open the Clipper index at the given path.
+pub fn try_from_path<P: AsRef<Path>>(path: P) -> DBaseResult<NtxIndex<BufReader<File>>> {
+    NtxIndex::new(BufReader::new(File::open(path)?))
+}
+
+/// Find the `.NTX` files in the same directory as the given DBF.
+///
+/// The legacy app keeps its indexes next to its tables,
+/// so this simply lists every file in that directory with an `ntx` extension (ignoring case),
+/// sorted by name.
+pub fn find_indexes<P: AsRef<Path>>(dbf_path: P) -> io::Result<Vec<PathBuf>> {
+    let dir = match dbf_path.as_ref().parent() {
+        Some(d) if !d.as_os_str().is_empty() => d.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+
+    let mut found: Vec<_> = std::fs::read_dir(dir)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("ntx")))
+        .collect();
+    found.sort();
+    Ok(found)
+}
+
+/// Compare a key against a (possibly shorter) search value.
+///
+/// Only the first `target.len()` bytes of the key are considered,
+/// so a short target acts like a prefix, matching Clipper's `SEEK` behavior.
+fn cmp_prefix(key: &[u8], target: &[u8]) -> Ordering {
+    key[..key.len().min(target.len())].cmp(target)
+}
+
+impl<R> NtxIndex<R>
+    where R: Read + Seek
+{
+    /// Read the index header from the given reader.
+    pub fn new(mut reader: R) -> DBaseResult<Self> {
+        let mut data = [0u8; PAGE_SIZE];
+        reader.seek(SeekFrom::Start(0))?;
+        reader.read_exact(&mut data[..clipper_index_header::SIZE.unwrap()])?;
+
+        let view = clipper_index_header::View::new(&data[..]);
+        let key_size = view.key_size().read() as usize;
+        if key_size == 0 || key_size + 8 != view.key_size_plus_8().read() as usize {
+            return Err(DBaseErrorKind::InvalidIndexHeader(
+                format!("inconsistent key size: {key_size}")
+            ));
+        }
+
+        let header = NtxHeader {
+            signature: view.signature().read(),
+            indexing_version: view.indexing_version().read(),
+            root_page: view.root_page_addr().read(),
+            next_page: view.next_page_addr().read(),
+            key_size,
+            num_decimals: view.num_dec_in_key().read(),
+            max_keys_per_page: view.max_keys_per_page().read(),
+            half_page: view.half_page().read(),
+            key_expression: data_to_string(view.key_expression())?.to_string(),
+            is_unique: view.is_unique().read() != 0,
+        };
+
+        if !(header.root_page as usize).is_multiple_of(PAGE_SIZE) {
+            return Err(DBaseErrorKind::InvalidIndexHeader(
+                format!("root page address {:#x} is not page-aligned", header.root_page)
+            ));
+        }
+
+        Ok(NtxIndex { inner: reader, header })
+    }
+
+    pub fn header(&self) -> &NtxHeader {
+        &self.header
+    }
+
+    /// Read and decode the page at the given address.
+    fn read_page(&mut self, addr: u32) -> DBaseResult<Page> {
+        let key_size = self.header.key_size;
+        let entry_size = clipper_index_entry::SIZE.unwrap() + key_size;
+        let offset_size = clipper_index_offset::SIZE.unwrap();
+
+        let mut data = [0u8; PAGE_SIZE];
+        self.inner.seek(SeekFrom::Start(addr as u64))?;
+        self.inner.read_exact(&mut data)?;
+
+        let used = clipper_index_page::View::new(&data[..]).used_entries().read() as usize;
+        let offsets_start = clipper_index_page::SIZE.unwrap();
+        if offsets_start + (used + 1) * offset_size > PAGE_SIZE {
+            return Err(DBaseErrorKind::InvalidIndexPage(addr, format!("too many entries: {used}")));
+        }
+
+        let entry_at = |i: usize| -> DBaseResult<(u32, u32, &[u8])> {
+            let start = offsets_start + i * offset_size;
+            let offset = clipper_index_offset::View::new(&data[start..]).offset().read() as usize;
+            if offset < offsets_start || offset + entry_size > PAGE_SIZE {
+                return Err(DBaseErrorKind::InvalidIndexPage(addr, format!("bad entry offset: {offset}")));
+            }
+            let view = clipper_index_entry::View::new(&data[offset..]);
+            let key_start = offset + clipper_index_entry::SIZE.unwrap();
+            Ok((
+                view.next_page_address().read(),
+                view.record_number().read(),
+                &data[key_start..key_start + key_size],
+            ))
+        };
+
+        let mut entries = Vec::with_capacity(used);
+        for i in 0..used {
+            let (left, record_number, key) = entry_at(i)?;
+            entries.push((left, IndexEntry { key: key.to_vec(), record_number }));
+        }
+        let (right, _, _) = entry_at(used)?;
+
+        Ok(Page { entries, right })
+    }
+
+    /// Pad a search key with spaces to the index key size.
+    /// Longer keys are truncated.
+    fn pad_key(&self, key: &[u8]) -> Vec<u8> {
+        let mut key = key.to_vec();
+        key.resize(self.header.key_size, b' ');
+        key
+    }
+
+    /// Find the record number for an exact key.
+    ///
+    /// The key is space-padded to the key size before searching,
+    /// so `find(b"123")` in an index with 4 byte keys looks for `"123 "`.
+    /// If the index isn't unique, this returns the first matching record.
+    pub fn find(&mut self, key: &[u8]) -> DBaseResult<Option<u32>> {
+        let key = self.pad_key(key);
+        let mut iter = self.range(Bound::Included(&key), Bound::Included(&key))?;
+        iter.next().transpose().map(|e| e.map(|e| e.record_number))
+    }
+
+    /// Iterate over every entry whose key begins with the given prefix, in key order.
+    pub fn seek<'a>(&'a mut self, prefix: &'a [u8]) -> DBaseResult<NtxIter<'a, R>> {
+        self.range(Bound::Included(prefix), Bound::Included(prefix))
+    }
+
+    /// Iterate over all entries in key order.
+    pub fn iter(&mut self) -> DBaseResult<NtxIter<'_, R>> {
+        self.range(Bound::Unbounded, Bound::Unbounded)
+    }
+
+    /// Iterate over entries within the given bounds, in key order.
+    ///
+    /// Bounds are compared against the leading bytes of each key,
+    /// so a bound shorter than the key size acts like a prefix:
+    /// e.g., an `Included(b"SM")` upper bound includes `"SMITH"`.
+    pub fn range<'a>(&'a mut self, lower: Bound<&'a [u8]>, upper: Bound<&'a [u8]>)
+        -> DBaseResult<NtxIter<'a, R>>
+    {
+        let root = self.header.root_page;
+        let mut iter = NtxIter { index: self, stack: Vec::new(), upper, done: false };
+        iter.descend(root, lower)?;
+        Ok(iter)
+    }
+}
+
+/// An in-order iterator over a range of index entries.
+pub struct NtxIter<'a, R> {
+    index: &'a mut NtxIndex<R>,
+    /// Pages along the current path and the position of the next entry to yield on each.
+    stack: Vec<(Page, usize)>,
+    upper: Bound<&'a [u8]>,
+    done: bool,
+}
+
+impl<'a, R> NtxIter<'a, R>
+    where R: Read + Seek
+{
+    /// Walk down from `addr` to the first entry satisfying the `lower` bound,
+    /// pushing each page along the way.
+    fn descend(&mut self, mut addr: u32, lower: Bound<&[u8]>) -> DBaseResult<()> {
+        while addr != 0 {
+            if self.stack.len() >= MAX_DEPTH {
+                return Err(DBaseErrorKind::InvalidIndexPage(addr, "index is too deep".to_string()));
+            }
+
+            let page = self.index.read_page(addr)?;
+            let pos = match lower {
+                Bound::Unbounded => 0,
+                Bound::Included(k) => page.entries.partition_point(|(_, e)| cmp_prefix(&e.key, k).is_lt()),
+                Bound::Excluded(k) => page.entries.partition_point(|(_, e)| cmp_prefix(&e.key, k).is_le()),
+            };
+            addr = page.child(pos);
+            self.stack.push((page, pos));
+        }
+        Ok(())
+    }
+
+    fn past_upper(&self, key: &[u8]) -> bool {
+        match self.upper {
+            Bound::Unbounded => false,
+            Bound::Included(k) => cmp_prefix(key, k).is_gt(),
+            Bound::Excluded(k) => cmp_prefix(key, k).is_ge(),
+        }
+    }
+}
+
+impl<'a, R> Iterator for NtxIter<'a, R>
+    where R: Read + Seek
+{
+    type Item = DBaseResult<IndexEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        loop {
+            let (page, pos) = self.stack.last_mut()?;
+            if *pos >= page.entries.len() {
+                self.stack.pop();
+                continue;
+            }
+
+            let entry = page.entries[*pos].1.clone();
+            *pos += 1;
+            let next_child = page.child(*pos);
+
+            if self.past_upper(&entry.key) {
+                self.done = true;
+                return None;
+            }
+
+            // Everything in the child to the right of this entry comes next.
+            if let Err(err) = self.descend(next_child, Bound::Unbounded) {
+                self.done = true;
+                return Some(Err(err));
+            }
+            return Some(Ok(entry));
+        }
+    }
+}
+
+/// A (very) small subset of the dBASE expression language, used to build index keys.
+///
+/// Clipper indexes are built on arbitrary expressions, but in practice they're almost always
+/// one or more fields concatenated with `+`, optionally wrapped in `UPPER()` or `DTOS()`.
+/// Those are the only forms supported here; anything else is an `UnsupportedKeyExpression`.
+#[derive(Debug, Clone)]
+pub struct KeyExpression {
+    source: String,
+    terms: Vec<KeyTerm>,
+}
+
+#[derive(Debug, Clone)]
+struct KeyTerm {
+    field: String,
+    upper: bool,
+}
+
+impl KeyExpression {
+    pub fn parse(expr: &str) -> DBaseResult<Self> {
+        let unsupported = || DBaseErrorKind::UnsupportedKeyExpression(expr.to_string());
+
+        let terms = expr.split('+').map(|term| {
+            let term = term.trim().to_ascii_uppercase();
+            let (func, field) = match term.split_once('(') {
+                Some((func, rest)) => (
+                    Some(func.trim().to_string()),
+                    rest.strip_suffix(')').ok_or_else(unsupported)?.trim().to_string(),
+                ),
+                None => (None, term),
+            };
+
+            if field.is_empty() || !field.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(unsupported());
+            }
+
+            match func.as_deref() {
+                None | Some("DTOS") => Ok(KeyTerm { field, upper: false }),
+                Some("UPPER") => Ok(KeyTerm { field, upper: true }),
+                Some(_) => Err(unsupported()),
+            }
+        }).collect::<DBaseResult<Vec<_>>>()?;
+
+        Ok(KeyExpression { source: expr.trim().to_string(), terms })
+    }
+
+    /// The expression as it was given, e.g. for writing to an index header.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Find the position of each term's field in the table's field list.
+    fn positions(&self, fields: &[FieldDescriptor]) -> DBaseResult<Vec<usize>> {
+        self.terms.iter().map(|t| {
+            fields.iter().position(|f| f.name.eq_ignore_ascii_case(&t.field))
+                .ok_or_else(|| DBaseErrorKind::UnknownKeyField(t.field.clone()))
+        }).collect()
+    }
+
+    /// The length of keys produced by this expression for a table with the given fields.
+    pub fn key_size(&self, fields: &[FieldDescriptor]) -> DBaseResult<usize> {
+        Ok(self.positions(fields)?.into_iter().map(|i| fields[i].length).sum())
+    }
+
+    /// Build the key for a record.
+    ///
+    /// `values` must be in the same order as `fields`, as they are when read from or written to a table.
+    /// Each field contributes the same bytes it would occupy in the DBF record:
+    /// `Character` fields are space-padded, `Numeric` fields are right-aligned,
+    /// and `Date` fields are `YYYYMMDD` (the same as `DTOS`).
+    pub fn evaluate(&self, fields: &[FieldDescriptor], values: &[Field], codepage: Codepage) -> DBaseResult<Vec<u8>> {
+        let mut key = Vec::new();
+        for (term, i) in self.terms.iter().zip(self.positions(fields)?) {
+            let f = &fields[i];
+            if matches!(f.field_type, FieldType::Memo) {
+                return Err(DBaseErrorKind::UnsupportedKeyExpression(f.name.clone()));
+            }
+            let value = values.get(i).ok_or_else(|| DBaseErrorKind::UnknownKeyField(f.name.clone()))?;
+
+            let start = key.len();
+            f.write_field(value, codepage, &mut key)?;
+            if term.upper {
+                key[start..].make_ascii_uppercase();
+            }
+        }
+        Ok(key)
+    }
+}
+
+/// Writes a Clipper index.
+///
+/// Keys may be added in any order; they're sorted when the index is written.
+/// Rather than inserting keys one at a time like Clipper does,
+/// this builds the tree bottom-up with every page at least half full and all leaves at the same depth,
+/// so the result is a valid starting point for the legacy app to update.
+pub struct NtxWriter<W> {
+    inner: W,
+    expr: KeyExpression,
+    key_size: usize,
+    is_unique: bool,
+    codepage: Codepage,
+    entries: Vec<IndexEntry>,
+}
+
+impl<W> NtxWriter<W>
+    where W: io::Write
+{
+    /// Create a writer for an index on `expr` over a table with the given fields and codepage.
+    pub fn new(writer: W, expr: KeyExpression, is_unique: bool, fields: &[FieldDescriptor], codepage: Codepage)
+        -> DBaseResult<Self>
+    {
+        let key_size = expr.key_size(fields)?;
+        if key_size == 0 || key_size > MAX_KEY_SIZE {
+            return Err(DBaseErrorKind::UnsupportedKeyExpression(
+                format!("{} (key size {key_size})", expr.as_str())
+            ));
+        }
+
+        Ok(NtxWriter { inner: writer, expr, key_size, is_unique, codepage, entries: Vec::new() })
+    }
+
+    /// Add the key for a record, built from its field values.
+    pub fn add_record(&mut self, fields: &[FieldDescriptor], values: &[Field], record_number: u32) -> DBaseResult<()> {
+        let key = self.expr.evaluate(fields, values, self.codepage)?;
+        self.entries.push(IndexEntry { key, record_number });
+        Ok(())
+    }
+
+    /// Sort the keys, build the tree, and write it out.
+    ///
+    /// For unique indexes, only the first record (by record number) with a given key is kept,
+    /// matching what Clipper does when it builds a unique index.
+    pub fn finish(mut self) -> DBaseResult<W> {
+        let key_size = self.key_size;
+        let max_keys = max_keys_per_page(key_size);
+
+        for e in &mut self.entries {
+            e.key.resize(key_size, b' ');
+        }
+        self.entries.sort_by(|a, b| a.key.cmp(&b.key).then(a.record_number.cmp(&b.record_number)));
+        if self.is_unique {
+            self.entries.dedup_by(|a, b| a.key == b.key);
+        }
+
+        // Find the smallest height that can hold every entry.
+        let mut height = 1;
+        while capacity(max_keys, height) < self.entries.len() {
+            height += 1;
+        }
+
+        // Page 0 is the header, so tree pages start at the next page.
+        let mut pages = Vec::new();
+        let root_page = build_page(&self.entries, height, max_keys, key_size, &mut pages);
+
+        let mut data = [0u8; PAGE_SIZE];
+        {
+            let mut view = clipper_index_header::View::new(&mut data[..]);
+            view.signature_mut().write(6);
+            view.indexing_version_mut().write(1);
+            view.root_page_addr_mut().write(root_page);
+            view.next_page_addr_mut().write(0);
+            view.key_size_plus_8_mut().write(key_size as u16 + 8);
+            view.key_size_mut().write(key_size as u16);
+            view.num_dec_in_key_mut().write(0);
+            view.max_keys_per_page_mut().write(max_keys as u16);
+            view.half_page_mut().write((max_keys / 2) as u16);
+            let expr = self.expr.as_str().as_bytes();
+            let expr_len = expr.len().min(255);
+            view.key_expression_mut()[..expr_len].copy_from_slice(&expr[..expr_len]);
+            view.is_unique_mut().write(self.is_unique as u8);
+        }
+
+        self.inner.write_all(&data)?;
+        for page in &pages {
+            self.inner.write_all(page)?;
+        }
+        self.inner.flush()?;
+
+        Ok(self.inner)
+    }
+}
+
+/// The number of keys that fit on a page, along with their offsets and the extra right-most entry.
+/// Clipper keeps this even, so that a full page splits into two equal halves.
+fn max_keys_per_page(key_size: usize) -> usize {
+    let slot_size = clipper_index_offset::SIZE.unwrap() + clipper_index_entry::SIZE.unwrap() + key_size;
+    let n = (PAGE_SIZE - clipper_index_page::SIZE.unwrap()) / slot_size - 1;
+    n - (n % 2)
+}
+
+/// The most entries a tree of the given height can hold.
+fn capacity(max_keys: usize, height: u32) -> usize {
+    (max_keys + 1).saturating_pow(height) - 1
+}
+
+/// Recursively build pages for the sorted `entries`, appending them to `pages`,
+/// and return the address of the page at the top of this subtree.
+///
+/// The entries are spread evenly across as few children as will hold them,
+/// with one entry between each pair of children moved up to this page.
+fn build_page(entries: &[IndexEntry], height: u32, max_keys: usize, key_size: usize, pages: &mut Vec<Vec<u8>>) -> u32 {
+    let (children, separators) = if height <= 1 {
+        (vec![0; entries.len() + 1], entries.iter().collect::<Vec<_>>())
+    } else {
+        let child_capacity = capacity(max_keys, height - 1) + 1;
+        let n_children = (entries.len() + 1).div_ceil(child_capacity).max(1);
+        let n_child_entries = entries.len() + 1 - n_children;
+
+        let mut children = Vec::with_capacity(n_children);
+        let mut separators = Vec::with_capacity(n_children - 1);
+        let mut start = 0;
+        for i in 0..n_children {
+            // Distribute the remainder one at a time to the first few children.
+            let len = n_child_entries / n_children + usize::from(i < n_child_entries % n_children);
+            children.push(build_page(&entries[start..start + len], height - 1, max_keys, key_size, pages));
+            start += len;
+            if i + 1 < n_children {
+                separators.push(&entries[start]);
+                start += 1;
+            }
+        }
+        (children, separators)
+    };
+
+    let slot_size = clipper_index_entry::SIZE.unwrap() + key_size;
+    let offsets_start = clipper_index_page::SIZE.unwrap();
+    let items_start = offsets_start + (max_keys + 1) * clipper_index_offset::SIZE.unwrap();
+
+    let mut data = vec![0u8; PAGE_SIZE];
+    clipper_index_page::View::new(&mut data[..]).used_entries_mut().write(separators.len() as u16);
+
+    // Like Clipper, every slot gets an offset, even if it isn't used.
+    for slot in 0..=max_keys {
+        let offset = items_start + slot * slot_size;
+        let start = offsets_start + slot * clipper_index_offset::SIZE.unwrap();
+        clipper_index_offset::View::new(&mut data[start..]).offset_mut().write(offset as u16);
+
+        let (left, entry) = match (children.get(slot), separators.get(slot)) {
+            (Some(left), entry) => (*left, entry),
+            (None, _) => break,
+        };
+        let mut view = clipper_index_entry::View::new(&mut data[offset..]);
+        view.next_page_address_mut().write(left);
+        if let Some(entry) = entry {
+            view.record_number_mut().write(entry.record_number);
+            let key_start = offset + clipper_index_entry::SIZE.unwrap();
+            data[key_start..key_start + key_size].copy_from_slice(&entry.key);
+        }
+    }
+
+    pages.push(data);
+    (pages.len() * PAGE_SIZE) as u32
+}
+
+/// Build a Clipper index for an existing table and write it to `writer`.
+pub fn build_index<R, W>(table: TableReader<Header<R>>, expr: KeyExpression, is_unique: bool, writer: W)
+    -> DBaseResult<W>
+    where
+        R: Read,
+        W: io::Write,
+{
+    let fields = table.fields().to_vec();
+    let mut ntx = NtxWriter::new(writer, expr, is_unique, &fields, table.codepage())?;
+
+    let mut records = table.records();
+    while let Some(record) = records.next() {
+        let record = record?;
+        let record_number = record.record_number() as u32;
+        let values = record.map(|f| f.map(|f| f.value)).collect::<DBaseResult<Vec<_>>>()?;
+        ntx.add_record(&fields, &values, record_number)?;
+    }
+
+    ntx.finish()
+}
+
+/// The conventional path for an index on `dbf_path`: the same path with an `.NTX` extension.
+pub fn index_path_for<P: AsRef<Path>>(dbf_path: P) -> PathBuf {
+    dbf_path.as_ref().with_extension("NTX")
+}
+
+/// A disagreement between an index and the table it's supposed to describe.
+#[derive(Debug)]
+pub enum IndexProblem {
+    /// The index key doesn't match the key built from the record it points to.
+    KeyMismatch { record_number: u32, index_key: Vec<u8>, table_key: Vec<u8> },
+    /// The index points to a record that doesn't exist or is deleted.
+    MissingRecord { record_number: u32, index_key: Vec<u8> },
+    /// The table has a record that isn't in the index.
+    NotIndexed { record_number: u32, table_key: Vec<u8> },
+    /// The index keys aren't in sorted order at this entry.
+    OutOfOrder { record_number: u32, index_key: Vec<u8> },
+    /// The index is marked unique, but this key appears more than once.
+    Duplicate { record_number: u32, index_key: Vec<u8> },
+}
+
+impl Display for IndexProblem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = |k: &[u8]| String::from_utf8_lossy(k).into_owned();
+        match self {
+            IndexProblem::KeyMismatch { record_number, index_key, table_key } => {
+                write!(f, "record {record_number}: index key '{}' but table key '{}'", s(index_key), s(table_key))
+            }
+            IndexProblem::MissingRecord { record_number, index_key } => {
+                write!(f, "record {record_number}: indexed as '{}' but missing or deleted", s(index_key))
+            }
+            IndexProblem::NotIndexed { record_number, table_key } => {
+                write!(f, "record {record_number}: key '{}' is not in the index", s(table_key))
+            }
+            IndexProblem::OutOfOrder { record_number, index_key } => {
+                write!(f, "record {record_number}: index key '{}' is out of order", s(index_key))
+            }
+            IndexProblem::Duplicate { record_number, index_key } => {
+                write!(f, "record {record_number}: duplicate key '{}' in a unique index", s(index_key))
+            }
+        }
+    }
+}
+
+/// Compare an index against a table, returning every disagreement found.
+///
+/// This rebuilds each record's key from the index's key expression,
+/// then walks the index in order, checking that each entry points to a live record with that key.
+/// An empty result means the legacy app should see the same rows through the index as in the table.
+pub fn check_index<R, I>(table: TableReader<Header<R>>, index: &mut NtxIndex<I>)
+    -> DBaseResult<Vec<IndexProblem>>
+    where
+        R: Read,
+        I: Read + Seek,
+{
+    let expr = KeyExpression::parse(&index.header().key_expression)?;
+    let fields = table.fields().to_vec();
+    let codepage = table.codepage();
+    let is_unique = index.header().is_unique;
+
+    let mut table_keys = BTreeMap::new();
+    let mut records = table.records();
+    while let Some(record) = records.next() {
+        let record = record?;
+        let record_number = record.record_number() as u32;
+        let values = record.map(|f| f.map(|f| f.value)).collect::<DBaseResult<Vec<_>>>()?;
+        table_keys.insert(record_number, expr.evaluate(&fields, &values, codepage)?);
+    }
+
+    let mut problems = Vec::new();
+    let mut prev: Option<Vec<u8>> = None;
+    for entry in index.iter()? {
+        let IndexEntry { key, record_number } = entry?;
+
+        match prev.as_deref().map(|p| p.cmp(&key)) {
+            Some(Ordering::Greater) => {
+                problems.push(IndexProblem::OutOfOrder { record_number, index_key: key.clone() });
+            }
+            Some(Ordering::Equal) if is_unique => {
+                problems.push(IndexProblem::Duplicate { record_number, index_key: key.clone() });
+            }
+            _ => {}
+        }
+
+        match table_keys.remove(&record_number) {
+            None => problems.push(IndexProblem::MissingRecord { record_number, index_key: key.clone() }),
+            Some(table_key) if table_key != key => {
+                problems.push(IndexProblem::KeyMismatch { record_number, index_key: key.clone(), table_key });
+            }
+            Some(_) => {}
+        }
+
+        prev = Some(key);
+    }
+
+    problems.extend(table_keys.into_iter()
+        .map(|(record_number, table_key)| IndexProblem::NotIndexed { record_number, table_key }));
+
+    Ok(problems)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+    use std::ops::Bound;
+
+    use super::{Codepage, KeyExpression, NtxIndex, NtxWriter, PAGE_SIZE};
+    use crate::xbase::{Field, FieldDescriptor, FieldType};
+
+    /// Build a page holding `entries` of `(left page, record number, key)` and a right-most child.
+    fn page(entries: &[(u32, u32, &[u8])], right: u32, key_size: usize) -> Vec<u8> {
+        let mut data = vec![0u8; PAGE_SIZE];
+        let n = entries.len();
+        data[0..2].copy_from_slice(&(n as u16).to_le_bytes());
+
+        let first = 2 + 2 * (n + 1);
+        let item_size = 8 + key_size;
+        for i in 0..=n {
+            let offset = first + i * item_size;
+            data[2 + 2 * i..4 + 2 * i].copy_from_slice(&(offset as u16).to_le_bytes());
+            let (left, rec, key): (u32, u32, &[u8]) = entries.get(i).copied().unwrap_or((right, 0, b""));
+            data[offset..offset + 4].copy_from_slice(&left.to_le_bytes());
+            data[offset + 4..offset + 8].copy_from_slice(&rec.to_le_bytes());
+            data[offset + 8..offset + 8 + key.len()].copy_from_slice(key);
+        }
+        data
+    }
+
+    fn two_level_index() -> Vec<u8> {
+        let key_size = 4;
+        let mut data = vec![0u8; PAGE_SIZE];
+        data[4..8].copy_from_slice(&(PAGE_SIZE as u32).to_le_bytes());
+        data[12..14].copy_from_slice(&(key_size as u16 + 8).to_le_bytes());
+        data[14..16].copy_from_slice(&(key_size as u16).to_le_bytes());
+        data[22..30].copy_from_slice(b"IGRA_NUM");
+
+        // root: [child A] 1003 [child B] 1006 [child C]
+        let (a, b, c) = (2 * PAGE_SIZE as u32, 3 * PAGE_SIZE as u32, 4 * PAGE_SIZE as u32);
+        data.extend(page(&[(a, 3, b"1003"), (b, 6, b"1006")], c, key_size));
+        data.extend(page(&[(0, 1, b"1001"), (0, 2, b"1002")], 0, key_size));
+        data.extend(page(&[(0, 4, b"1004"), (0, 5, b"1005")], 0, key_size));
+        data.extend(page(&[(0, 7, b"1007"), (0, 8, b"2000")], 0, key_size));
+        data
+    }
+
+    #[test]
+    fn read_two_level_index() {
+        let mut index = NtxIndex::new(Cursor::new(two_level_index())).unwrap();
+        assert_eq!(index.header().key_expression, "IGRA_NUM");
+        assert_eq!(index.header().key_size, 4);
+
+        let all: Vec<_> = index.iter().unwrap().map(|e| e.unwrap().record_number).collect();
+        assert_eq!(all, (1..=8).collect::<Vec<_>>());
+
+        assert_eq!(index.find(b"1005").unwrap(), Some(5));
+        assert_eq!(index.find(b"1006").unwrap(), Some(6));
+        assert_eq!(index.find(b"1010").unwrap(), None);
+
+        let range: Vec<_> = index.range(Bound::Excluded(b"1002"), Bound::Included(b"1006")).unwrap()
+            .map(|e| e.unwrap().record_number).collect();
+        assert_eq!(range, vec![3, 4, 5, 6]);
+
+        let prefix: Vec<_> = index.seek(b"100").unwrap().map(|e| e.unwrap().record_number).collect();
+        assert_eq!(prefix, (1..=7).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn write_then_read_index() {
+        let fields = vec![
+            FieldDescriptor {
+                name: "LAST_NAME".to_string(),
+                field_type: FieldType::Character,
+                length: 30,
+                decimal_count: 0,
+                work_area_id: 0,
+                example: 1,
+            },
+        ];
+
+        // Enough keys to need three levels of 30-byte keys, inserted in reverse.
+        let n = 1000;
+        let expr = KeyExpression::parse("UPPER(LAST_NAME)").unwrap();
+        let mut writer = NtxWriter::new(Vec::new(), expr, false, &fields, Codepage::default()).unwrap();
+        for i in (1..=n).rev() {
+            writer.add_record(&fields, &[Field::Character(format!("name{i:05}"))], i).unwrap();
+        }
+        let data = writer.finish().unwrap();
+        assert_eq!(data.len() % PAGE_SIZE, 0);
+
+        let mut index = NtxIndex::new(Cursor::new(data)).unwrap();
+        assert_eq!(index.header().key_size, 30);
+        assert_eq!(index.header().key_expression, "UPPER(LAST_NAME)");
+
+        let all: Vec<_> = index.iter().unwrap().map(|e| e.unwrap().record_number).collect();
+        assert_eq!(all, (1..=n).collect::<Vec<_>>());
+        assert_eq!(index.find(b"NAME00500").unwrap(), Some(500));
+        assert_eq!(index.find(b"name00500").unwrap(), None);
+    }
+
+    #[test]
+    fn long_keys_are_rejected() {
+        let field = |name: &str| FieldDescriptor {
+            name: name.to_string(),
+            field_type: FieldType::Character,
+            length: 254,
+            decimal_count: 0,
+            work_area_id: 0,
+            example: 1,
+        };
+        let fields = vec![field("NOTES1"), field("NOTES2"), field("NOTES3"), field("NOTES4"), field("NOTES5")];
+
+        for expr in ["NOTES1", "NOTES1+NOTES2+NOTES3+NOTES4+NOTES5"] {
+            let expr = KeyExpression::parse(expr).unwrap();
+            assert!(NtxWriter::new(Vec::new(), expr, false, &fields, Codepage::default()).is_err());
+        }
+    }
+}
@@ -0,0 +1,620 @@
+//! Records from tables that don't have a Rust struct.
+//!
+//! A `Record` holds each of a record's fields by name, in column order,
+//! so any table can be read, inspected, and exported to JSON Lines or CSV.
+//! Going the other way, a `Schema` describes a table's fields,
+//! so rows from a CSV or JSON Lines file can be imported into a new table.
+
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::io::BufRead;
+use std::path::Path;
+
+use serde::ser::{SerializeMap, Serializer};
+use serde::{Deserialize, Serialize};
+
+use crate::xbase::codepage::Codepage;
+use crate::xbase::{
+    transliterate_fields, DBaseErrorKind, DBaseRecord, DBaseResult, Field, FieldDescriptor, FieldIterator,
+    FieldType, FromRecord, Header, NullFlagLayout, TableReader, TableWriter,
+};
+
+/// Name of the column added to exports that include deleted records.
+pub const DELETED_COLUMN: &str = "_DELETED";
+
+/// A record's fields, by name, in column order.
+#[derive(Debug, Clone, Default)]
+pub struct Record {
+    fields: Vec<(String, Field)>,
+}
+
+impl Record {
+    /// Get the value of the named field.
+    pub fn get(&self, name: &str) -> Option<&Field> {
+        self.fields.iter().find(|(n, _)| n == name).map(|(_, v)| v)
+    }
+
+    /// Set the value of the named field, returning its previous value.
+    ///
+    /// New fields are added after the existing ones.
+    pub fn insert(&mut self, name: &str, value: Field) -> Option<Field> {
+        match self.fields.iter_mut().find(|(n, _)| n == name) {
+            Some((_, v)) => Some(std::mem::replace(v, value)),
+            None => {
+                self.fields.push((name.to_string(), value));
+                None
+            }
+        }
+    }
+
+    /// Iterate over the fields' names and values, in column order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Field)> {
+        self.fields.iter().map(|(n, v)| (n.as_str(), v))
+    }
+
+    /// Return a record with only the named fields, in the given order.
+    pub fn select<S: AsRef<str>>(&self, names: &[S]) -> DBaseResult<Record> {
+        let fields = names.iter()
+            .map(|name| {
+                let name = name.as_ref();
+                self.get(name)
+                    .map(|v| (name.to_string(), v.clone()))
+                    .ok_or_else(|| DBaseErrorKind::UnknownField(name.to_string()))
+            })
+            .collect::<DBaseResult<_>>()?;
+        Ok(Record { fields })
+    }
+}
+
+impl FromRecord for Record {
+    fn from_record(fields: FieldIterator) -> DBaseResult<Self> {
+        let fields = fields
+            .map(|f| f.map(|f| (f.name.to_string(), f.value)))
+            .collect::<DBaseResult<_>>()?;
+        Ok(Record { fields })
+    }
+}
+
+/// Serializes as a map, keeping the column order.
+impl Serialize for Record {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.fields.len()))?;
+        for (name, value) in &self.fields {
+            map.serialize_entry(name, value)?;
+        }
+        map.end()
+    }
+}
+
+/// Serializes floats, integers, and logicals as JSON-style numbers and booleans,
+/// empty values as null, and everything else as its `Display` string,
+/// so `Numeric` and `Currency` values keep their decimal places, like `"12.50"`.
+impl Serialize for Field {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Field::Float(n) | Field::Double(n) => serializer.serialize_f64(*n),
+            Field::Numeric(Some(n)) | Field::Currency(n) => n.serialize(serializer),
+            Field::Integer(i) => serializer.serialize_i32(*i),
+            Field::Memo(Some(block)) => serializer.serialize_u64(*block),
+            Field::Boolean(Some(b)) => serializer.serialize_bool(*b),
+            Field::Boolean(None) | Field::Date(None) | Field::Memo(None) | Field::Numeric(None)
+                | Field::DateTime(None) | Field::Null => serializer.serialize_none(),
+            _ => serializer.collect_str(self),
+        }
+    }
+}
+
+/// The formats tables can be exported to and imported from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowFormat {
+    /// One JSON object per line.
+    JsonLines,
+    /// Comma-separated values with a header row, using the format `FieldDescriptor::parse_str` accepts.
+    Csv,
+}
+
+impl RowFormat {
+    /// Guess the format of a file from its extension: CSV for `.csv`, and JSON Lines otherwise.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> RowFormat {
+        match path.as_ref().extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("csv") => RowFormat::Csv,
+            _ => RowFormat::JsonLines,
+        }
+    }
+}
+
+impl std::str::FromStr for RowFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "jsonl" | "json" => Ok(RowFormat::JsonLines),
+            "csv" => Ok(RowFormat::Csv),
+            _ => Err(format!("unknown row format '{s}'; expected jsonl or csv")),
+        }
+    }
+}
+
+/// Write a table's records to `w`, returning the number of records written.
+///
+/// `columns` selects and orders the fields to write; by default, every field except `_NullFlags`.
+/// When `include_deleted` is set, records marked deleted are written too,
+/// with an extra `_DELETED` column telling them apart.
+/// `Memo` fields are written as their text if the table was opened with its memo file,
+/// as `try_from_path` does, and as block numbers otherwise.
+pub fn export<R, W>(
+    table: TableReader<Header<R>>,
+    format: RowFormat,
+    columns: Option<&[String]>,
+    include_deleted: bool,
+    w: W,
+) -> DBaseResult<usize>
+    where R: io::Read,
+          W: io::Write,
+{
+    let mut columns = match columns {
+        Some(columns) => columns.to_vec(),
+        None => table.fields().iter()
+            .filter(|f| !matches!(f.field_type, FieldType::NullFlags))
+            .map(|f| f.name.clone())
+            .collect(),
+    };
+    if let Some(name) = columns.iter().find(|&c| !table.fields().iter().any(|f| &f.name == c)) {
+        return Err(DBaseErrorKind::UnknownField(name.clone()));
+    }
+    if include_deleted {
+        columns.push(DELETED_COLUMN.to_string());
+    }
+    if table.memo.is_none() && table.fields().iter().any(|f| matches!(f.field_type, FieldType::Memo)) {
+        log::warn!("No memo file was found for this table, so its Memo fields are exported as block numbers");
+    }
+
+    let mut records = table.records();
+    if include_deleted {
+        records = records.include_deleted();
+    }
+
+    enum Output<W: io::Write> {
+        Csv(Box<csv::Writer<W>>),
+        JsonLines(io::BufWriter<W>),
+    }
+    let mut out = match format {
+        RowFormat::Csv => {
+            let mut csv = csv::Writer::from_writer(w);
+            csv.write_record(&columns).map_err(io::Error::from)?;
+            Output::Csv(Box::new(csv))
+        }
+        RowFormat::JsonLines => Output::JsonLines(io::BufWriter::new(w)),
+    };
+
+    let mut n = 0;
+    while let Some(fields) = records.next() {
+        let fields = fields?;
+        let is_deleted = fields.is_deleted();
+        let mut record = Record::from_record(fields)?;
+        if include_deleted {
+            record.insert(DELETED_COLUMN, Field::Boolean(Some(is_deleted)));
+        }
+        let record = record.select(&columns)?;
+
+        match &mut out {
+            Output::Csv(csv) => {
+                csv.write_record(record.iter().map(|(_, v)| v.to_string())).map_err(io::Error::from)?;
+            }
+            Output::JsonLines(w) => {
+                serde_json::to_writer(&mut *w, &record).map_err(io::Error::from)?;
+                io::Write::write_all(w, b"\n")?;
+            }
+        }
+        n += 1;
+    }
+
+    match &mut out {
+        Output::Csv(csv) => csv.flush()?,
+        Output::JsonLines(w) => io::Write::flush(w)?,
+    }
+    Ok(n)
+}
+
+/// The layout of a table to import into.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Schema {
+    pub fields: Vec<FieldDescriptor>,
+    /// The codepage to encode text in.
+    #[serde(skip)]
+    pub codepage: Codepage,
+}
+
+impl Schema {
+    /// Read a schema from a file.
+    ///
+    /// DBF files (by extension) supply their field descriptors and codepage.
+    /// Otherwise, the file must be TOML (with a `.toml` extension) or JSON
+    /// with a `fields` list of field descriptors.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> DBaseResult<Schema> {
+        let path = path.as_ref();
+        let ext = path.extension().map(|e| e.to_string_lossy().to_ascii_lowercase());
+        let invalid = |e: String| DBaseErrorKind::InvalidSchema(format!("{}: {e}", path.display()));
+
+        let schema: Schema = match ext.as_deref() {
+            Some("dbf") => {
+                let table = crate::xbase::try_from_path(path)?;
+                Schema { fields: table.fields().to_vec(), codepage: table.codepage() }
+            }
+            Some("toml") => toml::from_str(&fs::read_to_string(path)?).map_err(|e| invalid(e.to_string()))?,
+            _ => serde_json::from_str(&fs::read_to_string(path)?).map_err(|e| invalid(e.to_string()))?,
+        };
+        for f in &schema.fields {
+            f.check_decimal_count()?;
+        }
+        Ok(schema)
+    }
+}
+
+/// How a table's columns differ from an expected layout.
+#[derive(Debug, Default)]
+pub struct SchemaCheck {
+    /// Expected columns the table doesn't have.
+    pub missing: Vec<FieldDescriptor>,
+    /// Columns the table has that aren't expected.
+    pub extra: Vec<FieldDescriptor>,
+    /// Columns with the expected name but a different type, length, or decimal count,
+    /// as (expected, actual).
+    pub changed: Vec<(FieldDescriptor, FieldDescriptor)>,
+}
+
+impl SchemaCheck {
+    /// Compare a table's `fields` against the `expected` ones, ignoring column order.
+    ///
+    /// The `optional` columns aren't reported if they're missing or extra,
+    /// but are still checked if they're present in both.
+    pub fn new(expected: &[FieldDescriptor], optional: &[&str], fields: &[FieldDescriptor]) -> SchemaCheck {
+        let mut check = SchemaCheck::default();
+        for e in expected {
+            match fields.iter().find(|f| f.name == e.name) {
+                None if !optional.contains(&e.name.as_str()) => check.missing.push(e.clone()),
+                Some(f) if f.field_type != e.field_type || f.length != e.length || f.decimal_count != e.decimal_count => {
+                    check.changed.push((e.clone(), f.clone()));
+                }
+                _ => {}
+            }
+        }
+        check.extra = fields.iter()
+            .filter(|f| !optional.contains(&f.name.as_str()) && !expected.iter().any(|e| e.name == f.name))
+            .cloned()
+            .collect();
+        check
+    }
+
+    /// Whether the table matches the layout.
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Describes a column as its name, type, and size, like `NAME Numeric(7,2)`.
+fn describe_column(d: &FieldDescriptor) -> String {
+    format!("{} {:?}({},{})", d.name, d.field_type, d.length, d.decimal_count)
+}
+
+impl Display for SchemaCheck {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_ok() {
+            return write!(f, "matches the expected layout");
+        }
+        for d in &self.missing {
+            writeln!(f, "missing: {}", describe_column(d))?;
+        }
+        for d in &self.extra {
+            writeln!(f, "extra: {}", describe_column(d))?;
+        }
+        for (expected, actual) in &self.changed {
+            writeln!(f, "changed: expected {}, found {}", describe_column(expected), describe_column(actual))?;
+        }
+        Ok(())
+    }
+}
+
+/// A record being imported, with its fields in the schema's order.
+struct SchemaRecord<'a> {
+    fields: &'a [FieldDescriptor],
+    values: Vec<Field>,
+}
+
+impl DBaseRecord for SchemaRecord<'_> {
+    fn describe(&self) -> Vec<FieldDescriptor> {
+        self.fields.to_vec()
+    }
+
+    fn to_record(&self) -> Vec<Field> {
+        self.values.clone()
+    }
+}
+
+/// A row that couldn't be imported.
+#[derive(Debug)]
+pub struct RowError {
+    /// The 1-based row number, not counting a CSV header row.
+    pub row: usize,
+    /// The column with the bad value, if the error is about a specific one.
+    pub column: Option<String>,
+    pub error: DBaseErrorKind,
+}
+
+impl Display for RowError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.column {
+            Some(column) => write!(f, "row {}, {column}: {}", self.row, self.error),
+            None => write!(f, "row {}: {}", self.row, self.error),
+        }
+    }
+}
+
+/// Import rows from a CSV or JSON Lines file into a new table written to `w`.
+///
+/// CSV files must have a header row naming their columns.
+/// Values use the format `FieldDescriptor::parse_str` accepts, and JSON values may also be
+/// numbers, booleans, or null. Columns missing from a row are left blank,
+/// and blank values in nullable fields are imported as null.
+/// Text the schema's codepage can't represent is transliterated.
+///
+/// Rows with values that don't parse or don't fit their field are skipped and returned as errors;
+/// the rest are written to the table as they're parsed.
+/// If no rows can be imported, the table is written with the schema's fields and no records.
+///
+/// Schemas with `Memo` fields are rejected, since there's no memo file to write their text to.
+pub fn import<R, W>(input: R, format: RowFormat, schema: &Schema, w: W) -> DBaseResult<(usize, Vec<RowError>)>
+    where R: io::Read,
+          W: io::Write + io::Seek,
+{
+    if let Some(f) = schema.fields.iter().find(|f| matches!(f.field_type, FieldType::Memo)) {
+        return Err(DBaseErrorKind::InvalidSchema(format!("can't import into Memo field '{}'", f.name)));
+    }
+
+    let rows: Vec<Result<Vec<(String, String)>, RowError>> = match format {
+        RowFormat::Csv => {
+            let mut csv = csv::Reader::from_reader(input);
+            let headers = csv.headers().map_err(io::Error::from)?.clone();
+            if let Some(name) = headers.iter().find(|&h| !schema.fields.iter().any(|f| f.name == h)) {
+                return Err(DBaseErrorKind::UnknownField(name.to_string()));
+            }
+            csv.records()
+                .enumerate()
+                .map(|(i, row)| match row {
+                    Ok(row) => Ok(zip_row(&headers, &row)),
+                    Err(err) => Err(RowError { row: i + 1, column: None, error: io::Error::from(err).into() }),
+                })
+                .collect()
+        }
+        RowFormat::JsonLines => {
+            let mut rows = Vec::new();
+            for line in io::BufReader::new(input).lines() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                rows.push(json_row(rows.len() + 1, &line));
+            }
+            rows
+        }
+    };
+
+    let layout = NullFlagLayout::new(&schema.fields)?;
+    let mut writer = TableWriter::new(w)?
+        .with_codepage(schema.codepage)
+        .with_transliteration()
+        .begin(schema.fields.clone())?;
+    let mut errors = Vec::new();
+    for (i, row) in rows.into_iter().enumerate() {
+        match row.and_then(|row| parse_row(i + 1, &row, schema, &layout)) {
+            Ok(values) => writer.write_record(&SchemaRecord { fields: &schema.fields, values })?,
+            Err(err) => errors.push(err),
+        }
+    }
+
+    let n_records = writer.n_records();
+    writer.finish()?;
+    Ok((n_records, errors))
+}
+
+/// Pair a CSV row's values with their column names.
+fn zip_row(headers: &csv::StringRecord, row: &csv::StringRecord) -> Vec<(String, String)> {
+    headers.iter().zip(row.iter()).map(|(h, v)| (h.to_string(), v.to_string())).collect()
+}
+
+/// Convert a JSON object's values to strings, as if they'd come from a CSV file.
+fn json_row(row: usize, line: &str) -> Result<Vec<(String, String)>, RowError> {
+    let error = |column: Option<&str>, error: DBaseErrorKind| {
+        RowError { row, column: column.map(str::to_string), error }
+    };
+    let object: serde_json::Map<String, serde_json::Value> = serde_json::from_str(line)
+        .map_err(|e| error(None, io::Error::from(e).into()))?;
+
+    object.into_iter()
+        .map(|(name, value)| {
+            let value = match value {
+                serde_json::Value::String(s) => s,
+                serde_json::Value::Number(n) => n.to_string(),
+                serde_json::Value::Bool(b) => if b { "T" } else { "F" }.to_string(),
+                serde_json::Value::Null => String::new(),
+                v => return Err(error(Some(&name), DBaseErrorKind::InvalidValue(v.to_string()))),
+            };
+            Ok((name, value))
+        })
+        .collect()
+}
+
+/// Parse a row's values into fields in the schema's order,
+/// and check that they can be written.
+fn parse_row(row: usize, values: &[(String, String)], schema: &Schema, layout: &NullFlagLayout)
+    -> Result<Vec<Field>, RowError>
+{
+    let error = |column: Option<&str>, error: DBaseErrorKind| {
+        RowError { row, column: column.map(str::to_string), error }
+    };
+    if let Some((name, _)) = values.iter().find(|(n, _)| !schema.fields.iter().any(|f| &f.name == n)) {
+        return Err(error(Some(name), DBaseErrorKind::UnknownField(name.clone())));
+    }
+
+    let mut fields = schema.fields.iter()
+        .map(|f| {
+            let value = values.iter().find(|(n, _)| n == &f.name).map_or("", |(_, v)| v.as_str());
+            match f.field_type {
+                FieldType::NullFlags => Ok(Field::NullFlags(Vec::new())),
+                _ if value.is_empty() && f.is_nullable() => Ok(Field::Null),
+                _ => f.parse_str(value).map_err(|e| error(Some(&f.name), e)),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    transliterate_fields(&mut fields, schema.codepage);
+
+    // Write each field to a scratch buffer, so one that doesn't fit fails this row, not the table.
+    let mut buf = Vec::new();
+    let mut flags = vec![0; layout.range.as_ref().map_or(0, |r| r.len())];
+    for (i, (d, f)) in schema.fields.iter().zip(&fields).enumerate() {
+        buf.clear();
+        layout.write_field(i, d, f, schema.codepage, &mut buf, &mut flags)
+            .map_err(|e| error(Some(&d.name), e))?;
+    }
+    Ok(fields)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+    use chrono::NaiveDate;
+    use crate::xbase::{memo, DBaseRecord, Decimal, FieldDescriptor, TableEditor, TableWriter};
+    use super::*;
+
+    struct Payout(&'static str, Option<NaiveDate>, i64);
+
+    impl DBaseRecord for Payout {
+        fn describe(&self) -> Vec<FieldDescriptor> {
+            let field = |name: &str, field_type, length, decimal_count| FieldDescriptor {
+                name: name.to_string(),
+                field_type,
+                length,
+                decimal_count,
+                work_area_id: 0,
+                example: 1,
+            };
+            vec![
+                field("NAME", FieldType::Character, 12, 0),
+                field("PAID_ON", FieldType::Date, 8, 0),
+                field("AMOUNT", FieldType::Numeric, 7, 2),
+            ]
+        }
+
+        fn to_record(&self) -> Vec<Field> {
+            vec![
+                Field::Character(self.0.to_string()),
+                Field::Date(self.1),
+                Field::Numeric(Some(Decimal { mantissa: self.2, exponent: 2 })),
+            ]
+        }
+    }
+
+    #[test]
+    fn export_csv_and_json_lines() {
+        let mut data = Vec::new();
+        TableWriter::new(&mut data).unwrap().write_records(&[
+            Payout("Doe, Jo", NaiveDate::from_ymd_opt(2023, 7, 14), 12550),
+            Payout("Roe \"Rip\"", None, 0),
+        ]).unwrap();
+        TableEditor::new(Cursor::new(&mut data)).unwrap().delete(2).unwrap();
+
+        let table = TableReader::new(Cursor::new(&data)).unwrap();
+        let mut out = Vec::new();
+        assert_eq!(export(table, RowFormat::Csv, None, true, &mut out).unwrap(), 2);
+        assert_eq!(String::from_utf8(out).unwrap(), "NAME,PAID_ON,AMOUNT,_DELETED\n\
+            \"Doe, Jo\",20230714,125.50,F\n\
+            \"Roe \"\"Rip\"\"\",,0.00,T\n");
+
+        let table = TableReader::new(Cursor::new(&data)).unwrap();
+        let columns = ["AMOUNT".to_string(), "NAME".to_string()];
+        let mut out = Vec::new();
+        assert_eq!(export(table, RowFormat::JsonLines, Some(&columns), false, &mut out).unwrap(), 1);
+        assert_eq!(String::from_utf8(out).unwrap(), "{\"AMOUNT\":\"125.50\",\"NAME\":\"Doe, Jo\"}\n");
+    }
+
+    #[test]
+    fn export_memo_text_from_the_memo_file() {
+        struct Note(&'static str, &'static str);
+
+        impl DBaseRecord for Note {
+            fn describe(&self) -> Vec<FieldDescriptor> {
+                let field = |name: &str, field_type, length| FieldDescriptor {
+                    name: name.to_string(), field_type, length, decimal_count: 0, work_area_id: 0, example: 1,
+                };
+                vec![field("NAME", FieldType::Character, 10), field("NOTES", FieldType::Memo, 10)]
+            }
+
+            fn to_record(&self) -> Vec<Field> {
+                vec![Field::Character(self.0.to_string()), Field::MemoText(self.1.to_string())]
+            }
+        }
+
+        let path = std::env::temp_dir().join(format!("export-memo-{}.dbf", std::process::id()));
+        let memo_path = path.with_extension(memo::MemoFormat::DBase.extension());
+        TableWriter::new(fs::File::create(&path).unwrap()).unwrap()
+            .with_memo(memo::MemoFormat::DBase, fs::File::create(&memo_path).unwrap())
+            .write_records(&[Note("Jo", "Early, please"), Note("Rip", "")]).unwrap();
+
+        let mut out = Vec::new();
+        export(crate::xbase::try_from_path(&path).unwrap(), RowFormat::Csv, None, false, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "NAME,NOTES\nJo,\"Early, please\"\nRip,\n");
+
+        // Without the memo file, only the block numbers are left.
+        fs::remove_file(&memo_path).unwrap();
+        let mut out = Vec::new();
+        export(crate::xbase::try_from_path(&path).unwrap(), RowFormat::Csv, None, false, &mut out).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "NAME,NOTES\nJo,1\nRip,\n");
+    }
+
+    #[test]
+    fn schema_check_lists_differences() {
+        let d = |name: &str, field_type: FieldType, length: usize| FieldDescriptor {
+            name: name.to_string(), field_type, length, decimal_count: 0, work_area_id: 0, example: 1,
+        };
+        let expected = [d("ID", FieldType::Character, 4), d("NAME", FieldType::Character, 10), d("NOTES", FieldType::Memo, 10)];
+        let fields = [d("NAME", FieldType::Character, 12), d("ID", FieldType::Character, 4), d("EXTRA", FieldType::Boolean, 1)];
+
+        let check = SchemaCheck::new(&expected, &["NOTES"], &fields);
+        assert!(check.missing.is_empty());
+        assert_eq!(check.extra.iter().map(|f| f.name.as_str()).collect::<Vec<_>>(), ["EXTRA"]);
+        assert_eq!(check.changed.len(), 1);
+        assert_eq!(check.changed[0].1.length, 12);
+
+        let check = SchemaCheck::new(&expected, &[], &fields[..2]);
+        assert_eq!(check.missing.iter().map(|f| f.name.as_str()).collect::<Vec<_>>(), ["NOTES"]);
+        assert!(!check.is_ok());
+        assert!(SchemaCheck::new(&fields, &[], &fields).is_ok());
+    }
+
+    #[test]
+    fn import_skips_bad_rows() {
+        let schema = Schema { fields: Payout("", None, 0).describe(), codepage: Codepage::default() };
+        let input = "AMOUNT,NAME,PAID_ON\n12.5,Doe,2023-07-14\n1,Roe,July\n99999,Poe,\n,Zoe,\n";
+
+        let mut data = Cursor::new(Vec::new());
+        let (n, errors) = import(input.as_bytes(), RowFormat::Csv, &schema, &mut data).unwrap();
+        assert_eq!(n, 2);
+        assert_eq!(errors.iter().map(|e| e.to_string()).collect::<Vec<_>>(), [
+            "row 2, PAID_ON: unable to parse date as YYYYMMDD: July",
+            "row 3, AMOUNT: data exceeds field width: '99999.00'",
+        ]);
+
+        data.set_position(0);
+        let records = TableReader::new(data).unwrap().read_all::<Record>().unwrap();
+        assert_eq!(records.iter().map(|r| r.get("NAME").unwrap().to_string()).collect::<Vec<_>>(), ["Doe", "Zoe"]);
+        assert!(matches!(records[1].get("AMOUNT"), Some(Field::Numeric(None))));
+
+        let mut fields = schema.fields.clone();
+        fields[0].field_type = FieldType::Memo;
+        let schema = Schema { fields, codepage: Codepage::default() };
+        assert!(matches!(import(input.as_bytes(), RowFormat::Csv, &schema, Cursor::new(Vec::new())),
+            Err(DBaseErrorKind::InvalidSchema(_))));
+    }
+}
@@ -0,0 +1,355 @@
+//! Checking and repairing damaged table headers.
+//!
+//! When the legacy app appends a record, it writes the record and a new end-of-file marker,
+//! then updates the header's record count. If the machine loses power in between,
+//! the header no longer matches the data, and a partly written record may be left at the end.
+//! `TableReader` trusts the header, so it misses records or reads garbage from such a table.
+//!
+//! `check_table` compares the header against the rest of the file:
+//! the field descriptors and their terminator, the header and record lengths,
+//! the number of complete records, and the `0x1a` end-of-file marker.
+//! `repair_table` writes a copy with a corrected header and every complete record it found.
+
+use std::fmt::{Display, Formatter};
+
+use chrono::{Datelike, NaiveDate};
+
+use crate::xbase::{dbase_header, DBaseErrorKind, DBaseResult, FieldDescriptor, HeaderYear, FOXPRO_BACKLINK_SIZE};
+
+/// Ends the field descriptors.
+const TERMINATOR: u8 = 0x0d;
+/// Follows the last record.
+const EOF: u8 = 0x1a;
+/// The first byte of a record that isn't deleted.
+const VALID: u8 = 0x20;
+/// The first byte of a deleted record.
+const DELETED: u8 = 0x2a;
+
+/// A disagreement between a table's header and the rest of the file.
+#[derive(Debug)]
+pub enum HeaderProblem {
+    /// The last updated date isn't a real date.
+    InvalidLastUpdated { year: u16, month: u8, day: u8 },
+    /// This field descriptor can't be read, so it and any after it are dropped.
+    InvalidDescriptor { index: usize, error: String },
+    /// The field descriptors aren't followed by the `0x0d` terminator.
+    MissingTerminator,
+    /// The header length doesn't fit the field descriptors.
+    HeaderLength { stated: usize, actual: usize },
+    /// The record length isn't the sum of the field lengths plus the deleted marker.
+    RecordLength { stated: usize, actual: usize },
+    /// The record count doesn't match the number of complete records in the file.
+    RecordCount { stated: usize, actual: usize },
+    /// The last record isn't followed by the `0x1a` end-of-file marker.
+    MissingEof,
+    /// There's data after the last complete record, such as a partly written record.
+    TrailingData { bytes: usize },
+}
+
+impl Display for HeaderProblem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HeaderProblem::InvalidLastUpdated { year, month, day } => {
+                write!(f, "invalid last updated date: {year:04}-{month:02}-{day:02}")
+            }
+            HeaderProblem::InvalidDescriptor { index, error } => {
+                write!(f, "field descriptor {index} can't be read: {error}")
+            }
+            HeaderProblem::MissingTerminator => write!(f, "field descriptors aren't terminated by 0x0d"),
+            HeaderProblem::HeaderLength { stated, actual } => {
+                write!(f, "header length is {stated} bytes, but the field descriptors need {actual}")
+            }
+            HeaderProblem::RecordLength { stated, actual } => {
+                write!(f, "record length is {stated} bytes, but the fields need {actual}")
+            }
+            HeaderProblem::RecordCount { stated, actual } => {
+                write!(f, "header says there are {stated} records, but the file has {actual} complete records")
+            }
+            HeaderProblem::MissingEof => write!(f, "the last record isn't followed by the 0x1a end-of-file marker"),
+            HeaderProblem::TrailingData { bytes } => {
+                write!(f, "{bytes} bytes after the last complete record")
+            }
+        }
+    }
+}
+
+/// The result of checking a table: its problems, and the layout found in the file.
+#[derive(Debug)]
+pub struct TableCheck {
+    pub problems: Vec<HeaderProblem>,
+    /// The fields whose descriptors could be read.
+    pub fields: Vec<FieldDescriptor>,
+    /// The number of complete records, which a repair keeps.
+    pub n_records: usize,
+    header_len: usize,
+    record_size: usize,
+}
+
+/// Check a table's header against the rest of the file, which is given in `data`.
+///
+/// Records are counted from the start of the data until one is incomplete
+/// or doesn't start with a deleted marker, regardless of the header's record count.
+/// Returns an error only if the file is too damaged to find any fields.
+pub fn check_table(data: &[u8]) -> DBaseResult<TableCheck> {
+    if data.len() < 32 {
+        return Err(DBaseErrorKind::DamagedTable(format!("the file is only {} bytes long", data.len())));
+    }
+
+    let view = dbase_header::View::new(&data[..32]);
+    let flags = view.flags().read();
+    let stated_header_len = view.n_header_bytes().read() as usize;
+    let stated_record_size = view.n_record_bytes().read() as usize;
+    let stated_records = view.n_records().read() as usize;
+    let year = HeaderYear::default().year(view.last_updated().year().read());
+    let month = view.last_updated().month().read();
+    let day = view.last_updated().day().read();
+
+    let mut problems = Vec::new();
+    if NaiveDate::from_ymd_opt(year, month as u32, day as u32).is_none() {
+        problems.push(HeaderProblem::InvalidLastUpdated { year: year as u16, month, day });
+    }
+
+    // Don't read record data as descriptors if the terminator is missing but the header length is sane.
+    let limit = if (33..=data.len()).contains(&stated_header_len) { stated_header_len } else { data.len() };
+    let mut fields = Vec::new();
+    let mut pos = 32;
+    let mut terminated = false;
+    // Once a descriptor can't be read, the rest are dropped too, but still looked past for the terminator.
+    let mut dropped = false;
+    while pos < limit {
+        if data[pos] == TERMINATOR {
+            terminated = true;
+            break;
+        }
+        if pos + 32 > limit {
+            break;
+        }
+        let bytes = &data[pos..pos + 32];
+        match FieldDescriptor::from_bytes(bytes) {
+            _ if dropped => {}
+            Ok(f) if f.length > 0 => fields.push(f),
+            // Zeros may be a Visual FoxPro backlink following a lost terminator.
+            _ if bytes.iter().all(|&b| b == 0) => break,
+            Ok(_) => {
+                problems.push(HeaderProblem::InvalidDescriptor { index: fields.len(), error: "zero length".to_string() });
+                dropped = true;
+            }
+            Err(err) => {
+                problems.push(HeaderProblem::InvalidDescriptor { index: fields.len(), error: err.to_string() });
+                dropped = true;
+            }
+        }
+        pos += 32;
+    }
+    if fields.is_empty() {
+        return Err(DBaseErrorKind::DamagedTable("no field descriptors can be read".to_string()));
+    }
+    if !terminated {
+        problems.push(HeaderProblem::MissingTerminator);
+    }
+
+    // The header may hold more after the terminator, such as Visual FoxPro's backlink.
+    let min_header_len = 32 * (fields.len() + 1) + 1;
+    let max_header_len = if dropped { data.len() } else { min_header_len + FOXPRO_BACKLINK_SIZE };
+    let header_len = if (min_header_len..=max_header_len).contains(&stated_header_len)
+        && stated_header_len <= data.len()
+    {
+        stated_header_len
+    } else {
+        let is_foxpro = matches!(flags, 0x30..=0x32);
+        let actual = min_header_len + if is_foxpro { FOXPRO_BACKLINK_SIZE } else { 0 };
+        problems.push(HeaderProblem::HeaderLength { stated: stated_header_len, actual });
+        actual
+    };
+
+    let fields_size = 1 + fields.iter().map(|f| f.length).sum::<usize>();
+    let record_size = if dropped && stated_record_size > fields_size {
+        stated_record_size
+    } else {
+        if stated_record_size != fields_size {
+            problems.push(HeaderProblem::RecordLength { stated: stated_record_size, actual: fields_size });
+        }
+        fields_size
+    };
+
+    let mut n_records = 0;
+    let mut end = header_len;
+    while end + record_size <= data.len() && matches!(data[end], VALID | DELETED) {
+        n_records += 1;
+        end += record_size;
+    }
+    if n_records != stated_records {
+        problems.push(HeaderProblem::RecordCount { stated: stated_records, actual: n_records });
+    }
+
+    match data.get(end..).unwrap_or_default() {
+        [] => problems.push(HeaderProblem::MissingEof),
+        [EOF] => {}
+        [EOF, rest @ ..] => problems.push(HeaderProblem::TrailingData { bytes: rest.len() }),
+        rest => {
+            problems.push(HeaderProblem::MissingEof);
+            problems.push(HeaderProblem::TrailingData { bytes: rest.len() });
+        }
+    }
+
+    Ok(TableCheck { problems, fields, n_records, header_len, record_size })
+}
+
+/// Check a table like `check_table`, and return a repaired copy of it.
+///
+/// The copy's header is corrected to describe the readable fields and the complete records,
+/// followed by those records and an end-of-file marker.
+/// Anything after the last complete record is dropped.
+/// If the last updated date is invalid, it's set to today, with the year written as years since 1900.
+///
+/// Tables with a field descriptor that can't be read aren't repaired,
+/// since the copy would lose that field and every one after it.
+pub fn repair_table(data: &[u8]) -> DBaseResult<(Vec<u8>, TableCheck)> {
+    let check = check_table(data)?;
+    if let Some(p) = check.problems.iter().find(|p| matches!(p, HeaderProblem::InvalidDescriptor { .. })) {
+        return Err(DBaseErrorKind::DamagedTable(format!("{p}, so the table can't be repaired")));
+    }
+    let descriptors_end = 32 * (check.fields.len() + 1);
+    let records_len = check.n_records * check.record_size;
+
+    let mut out = Vec::with_capacity(check.header_len + records_len + 1);
+    out.extend_from_slice(&data[..descriptors_end]);
+    out.push(TERMINATOR);
+    // Keep the rest of the header as it was, padding it if the file is cut short.
+    out.extend_from_slice(data.get(descriptors_end + 1..check.header_len).unwrap_or_default());
+    out.resize(check.header_len, 0);
+    out.extend_from_slice(&data[check.header_len..check.header_len + records_len]);
+    out.push(EOF);
+
+    let mut view = dbase_header::View::new(&mut out[..32]);
+    view.n_records_mut().write(check.n_records as u32);
+    view.n_header_bytes_mut().write(check.header_len as u16);
+    view.n_record_bytes_mut().write(check.record_size as u16);
+    if check.problems.iter().any(|p| matches!(p, HeaderProblem::InvalidLastUpdated { .. })) {
+        let today = chrono::Utc::now().naive_utc().date();
+        let mut last_updated = view.last_updated_mut();
+        last_updated.year_mut().write(HeaderYear::default().byte(today.year()));
+        last_updated.month_mut().write(today.month() as u8);
+        last_updated.day_mut().write(today.day() as u8);
+    }
+
+    Ok((out, check))
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::xbase::{DBaseRecord, Field, FieldType, TableReader, TableWriter};
+
+    struct Name(&'static str);
+
+    impl DBaseRecord for Name {
+        fn describe(&self) -> Vec<FieldDescriptor> {
+            vec![FieldDescriptor {
+                name: "NAME".to_string(),
+                field_type: FieldType::Character,
+                length: 8,
+                decimal_count: 0,
+                work_area_id: 0,
+                example: 1,
+            }]
+        }
+
+        fn to_record(&self) -> Vec<Field> {
+            vec![Field::Character(self.0.to_string())]
+        }
+    }
+
+    fn names(data: &[u8]) -> Vec<String> {
+        let mut records = TableReader::new(Cursor::new(data)).unwrap().records();
+        let mut names = Vec::new();
+        while let Some(record) = records.next() {
+            for f in record.unwrap() {
+                names.push(f.unwrap().value.to_string());
+            }
+        }
+        names
+    }
+
+    #[test]
+    fn repair_salvages_complete_records() {
+        let mut data = Vec::new();
+        TableWriter::new(&mut data).unwrap()
+            .write_records(&[Name("Ann"), Name("Bea"), Name("Cy")])
+            .unwrap();
+
+        let check = check_table(&data).unwrap();
+        assert!(check.problems.is_empty(), "{:?}", check.problems);
+        assert_eq!(repair_table(&data).unwrap().0, data);
+
+        // Lose power halfway through appending a fourth record, before the header's count is updated.
+        data.pop();
+        data.extend_from_slice(b" Dee");
+
+        let (fixed, check) = repair_table(&data).unwrap();
+        let problems = check.problems.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        assert_eq!(problems, [
+            "the last record isn't followed by the 0x1a end-of-file marker",
+            "4 bytes after the last complete record",
+        ]);
+        assert_eq!(names(&fixed), ["Ann", "Bea", "Cy"]);
+
+        // Finish the record, but still lose the header update.
+        data.extend_from_slice(b"     \x1a");
+        let (fixed, check) = repair_table(&data).unwrap();
+        assert!(matches!(check.problems[..], [HeaderProblem::RecordCount { stated: 3, actual: 4 }]));
+        assert_eq!(names(&fixed), ["Ann", "Bea", "Cy", "Dee"]);
+        assert!(check_table(&fixed).unwrap().problems.is_empty());
+    }
+
+    #[test]
+    fn dropped_descriptors_keep_the_record_length() {
+        struct Resident(&'static str, &'static str);
+
+        impl DBaseRecord for Resident {
+            fn describe(&self) -> Vec<FieldDescriptor> {
+                let mut city = Name("").describe().remove(0);
+                city.name = "CITY".to_string();
+                city.length = 12;
+                vec![Name("").describe().remove(0), city]
+            }
+
+            fn to_record(&self) -> Vec<Field> {
+                vec![Field::Character(self.0.to_string()), Field::Character(self.1.to_string())]
+            }
+        }
+
+        let mut data = Vec::new();
+        TableWriter::new(&mut data).unwrap()
+            .write_records(&[Resident("Ann", "Reno"), Resident("Bea", "Tulsa")])
+            .unwrap();
+        // Corrupt the CITY descriptor's type.
+        data[32 * 2 + 11] = b'!';
+
+        let check = check_table(&data).unwrap();
+        assert!(matches!(check.problems[..], [HeaderProblem::InvalidDescriptor { index: 1, .. }]), "{:?}", check.problems);
+        assert_eq!(check.fields.len(), 1);
+        assert_eq!(check.n_records, 2);
+        assert!(matches!(repair_table(&data), Err(DBaseErrorKind::DamagedTable(_))));
+    }
+
+    #[test]
+    fn check_finds_bad_lengths() {
+        let mut data = Vec::new();
+        TableWriter::new(&mut data).unwrap().write_records(&[Name("Ann")]).unwrap();
+        let mut view = dbase_header::View::new(&mut data[..32]);
+        view.n_header_bytes_mut().write(7);
+        view.n_record_bytes_mut().write(300);
+
+        let (fixed, check) = repair_table(&data).unwrap();
+        assert!(matches!(check.problems[..], [
+            HeaderProblem::HeaderLength { stated: 7, actual: 65 },
+            HeaderProblem::RecordLength { stated: 300, actual: 9 },
+        ]));
+        assert_eq!(names(&fixed), ["Ann"]);
+
+        assert!(check_table(&data[..20]).is_err());
+    }
+}
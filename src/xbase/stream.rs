@@ -0,0 +1,150 @@
+//! Reading tables without blocking the async runtime.
+//!
+//! `AsyncTableReader` reads the header and records from a tokio `AsyncRead`,
+//! such as a `tokio::fs::File`, and yields records as a `Stream`,
+//! so the server can load a large table while it keeps answering requests.
+//! Fields are decoded exactly as `TableReader` decodes them.
+//!
+//! Memo files aren't read, so `Memo` fields are returned as block numbers.
+
+use std::io;
+use std::path::Path;
+
+use chrono::NaiveDate;
+use futures_util::{stream, Stream};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::xbase::{dbase_header, DBaseResult, DBaseTable, FieldIterator, FromRecord, SchemaWarning, TableReader};
+
+/// The first byte of a deleted record.
+const DELETED: u8 = 0x2a;
+
+/// Reads a DBF table from an async source, skipping deleted records.
+pub struct AsyncTableReader<R> {
+    table: Box<DBaseTable>,
+    inner: R,
+    record_size: usize,
+    /// Number of records read so far, including deleted ones.
+    record_number: usize,
+}
+
+/// Open the DBF table at the given path for async reading.
+pub async fn try_from_path<P: AsRef<Path>>(path: P)
+    -> DBaseResult<AsyncTableReader<tokio::io::BufReader<tokio::fs::File>>>
+{
+    let file = tokio::fs::File::open(path).await?;
+    AsyncTableReader::new(tokio::io::BufReader::new(file)).await
+}
+
+impl<R> AsyncTableReader<R>
+    where R: AsyncRead + Unpin
+{
+    /// Create a new AsyncTableReader, reading the table header from R.
+    pub async fn new(mut reader: R) -> DBaseResult<Self> {
+        // The header gives its own length, so read all of it, then parse it like `TableReader`.
+        let mut header = vec![0; 32];
+        reader.read_exact(&mut header).await?;
+        let n_header_bytes = dbase_header::View::new(&header[..]).n_header_bytes().read() as usize;
+        header.resize(n_header_bytes.max(header.len()), 0);
+        reader.read_exact(&mut header[32..]).await?;
+
+        let table = TableReader::new(io::Cursor::new(header))?.table;
+        let record_size = 1 + table.fields.iter().map(|f| f.length).sum::<usize>();
+
+        Ok(AsyncTableReader {
+            table,
+            inner: reader,
+            record_size,
+            record_number: 0,
+        })
+    }
+
+    /// Get the number of records the DBF table holds, including deleted ones.
+    pub fn n_records(&self) -> usize {
+        self.table.n_records
+    }
+
+    /// Get the date the table was last updated, according to its header.
+    pub fn last_updated(&self) -> NaiveDate {
+        self.table.last_updated
+    }
+
+    /// Stream every record that isn't deleted as a `T`, like `TableReader::read_all_lenient`,
+    /// along with the schema mismatches found in that record.
+    ///
+    /// The stream ends after the first error.
+    pub fn records_lenient<T: FromRecord>(self) -> impl Stream<Item = DBaseResult<(T, Vec<SchemaWarning>)>> {
+        self.stream(|fields| {
+            let mut warnings = Vec::new();
+            T::from_record_lenient(fields, &mut warnings).map(|value| (value, warnings))
+        })
+    }
+
+    /// Stream the records that aren't deleted, converting each with `read`.
+    fn stream<T, F>(self, read: F) -> impl Stream<Item = DBaseResult<T>>
+        where F: FnMut(FieldIterator<'_>) -> DBaseResult<T>
+    {
+        stream::try_unfold((self, read), |(mut reader, mut read)| async move {
+            let Some(buf) = reader.next_record().await? else {
+                return Ok(None);
+            };
+            let value = read(FieldIterator {
+                table: &reader.table,
+                memo: None,
+                record_number: reader.record_number,
+                buf,
+                cur_field: 0,
+                cur_byte: 1,
+            })?;
+            Ok(Some((value, (reader, read))))
+        })
+    }
+
+    /// Read the next record that isn't deleted, or None if there are no more records.
+    async fn next_record(&mut self) -> DBaseResult<Option<Vec<u8>>> {
+        let mut buf = vec![0; self.record_size];
+        loop {
+            // Deleted records count toward n_records, so stop based on every record read.
+            if self.record_number == self.table.n_records {
+                return Ok(None);
+            }
+            self.inner.read_exact(&mut buf).await?;
+            self.record_number += 1;
+            if buf[0] != DELETED {
+                return Ok(Some(buf));
+            }
+            log::info!("Record {} is deleted", self.record_number);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use futures_util::TryStreamExt;
+
+    use super::*;
+    use crate::xbase::record::Record;
+
+    #[tokio::test]
+    async fn streams_the_same_records_as_read_all() {
+        for fixture in [
+            &include_bytes!("../../tests/fixtures/dbase.dbf")[..],
+            &include_bytes!("../../tests/fixtures/foxpro.dbf")[..],
+            &include_bytes!("../../tests/fixtures/legacy.dbf")[..],
+        ] {
+            let expected = TableReader::new(io::Cursor::new(fixture)).unwrap().read_all::<Record>().unwrap();
+
+            let reader = AsyncTableReader::new(fixture).await.unwrap();
+            let records: Vec<(Record, _)> = reader.records_lenient().try_collect().await.unwrap();
+            assert!(records.iter().all(|(_, warnings)| warnings.is_empty()));
+            let records: Vec<Record> = records.into_iter().map(|(record, _)| record).collect();
+            assert_eq!(format!("{records:?}"), format!("{expected:?}"));
+        }
+
+        // A truncated table gives an error rather than a short list.
+        let truncated = &include_bytes!("../../tests/fixtures/legacy.dbf")[..200];
+        let reader = AsyncTableReader::new(truncated).await.unwrap();
+        let records: DBaseResult<Vec<(Record, _)>> = reader.records_lenient().try_collect().await;
+        assert!(records.is_err());
+    }
+}